@@ -0,0 +1,93 @@
+-- Store money as integer minor units (cents) instead of REAL.
+--
+-- accounts.balance, journal_entries.debit_amount and journal_entries.credit_amount
+-- keep their names but now hold whole cents; the application converts to and from
+-- rust_decimal::Decimal with a fixed scale of 2. Existing REAL values are rounded to
+-- the nearest cent during conversion.
+
+-- The balance triggers reference the columns being rebuilt, so drop them first
+DROP TRIGGER IF EXISTS update_account_balance_insert;
+DROP TRIGGER IF EXISTS update_account_balance_update;
+DROP TRIGGER IF EXISTS update_account_balance_delete;
+
+-- accounts is referenced by journal_entries and by itself, so convert the balance
+-- column in place instead of rebuilding the table
+ALTER TABLE accounts ADD COLUMN balance_minor INTEGER NOT NULL DEFAULT 0;
+UPDATE accounts SET balance_minor = CAST(ROUND(balance * 100) AS INTEGER);
+ALTER TABLE accounts DROP COLUMN balance;
+ALTER TABLE accounts RENAME COLUMN balance_minor TO balance;
+
+-- journal_entries carries CHECK constraints on the amount columns, so rebuild it
+CREATE TABLE journal_entries_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    transaction_id INTEGER NOT NULL,
+    account_id INTEGER NOT NULL,
+    debit_amount INTEGER NOT NULL DEFAULT 0,
+    credit_amount INTEGER NOT NULL DEFAULT 0,
+    description TEXT,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (transaction_id) REFERENCES transactions(id) ON DELETE CASCADE,
+    FOREIGN KEY (account_id) REFERENCES accounts(id),
+    CHECK (debit_amount >= 0 AND credit_amount >= 0),
+    CHECK (NOT (debit_amount > 0 AND credit_amount > 0))
+);
+
+INSERT INTO journal_entries_new (id, transaction_id, account_id, debit_amount, credit_amount, description, created_at)
+SELECT
+    id,
+    transaction_id,
+    account_id,
+    CAST(ROUND(debit_amount * 100) AS INTEGER),
+    CAST(ROUND(credit_amount * 100) AS INTEGER),
+    description,
+    created_at
+FROM journal_entries;
+
+DROP TABLE journal_entries;
+ALTER TABLE journal_entries_new RENAME TO journal_entries;
+
+CREATE INDEX idx_journal_entries_transaction ON journal_entries(transaction_id);
+CREATE INDEX idx_journal_entries_account ON journal_entries(account_id);
+
+-- Recompute cached balances from the converted lines so they match to the cent
+UPDATE accounts
+SET balance = COALESCE((
+    SELECT SUM(je.debit_amount) - SUM(je.credit_amount)
+    FROM journal_entries je
+    WHERE je.account_id = accounts.id
+), 0);
+
+-- Recreate the balance triggers against the integer columns
+CREATE TRIGGER update_account_balance_insert
+    AFTER INSERT ON journal_entries
+BEGIN
+    UPDATE accounts
+    SET balance = balance + NEW.debit_amount - NEW.credit_amount,
+        updated_at = CURRENT_TIMESTAMP
+    WHERE id = NEW.account_id;
+END;
+
+CREATE TRIGGER update_account_balance_update
+    AFTER UPDATE ON journal_entries
+BEGIN
+    -- Reverse old entry
+    UPDATE accounts
+    SET balance = balance - OLD.debit_amount + OLD.credit_amount,
+        updated_at = CURRENT_TIMESTAMP
+    WHERE id = OLD.account_id;
+
+    -- Apply new entry
+    UPDATE accounts
+    SET balance = balance + NEW.debit_amount - NEW.credit_amount,
+        updated_at = CURRENT_TIMESTAMP
+    WHERE id = NEW.account_id;
+END;
+
+CREATE TRIGGER update_account_balance_delete
+    AFTER DELETE ON journal_entries
+BEGIN
+    UPDATE accounts
+    SET balance = balance - OLD.debit_amount + OLD.credit_amount,
+        updated_at = CURRENT_TIMESTAMP
+    WHERE id = OLD.account_id;
+END;
@@ -0,0 +1,5 @@
+-- Link reversal (storno) transactions to the transaction they reverse
+ALTER TABLE transactions ADD COLUMN reversal_of_id INTEGER REFERENCES transactions(id);
+
+-- A transaction can be reversed at most once
+CREATE UNIQUE INDEX idx_transactions_reversal_of ON transactions(reversal_of_id);
@@ -0,0 +1,140 @@
+-- Draft / posted / voided lifecycle for transactions.
+--
+-- Only posted transactions affect accounts.balance. Drafts can be edited freely;
+-- posted and voided transactions are immutable apart from the posted -> voided
+-- transition. Existing transactions were already affecting balances, so they are
+-- migrated as posted.
+
+ALTER TABLE transactions ADD COLUMN status TEXT NOT NULL DEFAULT 'posted'
+    CHECK (status IN ('draft', 'posted', 'voided'));
+ALTER TABLE transactions ADD COLUMN posted_at DATETIME;
+ALTER TABLE transactions ADD COLUMN voided_at DATETIME;
+
+UPDATE transactions SET posted_at = created_at;
+
+CREATE INDEX idx_transactions_status ON transactions(status);
+
+-- Balance triggers now only apply lines of posted transactions
+DROP TRIGGER update_account_balance_insert;
+DROP TRIGGER update_account_balance_update;
+DROP TRIGGER update_account_balance_delete;
+
+CREATE TRIGGER update_account_balance_insert
+    AFTER INSERT ON journal_entries
+    WHEN (SELECT status FROM transactions WHERE id = NEW.transaction_id) = 'posted'
+BEGIN
+    UPDATE accounts
+    SET balance = balance + NEW.debit_amount - NEW.credit_amount,
+        updated_at = CURRENT_TIMESTAMP
+    WHERE id = NEW.account_id;
+END;
+
+CREATE TRIGGER update_account_balance_update
+    AFTER UPDATE ON journal_entries
+    WHEN (SELECT status FROM transactions WHERE id = NEW.transaction_id) = 'posted'
+BEGIN
+    -- Reverse old entry
+    UPDATE accounts
+    SET balance = balance - OLD.debit_amount + OLD.credit_amount,
+        updated_at = CURRENT_TIMESTAMP
+    WHERE id = OLD.account_id;
+
+    -- Apply new entry
+    UPDATE accounts
+    SET balance = balance + NEW.debit_amount - NEW.credit_amount,
+        updated_at = CURRENT_TIMESTAMP
+    WHERE id = NEW.account_id;
+END;
+
+CREATE TRIGGER update_account_balance_delete
+    AFTER DELETE ON journal_entries
+    WHEN (SELECT status FROM transactions WHERE id = OLD.transaction_id) = 'posted'
+BEGIN
+    UPDATE accounts
+    SET balance = balance - OLD.debit_amount + OLD.credit_amount,
+        updated_at = CURRENT_TIMESTAMP
+    WHERE id = OLD.account_id;
+END;
+
+-- Posting applies every line of the transaction, voiding a posted transaction removes them
+CREATE TRIGGER update_account_balance_post
+    AFTER UPDATE OF status ON transactions
+    WHEN OLD.status != 'posted' AND NEW.status = 'posted'
+BEGIN
+    UPDATE accounts
+    SET balance = balance + (
+            SELECT COALESCE(SUM(je.debit_amount - je.credit_amount), 0)
+            FROM journal_entries je
+            WHERE je.transaction_id = NEW.id AND je.account_id = accounts.id
+        ),
+        updated_at = CURRENT_TIMESTAMP
+    WHERE id IN (SELECT account_id FROM journal_entries WHERE transaction_id = NEW.id);
+END;
+
+CREATE TRIGGER update_account_balance_unpost
+    AFTER UPDATE OF status ON transactions
+    WHEN OLD.status = 'posted' AND NEW.status != 'posted'
+BEGIN
+    UPDATE accounts
+    SET balance = balance - (
+            SELECT COALESCE(SUM(je.debit_amount - je.credit_amount), 0)
+            FROM journal_entries je
+            WHERE je.transaction_id = NEW.id AND je.account_id = accounts.id
+        ),
+        updated_at = CURRENT_TIMESTAMP
+    WHERE id IN (SELECT account_id FROM journal_entries WHERE transaction_id = NEW.id);
+END;
+
+-- Allowed transitions: draft -> posted, draft -> voided, posted -> voided
+CREATE TRIGGER transactions_status_transition
+    BEFORE UPDATE OF status ON transactions
+    WHEN NOT (
+        OLD.status = NEW.status
+        OR (OLD.status = 'draft' AND NEW.status IN ('posted', 'voided'))
+        OR (OLD.status = 'posted' AND NEW.status = 'voided')
+    )
+BEGIN
+    SELECT RAISE(ABORT, 'Invalid transaction status transition');
+END;
+
+-- Posted and voided transactions are immutable at the database layer
+CREATE TRIGGER transactions_immutable_update
+    BEFORE UPDATE ON transactions
+    WHEN OLD.status != 'draft' AND (
+        NEW.description IS NOT OLD.description
+        OR NEW.reference IS NOT OLD.reference
+        OR NEW.transaction_date IS NOT OLD.transaction_date
+        OR NEW.reversal_of_id IS NOT OLD.reversal_of_id
+    )
+BEGIN
+    SELECT RAISE(ABORT, 'Posted and voided transactions cannot be modified');
+END;
+
+CREATE TRIGGER transactions_immutable_delete
+    BEFORE DELETE ON transactions
+    WHEN OLD.status != 'draft'
+BEGIN
+    SELECT RAISE(ABORT, 'Posted and voided transactions cannot be deleted');
+END;
+
+CREATE TRIGGER journal_entries_immutable_insert
+    BEFORE INSERT ON journal_entries
+    WHEN (SELECT status FROM transactions WHERE id = NEW.transaction_id) != 'draft'
+BEGIN
+    SELECT RAISE(ABORT, 'Journal lines can only be added to draft transactions');
+END;
+
+CREATE TRIGGER journal_entries_immutable_update
+    BEFORE UPDATE ON journal_entries
+    WHEN (SELECT status FROM transactions WHERE id = OLD.transaction_id) != 'draft'
+        OR (SELECT status FROM transactions WHERE id = NEW.transaction_id) != 'draft'
+BEGIN
+    SELECT RAISE(ABORT, 'Journal lines of posted and voided transactions cannot be modified');
+END;
+
+CREATE TRIGGER journal_entries_immutable_delete
+    BEFORE DELETE ON journal_entries
+    WHEN (SELECT status FROM transactions WHERE id = OLD.transaction_id) != 'draft'
+BEGIN
+    SELECT RAISE(ABORT, 'Journal lines of posted and voided transactions cannot be deleted');
+END;
@@ -0,0 +1,17 @@
+-- Fiscal periods with soft and hard locks.
+--
+-- open:        anyone can post
+-- soft_closed: only admins can post
+-- hard_closed: nobody can post, and the period cannot be reopened
+CREATE TABLE periods (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE,
+    start_date DATE NOT NULL,
+    end_date DATE NOT NULL,
+    status TEXT NOT NULL DEFAULT 'open' CHECK (status IN ('open', 'soft_closed', 'hard_closed')),
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CHECK (start_date <= end_date)
+);
+
+CREATE INDEX idx_periods_dates ON periods(start_date, end_date);
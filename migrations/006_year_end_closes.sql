@@ -0,0 +1,19 @@
+-- Year-end closes of revenue and expense accounts into an equity account
+CREATE TABLE year_end_closes (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    fiscal_year INTEGER NOT NULL,
+    start_date DATE NOT NULL,
+    end_date DATE NOT NULL,
+    equity_account_id INTEGER NOT NULL,
+    transaction_id INTEGER NOT NULL,
+    reversal_transaction_id INTEGER,
+    status TEXT NOT NULL DEFAULT 'closed' CHECK (status IN ('closed', 'reopened')),
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    reopened_at DATETIME,
+    FOREIGN KEY (equity_account_id) REFERENCES accounts(id),
+    FOREIGN KEY (transaction_id) REFERENCES transactions(id),
+    FOREIGN KEY (reversal_transaction_id) REFERENCES transactions(id)
+);
+
+-- A fiscal year can only be closed once until it is reopened
+CREATE UNIQUE INDEX idx_year_end_closes_active ON year_end_closes(fiscal_year) WHERE status = 'closed';
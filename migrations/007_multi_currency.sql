@@ -0,0 +1,24 @@
+-- Multi-currency accounts and journal lines.
+--
+-- debit_amount and credit_amount stay in the functional currency, so balances and
+-- reports keep working on one currency. A NULL currency means the functional currency.
+-- Foreign-currency lines additionally record the transaction-currency amount (in
+-- minor units, on the same side as the functional amount) and the rate used, as
+-- functional units per one unit of the transaction currency.
+
+ALTER TABLE accounts ADD COLUMN currency TEXT CHECK (currency IS NULL OR length(currency) = 3);
+
+ALTER TABLE journal_entries ADD COLUMN currency TEXT CHECK (currency IS NULL OR length(currency) = 3);
+ALTER TABLE journal_entries ADD COLUMN currency_amount INTEGER CHECK (currency_amount IS NULL OR currency_amount >= 0);
+ALTER TABLE journal_entries ADD COLUMN exchange_rate TEXT;
+
+-- Rates are functional units per one unit of the currency, stored as canonical decimal text
+CREATE TABLE exchange_rates (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    currency TEXT NOT NULL CHECK (length(currency) = 3),
+    rate_date DATE NOT NULL,
+    rate TEXT NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (currency, rate_date)
+);
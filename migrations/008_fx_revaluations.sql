@@ -0,0 +1,40 @@
+-- Period-end revaluation of foreign-currency monetary accounts at the closing rate.
+--
+-- Each run posts one adjustment transaction on the revaluation date and its reversal on the
+-- first day of the next period, and keeps the per-account figures as the revaluation report.
+
+INSERT OR IGNORE INTO accounts (code, name, account_type) VALUES
+('4900', 'Unrealized FX Gains', 'revenue'),
+('5900', 'Unrealized FX Losses', 'expense');
+
+CREATE TABLE fx_revaluations (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    revaluation_date DATE NOT NULL UNIQUE,
+    reversal_date DATE NOT NULL CHECK (reversal_date > revaluation_date),
+    gain_account_id INTEGER NOT NULL,
+    loss_account_id INTEGER NOT NULL,
+    transaction_id INTEGER NOT NULL,
+    reversal_transaction_id INTEGER NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (gain_account_id) REFERENCES accounts(id),
+    FOREIGN KEY (loss_account_id) REFERENCES accounts(id),
+    FOREIGN KEY (transaction_id) REFERENCES transactions(id),
+    FOREIGN KEY (reversal_transaction_id) REFERENCES transactions(id)
+);
+
+-- Amounts are in minor units; currency_balance is in the account currency, the rest are functional
+CREATE TABLE fx_revaluation_lines (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    revaluation_id INTEGER NOT NULL,
+    account_id INTEGER NOT NULL,
+    currency TEXT NOT NULL,
+    currency_balance INTEGER NOT NULL,
+    exchange_rate TEXT NOT NULL,
+    book_balance INTEGER NOT NULL,
+    revalued_balance INTEGER NOT NULL,
+    adjustment INTEGER NOT NULL,
+    FOREIGN KEY (revaluation_id) REFERENCES fx_revaluations(id) ON DELETE CASCADE,
+    FOREIGN KEY (account_id) REFERENCES accounts(id)
+);
+
+CREATE INDEX idx_fx_revaluation_lines_revaluation ON fx_revaluation_lines(revaluation_id);
@@ -0,0 +1,236 @@
+-- Multiple legal entities (companies) in one database.
+--
+-- Accounts, transactions, periods, year-end closes and FX revaluations belong to a company.
+-- Existing books become company 1. Exchange rates stay shared across companies.
+--
+-- Tables whose unique constraints change are rebuilt by copying the rows aside, dropping the
+-- table and recreating it. Foreign keys are deferred so rows referencing the dropped table are
+-- checked again at commit, once the recreated table holds the same ids.
+
+PRAGMA defer_foreign_keys = ON;
+
+CREATE TABLE companies (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    code TEXT NOT NULL UNIQUE,
+    name TEXT NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+INSERT INTO companies (id, code, name) VALUES (1, 'MAIN', 'Main Company');
+
+-- Chart of accounts copied into every new company
+CREATE TABLE account_templates (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    code TEXT NOT NULL UNIQUE,
+    name TEXT NOT NULL,
+    account_type TEXT NOT NULL CHECK (account_type IN ('asset', 'liability', 'equity', 'revenue', 'expense')),
+    parent_code TEXT REFERENCES account_templates(code)
+);
+
+INSERT INTO account_templates (code, name, account_type) VALUES
+('1000', 'Assets', 'asset'),
+('1100', 'Current Assets', 'asset'),
+('1110', 'Cash', 'asset'),
+('1120', 'Accounts Receivable', 'asset'),
+('1130', 'Inventory', 'asset'),
+('1200', 'Fixed Assets', 'asset'),
+('1210', 'Equipment', 'asset'),
+('1220', 'Buildings', 'asset'),
+('2000', 'Liabilities', 'liability'),
+('2100', 'Current Liabilities', 'liability'),
+('2110', 'Accounts Payable', 'liability'),
+('2120', 'Short-term Loans', 'liability'),
+('2200', 'Long-term Liabilities', 'liability'),
+('2210', 'Long-term Debt', 'liability'),
+('3000', 'Equity', 'equity'),
+('3100', 'Owner''s Equity', 'equity'),
+('3200', 'Retained Earnings', 'equity'),
+('4000', 'Revenue', 'revenue'),
+('4100', 'Sales Revenue', 'revenue'),
+('4200', 'Service Revenue', 'revenue'),
+('4900', 'Unrealized FX Gains', 'revenue'),
+('5000', 'Expenses', 'expense'),
+('5100', 'Cost of Goods Sold', 'expense'),
+('5200', 'Operating Expenses', 'expense'),
+('5210', 'Rent Expense', 'expense'),
+('5220', 'Utilities Expense', 'expense'),
+('5230', 'Office Supplies', 'expense'),
+('5900', 'Unrealized FX Losses', 'expense');
+
+-- Accounts: codes are unique per company
+CREATE TABLE accounts_copy AS SELECT * FROM accounts;
+DROP TABLE accounts;
+
+CREATE TABLE accounts (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    company_id INTEGER NOT NULL,
+    code TEXT NOT NULL,
+    name TEXT NOT NULL,
+    account_type TEXT NOT NULL CHECK (account_type IN ('asset', 'liability', 'equity', 'revenue', 'expense')),
+    parent_id INTEGER,
+    currency TEXT CHECK (currency IS NULL OR length(currency) = 3),
+    balance INTEGER NOT NULL DEFAULT 0,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (company_id, code),
+    FOREIGN KEY (company_id) REFERENCES companies(id),
+    FOREIGN KEY (parent_id) REFERENCES accounts(id)
+);
+
+INSERT INTO accounts (id, company_id, code, name, account_type, parent_id, currency, balance, created_at, updated_at)
+SELECT id, 1, code, name, account_type, parent_id, currency, balance, created_at, updated_at
+FROM accounts_copy;
+
+DROP TABLE accounts_copy;
+
+CREATE INDEX idx_accounts_code ON accounts(code);
+CREATE INDEX idx_accounts_type ON accounts(account_type);
+
+-- Transactions: SQLite cannot add a REFERENCES column with a non-NULL default, and the table is
+-- too entangled with triggers to rebuild, so the company link is enforced by triggers instead
+ALTER TABLE transactions ADD COLUMN company_id INTEGER NOT NULL DEFAULT 1;
+
+CREATE INDEX idx_transactions_company ON transactions(company_id, transaction_date);
+
+CREATE TRIGGER transactions_company_exists
+    BEFORE INSERT ON transactions
+    WHEN NOT EXISTS (SELECT 1 FROM companies WHERE id = NEW.company_id)
+BEGIN
+    SELECT RAISE(ABORT, 'Company does not exist');
+END;
+
+CREATE TRIGGER transactions_company_immutable
+    BEFORE UPDATE OF company_id ON transactions
+    WHEN NEW.company_id IS NOT OLD.company_id
+BEGIN
+    SELECT RAISE(ABORT, 'Transactions cannot move between companies');
+END;
+
+-- Journal lines may only use accounts of the transaction's company
+CREATE TRIGGER journal_entries_same_company_insert
+    BEFORE INSERT ON journal_entries
+    WHEN (SELECT company_id FROM accounts WHERE id = NEW.account_id)
+        IS NOT (SELECT company_id FROM transactions WHERE id = NEW.transaction_id)
+BEGIN
+    SELECT RAISE(ABORT, 'Journal entry account belongs to another company');
+END;
+
+CREATE TRIGGER journal_entries_same_company_update
+    BEFORE UPDATE OF account_id ON journal_entries
+    WHEN (SELECT company_id FROM accounts WHERE id = NEW.account_id)
+        IS NOT (SELECT company_id FROM transactions WHERE id = NEW.transaction_id)
+BEGIN
+    SELECT RAISE(ABORT, 'Journal entry account belongs to another company');
+END;
+
+-- Periods: names are unique per company
+CREATE TABLE periods_copy AS SELECT * FROM periods;
+DROP TABLE periods;
+
+CREATE TABLE periods (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    company_id INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    start_date DATE NOT NULL,
+    end_date DATE NOT NULL,
+    status TEXT NOT NULL DEFAULT 'open' CHECK (status IN ('open', 'soft_closed', 'hard_closed')),
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CHECK (start_date <= end_date),
+    UNIQUE (company_id, name),
+    FOREIGN KEY (company_id) REFERENCES companies(id)
+);
+
+INSERT INTO periods (id, company_id, name, start_date, end_date, status, created_at, updated_at)
+SELECT id, 1, name, start_date, end_date, status, created_at, updated_at
+FROM periods_copy;
+
+DROP TABLE periods_copy;
+
+CREATE INDEX idx_periods_dates ON periods(company_id, start_date, end_date);
+
+-- Year-end closes: one active close per company and fiscal year
+CREATE TABLE year_end_closes_copy AS SELECT * FROM year_end_closes;
+DROP TABLE year_end_closes;
+
+CREATE TABLE year_end_closes (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    company_id INTEGER NOT NULL,
+    fiscal_year INTEGER NOT NULL,
+    start_date DATE NOT NULL,
+    end_date DATE NOT NULL,
+    equity_account_id INTEGER NOT NULL,
+    transaction_id INTEGER NOT NULL,
+    reversal_transaction_id INTEGER,
+    status TEXT NOT NULL DEFAULT 'closed' CHECK (status IN ('closed', 'reopened')),
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    reopened_at DATETIME,
+    FOREIGN KEY (company_id) REFERENCES companies(id),
+    FOREIGN KEY (equity_account_id) REFERENCES accounts(id),
+    FOREIGN KEY (transaction_id) REFERENCES transactions(id),
+    FOREIGN KEY (reversal_transaction_id) REFERENCES transactions(id)
+);
+
+INSERT INTO year_end_closes
+    (id, company_id, fiscal_year, start_date, end_date, equity_account_id, transaction_id,
+     reversal_transaction_id, status, created_at, reopened_at)
+SELECT id, 1, fiscal_year, start_date, end_date, equity_account_id, transaction_id,
+       reversal_transaction_id, status, created_at, reopened_at
+FROM year_end_closes_copy;
+
+DROP TABLE year_end_closes_copy;
+
+CREATE UNIQUE INDEX idx_year_end_closes_active ON year_end_closes(company_id, fiscal_year) WHERE status = 'closed';
+
+-- FX revaluations: one run per company and date. Lines are rebuilt first since they cascade.
+CREATE TABLE fx_revaluation_lines_copy AS SELECT * FROM fx_revaluation_lines;
+CREATE TABLE fx_revaluations_copy AS SELECT * FROM fx_revaluations;
+DROP TABLE fx_revaluation_lines;
+DROP TABLE fx_revaluations;
+
+CREATE TABLE fx_revaluations (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    company_id INTEGER NOT NULL,
+    revaluation_date DATE NOT NULL,
+    reversal_date DATE NOT NULL CHECK (reversal_date > revaluation_date),
+    gain_account_id INTEGER NOT NULL,
+    loss_account_id INTEGER NOT NULL,
+    transaction_id INTEGER NOT NULL,
+    reversal_transaction_id INTEGER NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (company_id, revaluation_date),
+    FOREIGN KEY (company_id) REFERENCES companies(id),
+    FOREIGN KEY (gain_account_id) REFERENCES accounts(id),
+    FOREIGN KEY (loss_account_id) REFERENCES accounts(id),
+    FOREIGN KEY (transaction_id) REFERENCES transactions(id),
+    FOREIGN KEY (reversal_transaction_id) REFERENCES transactions(id)
+);
+
+CREATE TABLE fx_revaluation_lines (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    revaluation_id INTEGER NOT NULL,
+    account_id INTEGER NOT NULL,
+    currency TEXT NOT NULL,
+    currency_balance INTEGER NOT NULL,
+    exchange_rate TEXT NOT NULL,
+    book_balance INTEGER NOT NULL,
+    revalued_balance INTEGER NOT NULL,
+    adjustment INTEGER NOT NULL,
+    FOREIGN KEY (revaluation_id) REFERENCES fx_revaluations(id) ON DELETE CASCADE,
+    FOREIGN KEY (account_id) REFERENCES accounts(id)
+);
+
+INSERT INTO fx_revaluations
+    (id, company_id, revaluation_date, reversal_date, gain_account_id, loss_account_id,
+     transaction_id, reversal_transaction_id, created_at)
+SELECT id, 1, revaluation_date, reversal_date, gain_account_id, loss_account_id,
+       transaction_id, reversal_transaction_id, created_at
+FROM fx_revaluations_copy;
+
+INSERT INTO fx_revaluation_lines SELECT * FROM fx_revaluation_lines_copy;
+
+DROP TABLE fx_revaluation_lines_copy;
+DROP TABLE fx_revaluations_copy;
+
+CREATE INDEX idx_fx_revaluation_lines_revaluation ON fx_revaluation_lines(revaluation_id);
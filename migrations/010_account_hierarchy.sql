@@ -0,0 +1,47 @@
+-- Link the seeded chart of accounts into a tree (1000 > 1100 > 1110, ...), both in the
+-- account templates and in every company that still has the seeded accounts unparented.
+
+UPDATE account_templates SET parent_code = CASE code
+    WHEN '1100' THEN '1000'
+    WHEN '1110' THEN '1100'
+    WHEN '1120' THEN '1100'
+    WHEN '1130' THEN '1100'
+    WHEN '1200' THEN '1000'
+    WHEN '1210' THEN '1200'
+    WHEN '1220' THEN '1200'
+    WHEN '2100' THEN '2000'
+    WHEN '2110' THEN '2100'
+    WHEN '2120' THEN '2100'
+    WHEN '2200' THEN '2000'
+    WHEN '2210' THEN '2200'
+    WHEN '3100' THEN '3000'
+    WHEN '3200' THEN '3000'
+    WHEN '4100' THEN '4000'
+    WHEN '4200' THEN '4000'
+    WHEN '4900' THEN '4000'
+    WHEN '5100' THEN '5000'
+    WHEN '5200' THEN '5000'
+    WHEN '5210' THEN '5200'
+    WHEN '5220' THEN '5200'
+    WHEN '5230' THEN '5200'
+    WHEN '5900' THEN '5000'
+END;
+
+-- Only link accounts to a parent of the same type, so renamed or repurposed codes are left alone
+UPDATE accounts
+SET parent_id = (
+    SELECT parent.id
+    FROM account_templates t
+    JOIN accounts parent ON parent.company_id = accounts.company_id AND parent.code = t.parent_code
+    WHERE t.code = accounts.code AND parent.account_type = accounts.account_type
+),
+    updated_at = CURRENT_TIMESTAMP
+WHERE parent_id IS NULL
+  AND EXISTS (
+    SELECT 1
+    FROM account_templates t
+    JOIN accounts parent ON parent.company_id = accounts.company_id AND parent.code = t.parent_code
+    WHERE t.code = accounts.code AND parent.account_type = accounts.account_type
+);
+
+CREATE INDEX idx_accounts_parent ON accounts(parent_id);
@@ -0,0 +1,3 @@
+-- Archived accounts stay in the chart and in reports but take no new postings
+ALTER TABLE accounts ADD COLUMN is_active BOOLEAN NOT NULL DEFAULT 1;
+ALTER TABLE accounts ADD COLUMN archived_at DATETIME;
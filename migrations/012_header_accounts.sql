@@ -0,0 +1,33 @@
+-- Header (summary) accounts only group other accounts and cannot take journal lines
+
+ALTER TABLE accounts ADD COLUMN is_header BOOLEAN NOT NULL DEFAULT 0;
+ALTER TABLE account_templates ADD COLUMN is_header BOOLEAN NOT NULL DEFAULT 0;
+
+UPDATE account_templates SET is_header = 1
+WHERE code IN ('1000', '1100', '1200', '2000', '2100', '2200', '3000', '4000', '5000', '5200');
+
+-- Seeded headers that were already posted to stay postable
+UPDATE accounts SET is_header = 1, updated_at = CURRENT_TIMESTAMP
+WHERE code IN (SELECT code FROM account_templates WHERE is_header = 1)
+  AND NOT EXISTS (SELECT 1 FROM journal_entries WHERE account_id = accounts.id);
+
+CREATE TRIGGER journal_entries_header_insert
+    BEFORE INSERT ON journal_entries
+    WHEN (SELECT is_header FROM accounts WHERE id = NEW.account_id)
+BEGIN
+    SELECT RAISE(ABORT, 'Header accounts cannot take journal entries');
+END;
+
+CREATE TRIGGER journal_entries_header_update
+    BEFORE UPDATE OF account_id ON journal_entries
+    WHEN (SELECT is_header FROM accounts WHERE id = NEW.account_id)
+BEGIN
+    SELECT RAISE(ABORT, 'Header accounts cannot take journal entries');
+END;
+
+CREATE TRIGGER accounts_header_without_lines
+    BEFORE UPDATE OF is_header ON accounts
+    WHEN NEW.is_header AND EXISTS (SELECT 1 FROM journal_entries WHERE account_id = NEW.id)
+BEGIN
+    SELECT RAISE(ABORT, 'Accounts with journal entries cannot become headers');
+END;
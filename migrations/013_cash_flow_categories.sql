@@ -0,0 +1,33 @@
+-- Where an account's balance changes land on the statement of cash flows. Cash accounts are
+-- what the statement explains; revenue and expense accounts stay unclassified because they
+-- reach it through net income.
+
+ALTER TABLE accounts ADD COLUMN cash_flow_category TEXT
+    CHECK (cash_flow_category IN ('cash', 'operating', 'investing', 'financing'));
+ALTER TABLE account_templates ADD COLUMN cash_flow_category TEXT
+    CHECK (cash_flow_category IN ('cash', 'operating', 'investing', 'financing'));
+
+UPDATE account_templates SET cash_flow_category = CASE code
+    WHEN '1110' THEN 'cash'
+    WHEN '1120' THEN 'operating'
+    WHEN '1130' THEN 'operating'
+    WHEN '1210' THEN 'investing'
+    WHEN '1220' THEN 'investing'
+    WHEN '2110' THEN 'operating'
+    WHEN '2120' THEN 'financing'
+    WHEN '2210' THEN 'financing'
+    WHEN '3100' THEN 'financing'
+    WHEN '3200' THEN 'financing'
+END;
+
+-- Classify seeded accounts that still have their template's type
+UPDATE accounts
+SET cash_flow_category = (
+    SELECT t.cash_flow_category FROM account_templates t
+    WHERE t.code = accounts.code AND t.account_type = accounts.account_type
+),
+    updated_at = CURRENT_TIMESTAMP
+WHERE EXISTS (
+    SELECT 1 FROM account_templates t
+    WHERE t.code = accounts.code AND t.account_type = accounts.account_type AND t.cash_flow_category IS NOT NULL
+);
@@ -0,0 +1,68 @@
+-- Customers and vendors, kept as subledgers of the control accounts they post through
+
+CREATE TABLE parties (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    company_id INTEGER NOT NULL REFERENCES companies(id),
+    code TEXT NOT NULL,
+    name TEXT NOT NULL,
+    party_type TEXT NOT NULL CHECK (party_type IN ('customer', 'vendor')),
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE(company_id, code)
+);
+
+-- Control accounts carry one line per party movement; every line names its party
+ALTER TABLE accounts ADD COLUMN control_party_type TEXT
+    CHECK (control_party_type IN ('customer', 'vendor'));
+ALTER TABLE account_templates ADD COLUMN control_party_type TEXT
+    CHECK (control_party_type IN ('customer', 'vendor'));
+
+UPDATE account_templates SET control_party_type = CASE code
+    WHEN '1120' THEN 'customer'
+    WHEN '2110' THEN 'vendor'
+END;
+
+UPDATE accounts
+SET control_party_type = (
+    SELECT t.control_party_type FROM account_templates t
+    WHERE t.code = accounts.code AND t.account_type = accounts.account_type
+),
+    updated_at = CURRENT_TIMESTAMP
+WHERE EXISTS (
+    SELECT 1 FROM account_templates t
+    WHERE t.code = accounts.code AND t.account_type = accounts.account_type AND t.control_party_type IS NOT NULL
+);
+
+-- Lines written before parties existed keep no party and show as unassigned
+ALTER TABLE journal_entries ADD COLUMN party_id INTEGER REFERENCES parties(id);
+
+CREATE INDEX idx_journal_entries_party ON journal_entries(party_id);
+
+-- A party may only appear on a control account of its own company and party type
+CREATE TRIGGER journal_entries_party_insert
+    BEFORE INSERT ON journal_entries
+    WHEN NEW.party_id IS NOT NULL AND NOT EXISTS (
+        SELECT 1
+        FROM accounts a
+        JOIN parties p ON p.id = NEW.party_id
+        WHERE a.id = NEW.account_id
+          AND p.company_id = a.company_id
+          AND p.party_type = a.control_party_type
+    )
+BEGIN
+    SELECT RAISE(ABORT, 'Journal line party does not belong to the control account');
+END;
+
+CREATE TRIGGER journal_entries_party_update
+    BEFORE UPDATE OF account_id, party_id ON journal_entries
+    WHEN NEW.party_id IS NOT NULL AND NOT EXISTS (
+        SELECT 1
+        FROM accounts a
+        JOIN parties p ON p.id = NEW.party_id
+        WHERE a.id = NEW.account_id
+          AND p.company_id = a.company_id
+          AND p.party_type = a.control_party_type
+    )
+BEGIN
+    SELECT RAISE(ABORT, 'Journal line party does not belong to the control account');
+END;
@@ -0,0 +1,3 @@
+-- When a receivable or payable line falls due, for aging reports; lines without one age
+-- from their transaction date
+ALTER TABLE journal_entries ADD COLUMN due_date DATE;
@@ -0,0 +1,67 @@
+-- Sales invoices. Issuing an invoice posts it straight to the ledger (receivable against revenue
+-- and sales tax); payments allocated to it decide whether it is open, partially paid or paid.
+
+INSERT INTO account_templates (code, name, account_type, parent_code, cash_flow_category) VALUES
+('2130', 'Sales Tax Payable', 'liability', '2100', 'operating');
+
+INSERT INTO accounts (company_id, code, name, account_type, parent_id, cash_flow_category)
+SELECT c.id, '2130', 'Sales Tax Payable', 'liability', parent.id, 'operating'
+FROM companies c
+LEFT JOIN accounts parent ON parent.company_id = c.id AND parent.code = '2100' AND parent.account_type = 'liability'
+WHERE NOT EXISTS (SELECT 1 FROM accounts a WHERE a.company_id = c.id AND a.code = '2130');
+
+-- Last number handed out per company and document type
+CREATE TABLE document_sequences (
+    company_id INTEGER NOT NULL REFERENCES companies(id),
+    document_type TEXT NOT NULL,
+    last_number INTEGER NOT NULL,
+    PRIMARY KEY (company_id, document_type)
+);
+
+-- Amounts are in minor units
+CREATE TABLE invoices (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    company_id INTEGER NOT NULL REFERENCES companies(id),
+    invoice_number TEXT NOT NULL,
+    party_id INTEGER NOT NULL REFERENCES parties(id),
+    invoice_date DATE NOT NULL,
+    due_date DATE NOT NULL CHECK (due_date >= invoice_date),
+    description TEXT,
+    receivable_account_id INTEGER NOT NULL REFERENCES accounts(id),
+    tax_account_id INTEGER REFERENCES accounts(id),
+    subtotal INTEGER NOT NULL,
+    tax_amount INTEGER NOT NULL,
+    total INTEGER NOT NULL CHECK (total > 0),
+    transaction_id INTEGER NOT NULL REFERENCES transactions(id),
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (company_id, invoice_number)
+);
+
+CREATE INDEX idx_invoices_party ON invoices(party_id);
+
+-- Quantities and tax rates are decimal strings; unit prices may carry more places than amounts
+CREATE TABLE invoice_lines (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    invoice_id INTEGER NOT NULL REFERENCES invoices(id) ON DELETE CASCADE,
+    line_number INTEGER NOT NULL,
+    description TEXT NOT NULL,
+    quantity TEXT NOT NULL,
+    unit_price TEXT NOT NULL,
+    account_id INTEGER NOT NULL REFERENCES accounts(id),
+    tax_rate TEXT,
+    amount INTEGER NOT NULL,
+    tax_amount INTEGER NOT NULL,
+    UNIQUE (invoice_id, line_number)
+);
+
+-- The part of a payment transaction applied to an invoice
+CREATE TABLE invoice_payments (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    invoice_id INTEGER NOT NULL REFERENCES invoices(id),
+    transaction_id INTEGER NOT NULL REFERENCES transactions(id),
+    payment_date DATE NOT NULL,
+    amount INTEGER NOT NULL CHECK (amount > 0),
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_invoice_payments_invoice ON invoice_payments(invoice_id);
@@ -0,0 +1,63 @@
+-- Vendor bills and the payments made against them. A bill posts its lines as debits against
+-- the vendor's payable; a payment posts the other way and is allocated to one or more bills.
+-- Whatever part of a payment is not allocated is held as credit with the vendor.
+
+-- Amounts are in minor units
+CREATE TABLE bills (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    company_id INTEGER NOT NULL REFERENCES companies(id),
+    bill_number TEXT NOT NULL,
+    -- The vendor's own invoice number
+    vendor_reference TEXT,
+    party_id INTEGER NOT NULL REFERENCES parties(id),
+    bill_date DATE NOT NULL,
+    due_date DATE NOT NULL CHECK (due_date >= bill_date),
+    description TEXT,
+    payable_account_id INTEGER NOT NULL REFERENCES accounts(id),
+    total INTEGER NOT NULL CHECK (total > 0),
+    transaction_id INTEGER NOT NULL REFERENCES transactions(id),
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (company_id, bill_number)
+);
+
+CREATE INDEX idx_bills_party ON bills(party_id);
+
+CREATE TABLE bill_lines (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    bill_id INTEGER NOT NULL REFERENCES bills(id) ON DELETE CASCADE,
+    line_number INTEGER NOT NULL,
+    description TEXT NOT NULL,
+    quantity TEXT NOT NULL,
+    unit_price TEXT NOT NULL,
+    account_id INTEGER NOT NULL REFERENCES accounts(id),
+    amount INTEGER NOT NULL,
+    UNIQUE (bill_id, line_number)
+);
+
+CREATE TABLE vendor_payments (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    company_id INTEGER NOT NULL REFERENCES companies(id),
+    party_id INTEGER NOT NULL REFERENCES parties(id),
+    payment_date DATE NOT NULL,
+    amount INTEGER NOT NULL CHECK (amount > 0),
+    -- Cash or bank account the money is paid from
+    account_id INTEGER NOT NULL REFERENCES accounts(id),
+    payable_account_id INTEGER NOT NULL REFERENCES accounts(id),
+    reference TEXT,
+    transaction_id INTEGER NOT NULL REFERENCES transactions(id),
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_vendor_payments_party ON vendor_payments(party_id);
+
+-- The part of a payment applied to a bill; allocations are only ever added
+CREATE TABLE bill_allocations (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    payment_id INTEGER NOT NULL REFERENCES vendor_payments(id),
+    bill_id INTEGER NOT NULL REFERENCES bills(id),
+    amount INTEGER NOT NULL CHECK (amount > 0),
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_bill_allocations_payment ON bill_allocations(payment_id);
+CREATE INDEX idx_bill_allocations_bill ON bill_allocations(bill_id);
@@ -0,0 +1,28 @@
+-- Open-item matching: a clearing group ties together debit and credit lines of one account
+-- (an invoice and the receipt that settles it, say). A line can be split across several groups
+-- and a group can hold any number of lines, but each group's amounts net to zero.
+--
+-- A group stops counting as soon as one of its lines' transactions is no longer posted.
+
+CREATE TABLE clearing_groups (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    company_id INTEGER NOT NULL REFERENCES companies(id),
+    account_id INTEGER NOT NULL REFERENCES accounts(id),
+    party_id INTEGER REFERENCES parties(id),
+    -- Made by auto-match rather than by hand
+    is_automatic BOOLEAN NOT NULL DEFAULT 0,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_clearing_groups_account ON clearing_groups(account_id);
+
+-- Amounts are in minor units, debits positive and credits negative
+CREATE TABLE clearing_lines (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    group_id INTEGER NOT NULL REFERENCES clearing_groups(id),
+    journal_entry_id INTEGER NOT NULL REFERENCES journal_entries(id),
+    amount INTEGER NOT NULL CHECK (amount != 0),
+    UNIQUE (group_id, journal_entry_id)
+);
+
+CREATE INDEX idx_clearing_lines_entry ON clearing_lines(journal_entry_id);
@@ -0,0 +1,9 @@
+-- An account's lines were written under its control party type; changing it afterwards would
+-- leave them without a party of the right type
+CREATE TRIGGER accounts_control_party_type_without_lines
+    BEFORE UPDATE OF control_party_type ON accounts
+    WHEN NEW.control_party_type IS NOT OLD.control_party_type
+        AND EXISTS (SELECT 1 FROM journal_entries WHERE account_id = NEW.id)
+BEGIN
+    SELECT RAISE(ABORT, 'Accounts with journal entries cannot change their control party type');
+END;
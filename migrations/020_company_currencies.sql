@@ -0,0 +1,50 @@
+-- Each company keeps its books in its own functional currency and its own exchange rates.
+--
+-- Existing companies keep USD, the functional currency used when none is configured. Rates were
+-- shared by every company until now, so each company gets a copy of the existing rate table.
+
+PRAGMA defer_foreign_keys = ON;
+
+ALTER TABLE companies ADD COLUMN functional_currency TEXT NOT NULL DEFAULT 'USD'
+    CHECK (length(functional_currency) = 3);
+
+-- Exchange rates: one rate per company, currency and day
+CREATE TABLE exchange_rates_copy AS SELECT * FROM exchange_rates;
+DROP TABLE exchange_rates;
+
+CREATE TABLE exchange_rates (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    company_id INTEGER NOT NULL,
+    currency TEXT NOT NULL CHECK (length(currency) = 3),
+    rate_date DATE NOT NULL,
+    rate TEXT NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (company_id, currency, rate_date),
+    FOREIGN KEY (company_id) REFERENCES companies(id)
+);
+
+INSERT INTO exchange_rates (company_id, currency, rate_date, rate, created_at, updated_at)
+SELECT companies.id, rates.currency, rates.rate_date, rates.rate, rates.created_at, rates.updated_at
+FROM exchange_rates_copy rates
+CROSS JOIN companies
+WHERE rates.currency <> companies.functional_currency
+ORDER BY companies.id, rates.currency, rates.rate_date;
+
+DROP TABLE exchange_rates_copy;
+
+-- The functional currency converts at 1 by definition
+CREATE TRIGGER exchange_rates_not_functional
+    BEFORE INSERT ON exchange_rates
+    WHEN NEW.currency = (SELECT functional_currency FROM companies WHERE id = NEW.company_id)
+BEGIN
+    SELECT RAISE(ABORT, 'The functional currency does not take an exchange rate');
+END;
+
+-- Journal lines and rates are kept in the functional currency they were entered under
+CREATE TRIGGER companies_functional_currency_immutable
+    BEFORE UPDATE OF functional_currency ON companies
+    WHEN NEW.functional_currency IS NOT OLD.functional_currency
+BEGIN
+    SELECT RAISE(ABORT, 'A company cannot change its functional currency');
+END;
@@ -0,0 +1,28 @@
+-- Party timestamps are required like every other table's.
+--
+-- SQLite cannot add NOT NULL to an existing column, so the table is rebuilt; foreign keys from
+-- journal lines, invoices, bills and clearing lines are deferred until the same ids are back.
+
+PRAGMA defer_foreign_keys = ON;
+
+CREATE TABLE parties_copy AS SELECT * FROM parties;
+DROP TABLE parties;
+
+CREATE TABLE parties (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    company_id INTEGER NOT NULL REFERENCES companies(id),
+    code TEXT NOT NULL,
+    name TEXT NOT NULL,
+    party_type TEXT NOT NULL CHECK (party_type IN ('customer', 'vendor')),
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE(company_id, code)
+);
+
+INSERT INTO parties (id, company_id, code, name, party_type, created_at, updated_at)
+SELECT id, company_id, code, name, party_type,
+       COALESCE(created_at, CURRENT_TIMESTAMP),
+       COALESCE(updated_at, created_at, CURRENT_TIMESTAMP)
+FROM parties_copy;
+
+DROP TABLE parties_copy;
@@ -1,15 +1,39 @@
-use crate::database::Database;
+use crate::{database::Database, models::{FiscalCalendar, DEFAULT_FUNCTIONAL_CURRENCY}};
 use std::sync::Arc;
 
 #[derive(Clone)]
 pub struct AppState {
     pub database: Arc<Database>,
+    /// Token that identifies admin requests via the `X-Admin-Token` header.
+    /// When unset, no request is treated as coming from an admin.
+    pub admin_token: Option<Arc<str>>,
+    pub fiscal_calendar: FiscalCalendar,
+    /// Functional currency given to companies created without one
+    pub functional_currency: Arc<str>,
 }
 
 impl AppState {
     pub fn new(database: Database) -> Self {
         Self {
             database: Arc::new(database),
+            admin_token: None,
+            fiscal_calendar: FiscalCalendar::default(),
+            functional_currency: Arc::from(DEFAULT_FUNCTIONAL_CURRENCY),
         }
     }
+
+    pub fn with_admin_token(mut self, admin_token: Option<String>) -> Self {
+        self.admin_token = admin_token.filter(|token| !token.is_empty()).map(Arc::from);
+        self
+    }
+
+    pub fn with_fiscal_calendar(mut self, fiscal_calendar: FiscalCalendar) -> Self {
+        self.fiscal_calendar = fiscal_calendar;
+        self
+    }
+
+    pub fn with_functional_currency(mut self, functional_currency: &str) -> Self {
+        self.functional_currency = Arc::from(functional_currency);
+        self
+    }
 }
@@ -0,0 +1,408 @@
+use anyhow::Result;
+use sqlx::{sqlite::SqliteRow, Row, SqliteConnection};
+
+use super::{insert_transaction, next_sequence_number, Database};
+use crate::models::*;
+
+impl Database {
+    // Bill operations
+    /// Number the bill if it has no number, post it and keep its lines, all in one database
+    /// transaction. The payable account must already have been resolved.
+    pub async fn create_bill(&self, company_id: i64, request: CreateBillRequest) -> Result<BillWithDetails> {
+        request.validate()?;
+
+        let mut tx = self.pool.begin().await?;
+
+        let bill_number = match request.bill_number {
+            Some(ref number) => number.clone(),
+            None => next_bill_number(&mut tx, company_id).await?,
+        };
+
+        let posting = request.transaction_request(&bill_number)?;
+        posting.validate()?;
+        let transaction = insert_transaction(&mut tx, company_id, &posting, None).await?;
+
+        let row = sqlx::query(
+            r#"
+            INSERT INTO bills
+                (company_id, bill_number, vendor_reference, party_id, bill_date, due_date,
+                 description, payable_account_id, total, transaction_id)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            RETURNING id
+            "#,
+        )
+        .bind(company_id)
+        .bind(&bill_number)
+        .bind(&request.vendor_reference)
+        .bind(request.party_id)
+        .bind(request.bill_date)
+        .bind(request.due_date())
+        .bind(&request.description)
+        .bind(request.payable_account_id)
+        .bind(to_minor_units(request.total())?)
+        .bind(transaction.transaction.id)
+        .fetch_one(&mut *tx)
+        .await?;
+        let id: i64 = row.try_get("id")?;
+
+        for (index, line) in request.lines.iter().enumerate() {
+            sqlx::query(
+                r#"
+                INSERT INTO bill_lines
+                    (bill_id, line_number, description, quantity, unit_price, account_id, amount)
+                VALUES (?, ?, ?, ?, ?, ?, ?)
+                "#,
+            )
+            .bind(id)
+            .bind(index as i64 + 1)
+            .bind(&line.description)
+            .bind(line.quantity.normalize().to_string())
+            .bind(line.unit_price.normalize().to_string())
+            .bind(line.account_id)
+            .bind(to_minor_units(line.amount())?)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        let bill = fetch_bill(&mut tx, company_id, id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Bill {} disappeared after insert", id))?;
+        tx.commit().await?;
+
+        Ok(bill)
+    }
+
+    pub async fn get_bill(&self, company_id: i64, id: i64) -> Result<Option<BillWithDetails>> {
+        let mut conn = self.pool.acquire().await?;
+        fetch_bill(&mut conn, company_id, id).await
+    }
+
+    pub async fn get_bill_by_number(&self, company_id: i64, bill_number: &str) -> Result<Option<Bill>> {
+        let row = sqlx::query("SELECT * FROM bills WHERE company_id = ? AND bill_number = ?")
+            .bind(company_id)
+            .bind(bill_number)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        row.as_ref().map(bill_from_row).transpose()
+    }
+
+    /// Bills in number order, optionally for one vendor and in one status
+    pub async fn list_bills(
+        &self,
+        company_id: i64,
+        party_id: Option<i64>,
+        status: Option<InvoiceStatus>,
+    ) -> Result<Vec<BillWithDetails>> {
+        let mut conn = self.pool.acquire().await?;
+
+        let ids: Vec<i64> = sqlx::query_scalar(
+            "SELECT id FROM bills WHERE company_id = ? AND (? IS NULL OR party_id = ?) ORDER BY bill_number"
+        )
+        .bind(company_id)
+        .bind(party_id)
+        .bind(party_id)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        let mut bills = Vec::with_capacity(ids.len());
+        for id in ids {
+            if let Some(bill) = fetch_bill(&mut conn, company_id, id).await? {
+                if status.is_none_or(|status| bill.status == status) {
+                    bills.push(bill);
+                }
+            }
+        }
+
+        Ok(bills)
+    }
+
+    /// Post a payment to a vendor and apply it to the requested bills; the rest is held as
+    /// vendor credit. Both accounts on the request must already have been resolved.
+    pub async fn create_vendor_payment(&self, company_id: i64, request: VendorPaymentRequest) -> Result<VendorPaymentWithAllocations> {
+        request.validate()?;
+
+        let mut tx = self.pool.begin().await?;
+
+        let posting = request.transaction_request()?;
+        posting.validate()?;
+        let transaction = insert_transaction(&mut tx, company_id, &posting, None).await?;
+
+        let row = sqlx::query(
+            r#"
+            INSERT INTO vendor_payments
+                (company_id, party_id, payment_date, amount, account_id, payable_account_id,
+                 reference, transaction_id)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            RETURNING id
+            "#,
+        )
+        .bind(company_id)
+        .bind(request.party_id)
+        .bind(request.payment_date)
+        .bind(to_minor_units(request.amount)?)
+        .bind(request.account_id)
+        .bind(request.payable_account_id)
+        .bind(&request.reference)
+        .bind(transaction.transaction.id)
+        .fetch_one(&mut *tx)
+        .await?;
+        let id: i64 = row.try_get("id")?;
+
+        let payment = fetch_vendor_payment(&mut tx, company_id, id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Vendor payment {} disappeared after insert", id))?;
+        insert_allocations(&mut tx, company_id, &payment, &request.allocations).await?;
+
+        let payment = fetch_vendor_payment(&mut tx, company_id, id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Vendor payment {} disappeared after insert", id))?;
+        tx.commit().await?;
+
+        Ok(payment)
+    }
+
+    /// Apply credit left on an earlier payment to bills.
+    /// Returns `None` when the payment does not exist.
+    pub async fn allocate_vendor_payment(
+        &self,
+        company_id: i64,
+        id: i64,
+        allocations: &[BillAllocationRequest],
+    ) -> Result<Option<VendorPaymentWithAllocations>> {
+        let mut tx = self.pool.begin().await?;
+
+        let Some(payment) = fetch_vendor_payment(&mut tx, company_id, id).await? else {
+            return Ok(None);
+        };
+        insert_allocations(&mut tx, company_id, &payment, allocations).await?;
+
+        let payment = fetch_vendor_payment(&mut tx, company_id, id).await?;
+        tx.commit().await?;
+
+        Ok(payment)
+    }
+
+    pub async fn get_vendor_payment(&self, company_id: i64, id: i64) -> Result<Option<VendorPaymentWithAllocations>> {
+        let mut conn = self.pool.acquire().await?;
+        fetch_vendor_payment(&mut conn, company_id, id).await
+    }
+
+    /// Payments in date order, optionally for one vendor
+    pub async fn list_vendor_payments(&self, company_id: i64, party_id: Option<i64>) -> Result<Vec<VendorPaymentWithAllocations>> {
+        let mut conn = self.pool.acquire().await?;
+
+        let ids: Vec<i64> = sqlx::query_scalar(
+            "SELECT id FROM vendor_payments WHERE company_id = ? AND (? IS NULL OR party_id = ?) ORDER BY payment_date, id"
+        )
+        .bind(company_id)
+        .bind(party_id)
+        .bind(party_id)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        let mut payments = Vec::with_capacity(ids.len());
+        for id in ids {
+            payments.extend(fetch_vendor_payment(&mut conn, company_id, id).await?);
+        }
+
+        Ok(payments)
+    }
+}
+
+/// Hand out the next number of the company's bill sequence, skipping numbers already given
+/// to bills by hand
+async fn next_bill_number(conn: &mut SqliteConnection, company_id: i64) -> Result<String> {
+    loop {
+        let bill_number = format!("BILL-{:05}", next_sequence_number(conn, company_id, "bill").await?);
+        let taken: bool = sqlx::query_scalar(
+            "SELECT EXISTS (SELECT 1 FROM bills WHERE company_id = ? AND bill_number = ?)"
+        )
+        .bind(company_id)
+        .bind(&bill_number)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        if !taken {
+            return Ok(bill_number);
+        }
+    }
+}
+
+/// Apply part of a payment to bills after checking each bill still takes it and the payment
+/// has that much left
+async fn insert_allocations(
+    conn: &mut SqliteConnection,
+    company_id: i64,
+    payment: &VendorPaymentWithAllocations,
+    allocations: &[BillAllocationRequest],
+) -> Result<()> {
+    if payment.is_voided && !allocations.is_empty() {
+        return Err(anyhow::anyhow!("Vendor payment {} has been voided", payment.payment.id));
+    }
+    validate_allocations(allocations, payment.unapplied)?;
+
+    for allocation in allocations {
+        let bill = fetch_bill(conn, company_id, allocation.bill_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Bill with ID {} does not exist", allocation.bill_id))?;
+        if let Some(message) = bill.allocation_rejection(payment.payment.party_id, payment.payment.payable_account_id, allocation.amount) {
+            return Err(anyhow::anyhow!(message));
+        }
+
+        sqlx::query("INSERT INTO bill_allocations (payment_id, bill_id, amount) VALUES (?, ?, ?)")
+            .bind(payment.payment.id)
+            .bind(allocation.bill_id)
+            .bind(to_minor_units(allocation.amount)?)
+            .execute(&mut *conn)
+            .await?;
+    }
+
+    Ok(())
+}
+
+async fn fetch_bill(conn: &mut SqliteConnection, company_id: i64, id: i64) -> Result<Option<BillWithDetails>> {
+    let row = sqlx::query(
+        r#"
+        SELECT b.*, t.status AS transaction_status
+        FROM bills b
+        JOIN transactions t ON b.transaction_id = t.id
+        WHERE b.id = ? AND b.company_id = ?
+        "#,
+    )
+    .bind(id)
+    .bind(company_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+    let Some(row) = row else {
+        return Ok(None);
+    };
+    let bill = bill_from_row(&row)?;
+    let bill_status: TransactionStatus = row.try_get("transaction_status")?;
+
+    let rows = sqlx::query("SELECT * FROM bill_lines WHERE bill_id = ? ORDER BY line_number")
+        .bind(id)
+        .fetch_all(&mut *conn)
+        .await?;
+    let lines = rows.iter().map(bill_line_from_row).collect::<Result<Vec<_>>>()?;
+
+    let rows = sqlx::query(
+        r#"
+        SELECT ba.*, b.bill_number, p.payment_date, p.transaction_id, t.status AS transaction_status
+        FROM bill_allocations ba
+        JOIN bills b ON ba.bill_id = b.id
+        JOIN vendor_payments p ON ba.payment_id = p.id
+        JOIN transactions t ON p.transaction_id = t.id
+        WHERE ba.bill_id = ?
+        ORDER BY p.payment_date, ba.id
+        "#,
+    )
+    .bind(id)
+    .fetch_all(&mut *conn)
+    .await?;
+    let allocations = rows
+        .iter()
+        .map(|row| Ok((bill_allocation_from_row(row)?, row.try_get("transaction_status")?)))
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(Some(BillWithDetails::new(bill, bill_status, lines, allocations)))
+}
+
+async fn fetch_vendor_payment(conn: &mut SqliteConnection, company_id: i64, id: i64) -> Result<Option<VendorPaymentWithAllocations>> {
+    let row = sqlx::query(
+        r#"
+        SELECT p.*, t.status AS transaction_status
+        FROM vendor_payments p
+        JOIN transactions t ON p.transaction_id = t.id
+        WHERE p.id = ? AND p.company_id = ?
+        "#,
+    )
+    .bind(id)
+    .bind(company_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+    let Some(row) = row else {
+        return Ok(None);
+    };
+    let payment = vendor_payment_from_row(&row)?;
+    let status: TransactionStatus = row.try_get("transaction_status")?;
+
+    let rows = sqlx::query(
+        r#"
+        SELECT ba.*, b.bill_number, p.payment_date, p.transaction_id, bt.status AS bill_status
+        FROM bill_allocations ba
+        JOIN bills b ON ba.bill_id = b.id
+        JOIN transactions bt ON b.transaction_id = bt.id
+        JOIN vendor_payments p ON ba.payment_id = p.id
+        WHERE ba.payment_id = ?
+        ORDER BY ba.id
+        "#,
+    )
+    .bind(id)
+    .fetch_all(&mut *conn)
+    .await?;
+    let allocations = rows
+        .iter()
+        .map(|row| Ok((bill_allocation_from_row(row)?, row.try_get("bill_status")?)))
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(Some(VendorPaymentWithAllocations::new(payment, status, allocations)))
+}
+
+fn bill_from_row(row: &SqliteRow) -> Result<Bill> {
+    Ok(Bill {
+        id: row.try_get("id")?,
+        company_id: row.try_get("company_id")?,
+        bill_number: row.try_get("bill_number")?,
+        vendor_reference: row.try_get("vendor_reference")?,
+        party_id: row.try_get("party_id")?,
+        bill_date: row.try_get("bill_date")?,
+        due_date: row.try_get("due_date")?,
+        description: row.try_get("description")?,
+        payable_account_id: row.try_get("payable_account_id")?,
+        total: from_minor_units(row.try_get("total")?),
+        transaction_id: row.try_get("transaction_id")?,
+        created_at: row.try_get("created_at")?,
+    })
+}
+
+fn bill_line_from_row(row: &SqliteRow) -> Result<BillLine> {
+    Ok(BillLine {
+        id: row.try_get("id")?,
+        bill_id: row.try_get("bill_id")?,
+        line_number: row.try_get("line_number")?,
+        description: row.try_get("description")?,
+        quantity: row.try_get::<String, _>("quantity")?.parse()?,
+        unit_price: row.try_get::<String, _>("unit_price")?.parse()?,
+        account_id: row.try_get("account_id")?,
+        amount: from_minor_units(row.try_get("amount")?),
+    })
+}
+
+fn bill_allocation_from_row(row: &SqliteRow) -> Result<BillAllocation> {
+    Ok(BillAllocation {
+        id: row.try_get("id")?,
+        payment_id: row.try_get("payment_id")?,
+        bill_id: row.try_get("bill_id")?,
+        bill_number: row.try_get("bill_number")?,
+        payment_date: row.try_get("payment_date")?,
+        transaction_id: row.try_get("transaction_id")?,
+        amount: from_minor_units(row.try_get("amount")?),
+        created_at: row.try_get("created_at")?,
+    })
+}
+
+fn vendor_payment_from_row(row: &SqliteRow) -> Result<VendorPayment> {
+    Ok(VendorPayment {
+        id: row.try_get("id")?,
+        company_id: row.try_get("company_id")?,
+        party_id: row.try_get("party_id")?,
+        payment_date: row.try_get("payment_date")?,
+        amount: from_minor_units(row.try_get("amount")?),
+        account_id: row.try_get("account_id")?,
+        payable_account_id: row.try_get("payable_account_id")?,
+        reference: row.try_get("reference")?,
+        transaction_id: row.try_get("transaction_id")?,
+        created_at: row.try_get("created_at")?,
+    })
+}
@@ -0,0 +1,242 @@
+use anyhow::Result;
+use rust_decimal::Decimal;
+use sqlx::{sqlite::SqliteRow, Row, SqliteConnection};
+
+use super::Database;
+use crate::models::*;
+
+impl Database {
+    // Clearing operations
+    /// Posted lines of an account that are not fully cleared, oldest first, optionally for
+    /// one party
+    pub async fn get_uncleared_lines(&self, company_id: i64, account_id: i64, party_id: Option<i64>) -> Result<Vec<UnclearedLine>> {
+        let mut conn = self.pool.acquire().await?;
+        fetch_uncleared_lines(&mut conn, company_id, account_id, party_id).await
+    }
+
+    /// Clear the requested lines of an account against each other.
+    /// The lines are checked again inside the database transaction.
+    pub async fn create_clearing_group(
+        &self,
+        company_id: i64,
+        request: &CreateClearingGroupRequest,
+    ) -> Result<ClearingGroupWithLines> {
+        let mut tx = self.pool.begin().await?;
+
+        let open = fetch_uncleared_lines(&mut tx, company_id, request.account_id, None).await?;
+        let amounts = clearing_amounts(&open, &request.lines)?;
+        let group = insert_clearing_group(&mut tx, company_id, request.account_id, &open, &amounts, false).await?;
+
+        tx.commit().await?;
+        Ok(group)
+    }
+
+    /// Clear every open line of the account that auto-match pairs up, one group per match
+    pub async fn auto_match(&self, company_id: i64, request: &AutoMatchRequest) -> Result<Vec<ClearingGroupWithLines>> {
+        let mut tx = self.pool.begin().await?;
+
+        let open = fetch_uncleared_lines(&mut tx, company_id, request.account_id, request.party_id).await?;
+        let mut groups = Vec::new();
+        for amounts in propose_matches(&open) {
+            groups.push(insert_clearing_group(&mut tx, company_id, request.account_id, &open, &amounts, true).await?);
+        }
+
+        tx.commit().await?;
+        Ok(groups)
+    }
+
+    pub async fn get_clearing_group(&self, company_id: i64, id: i64) -> Result<Option<ClearingGroupWithLines>> {
+        let mut conn = self.pool.acquire().await?;
+        fetch_clearing_group(&mut conn, company_id, id).await
+    }
+
+    pub async fn list_clearing_groups(&self, company_id: i64, account_id: Option<i64>) -> Result<Vec<ClearingGroupWithLines>> {
+        let mut conn = self.pool.acquire().await?;
+
+        let ids: Vec<i64> = sqlx::query_scalar(
+            "SELECT id FROM clearing_groups WHERE company_id = ? AND (? IS NULL OR account_id = ?) ORDER BY id"
+        )
+        .bind(company_id)
+        .bind(account_id)
+        .bind(account_id)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        let mut groups = Vec::with_capacity(ids.len());
+        for id in ids {
+            groups.extend(fetch_clearing_group(&mut conn, company_id, id).await?);
+        }
+
+        Ok(groups)
+    }
+}
+
+async fn insert_clearing_group(
+    conn: &mut SqliteConnection,
+    company_id: i64,
+    account_id: i64,
+    open: &[UnclearedLine],
+    amounts: &[(i64, Decimal)],
+    is_automatic: bool,
+) -> Result<ClearingGroupWithLines> {
+    let party_id = amounts
+        .first()
+        .and_then(|(journal_entry_id, _)| open.iter().find(|line| line.journal_entry_id == *journal_entry_id))
+        .and_then(|line| line.party_id);
+
+    let row = sqlx::query(
+        "INSERT INTO clearing_groups (company_id, account_id, party_id, is_automatic) VALUES (?, ?, ?, ?) RETURNING id"
+    )
+    .bind(company_id)
+    .bind(account_id)
+    .bind(party_id)
+    .bind(is_automatic)
+    .fetch_one(&mut *conn)
+    .await?;
+    let id: i64 = row.try_get("id")?;
+
+    for (journal_entry_id, amount) in amounts {
+        sqlx::query("INSERT INTO clearing_lines (group_id, journal_entry_id, amount) VALUES (?, ?, ?)")
+            .bind(id)
+            .bind(journal_entry_id)
+            .bind(to_minor_units(*amount)?)
+            .execute(&mut *conn)
+            .await?;
+    }
+
+    fetch_clearing_group(conn, company_id, id)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("Clearing group {} disappeared after insert", id))
+}
+
+async fn fetch_uncleared_lines(
+    conn: &mut SqliteConnection,
+    company_id: i64,
+    account_id: i64,
+    party_id: Option<i64>,
+) -> Result<Vec<UnclearedLine>> {
+    let rows = sqlx::query(
+        r#"
+        WITH inactive_groups AS (
+            SELECT DISTINCT cl.group_id
+            FROM clearing_lines cl
+            JOIN journal_entries je ON cl.journal_entry_id = je.id
+            JOIN transactions t ON je.transaction_id = t.id
+            WHERE t.status != 'posted'
+        ),
+        cleared AS (
+            SELECT journal_entry_id, SUM(amount) AS amount
+            FROM clearing_lines
+            WHERE group_id NOT IN (SELECT group_id FROM inactive_groups)
+            GROUP BY journal_entry_id
+        )
+        SELECT
+            je.id,
+            je.transaction_id,
+            je.account_id,
+            je.party_id,
+            p.code as party_code,
+            t.transaction_date,
+            je.due_date,
+            t.reference,
+            COALESCE(je.description, t.description) as description,
+            je.debit_amount - je.credit_amount as amount,
+            COALESCE(c.amount, 0) as cleared
+        FROM journal_entries je
+        JOIN transactions t ON je.transaction_id = t.id
+        LEFT JOIN parties p ON je.party_id = p.id
+        LEFT JOIN cleared c ON c.journal_entry_id = je.id
+        WHERE t.company_id = ? AND je.account_id = ?
+          AND t.status = 'posted'
+          AND (? IS NULL OR je.party_id = ?)
+          AND je.debit_amount - je.credit_amount != COALESCE(c.amount, 0)
+        ORDER BY t.transaction_date, t.id, je.id
+        "#,
+    )
+    .bind(company_id)
+    .bind(account_id)
+    .bind(party_id)
+    .bind(party_id)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    rows.iter()
+        .map(|row| {
+            let amount = from_minor_units(row.try_get("amount")?);
+            let cleared = from_minor_units(row.try_get("cleared")?);
+            Ok(UnclearedLine {
+                journal_entry_id: row.try_get("id")?,
+                transaction_id: row.try_get("transaction_id")?,
+                account_id: row.try_get("account_id")?,
+                party_id: row.try_get("party_id")?,
+                party_code: row.try_get("party_code")?,
+                transaction_date: row.try_get("transaction_date")?,
+                due_date: row.try_get("due_date")?,
+                reference: row.try_get("reference")?,
+                description: row.try_get("description")?,
+                amount,
+                cleared,
+                residual: amount - cleared,
+            })
+        })
+        .collect()
+}
+
+async fn fetch_clearing_group(conn: &mut SqliteConnection, company_id: i64, id: i64) -> Result<Option<ClearingGroupWithLines>> {
+    let row = sqlx::query("SELECT * FROM clearing_groups WHERE id = ? AND company_id = ?")
+        .bind(id)
+        .bind(company_id)
+        .fetch_optional(&mut *conn)
+        .await?;
+    let Some(row) = row else {
+        return Ok(None);
+    };
+    let group = clearing_group_from_row(&row)?;
+
+    let rows = sqlx::query(
+        r#"
+        SELECT cl.*, je.transaction_id, t.transaction_date, t.reference, t.status AS transaction_status
+        FROM clearing_lines cl
+        JOIN journal_entries je ON cl.journal_entry_id = je.id
+        JOIN transactions t ON je.transaction_id = t.id
+        WHERE cl.group_id = ?
+        ORDER BY t.transaction_date, cl.id
+        "#,
+    )
+    .bind(id)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let mut is_active = true;
+    let mut lines = Vec::with_capacity(rows.len());
+    for row in &rows {
+        let status: TransactionStatus = row.try_get("transaction_status")?;
+        is_active &= status.affects_balances();
+        lines.push(clearing_line_from_row(row)?);
+    }
+
+    Ok(Some(ClearingGroupWithLines { group, lines, is_active }))
+}
+
+fn clearing_group_from_row(row: &SqliteRow) -> Result<ClearingGroup> {
+    Ok(ClearingGroup {
+        id: row.try_get("id")?,
+        company_id: row.try_get("company_id")?,
+        account_id: row.try_get("account_id")?,
+        party_id: row.try_get("party_id")?,
+        is_automatic: row.try_get("is_automatic")?,
+        created_at: row.try_get("created_at")?,
+    })
+}
+
+fn clearing_line_from_row(row: &SqliteRow) -> Result<ClearingLine> {
+    Ok(ClearingLine {
+        id: row.try_get("id")?,
+        group_id: row.try_get("group_id")?,
+        journal_entry_id: row.try_get("journal_entry_id")?,
+        transaction_id: row.try_get("transaction_id")?,
+        transaction_date: row.try_get("transaction_date")?,
+        reference: row.try_get("reference")?,
+        amount: from_minor_units(row.try_get("amount")?),
+    })
+}
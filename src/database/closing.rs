@@ -0,0 +1,201 @@
+use anyhow::Result;
+use chrono::NaiveDate;
+use sqlx::{sqlite::SqliteRow, Row, SqliteConnection};
+
+use super::{fetch_account_activity, fetch_transaction, insert_transaction, Database};
+use crate::models::*;
+
+impl Database {
+    // Year-end close operations
+    pub async fn preview_year_end_close(
+        &self,
+        company_id: i64,
+        fiscal_year: i32,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+        equity_account_id: i64,
+    ) -> Result<YearEndClosePreview> {
+        let mut conn = self.pool.acquire().await?;
+        build_preview(&mut conn, company_id, fiscal_year, start_date, end_date, equity_account_id).await
+    }
+
+    /// Post the closing entry for a fiscal year and record the close.
+    /// The entry is recomputed inside the database transaction so it matches the ledger exactly.
+    pub async fn close_year(
+        &self,
+        company_id: i64,
+        fiscal_year: i32,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+        equity_account_id: i64,
+    ) -> Result<YearEndCloseResult> {
+        let mut tx = self.pool.begin().await?;
+
+        let preview = build_preview(&mut tx, company_id, fiscal_year, start_date, end_date, equity_account_id).await?;
+        if !preview.has_activity() {
+            return Err(anyhow::anyhow!("Fiscal year {} has no revenue or expense activity to close", fiscal_year));
+        }
+        preview.transaction.validate()?;
+
+        let transaction = insert_transaction(&mut tx, company_id, &preview.transaction, None).await?;
+
+        let row = sqlx::query(
+            r#"
+            INSERT INTO year_end_closes (company_id, fiscal_year, start_date, end_date, equity_account_id, transaction_id)
+            VALUES (?, ?, ?, ?, ?, ?)
+            RETURNING *
+            "#,
+        )
+        .bind(company_id)
+        .bind(fiscal_year)
+        .bind(start_date)
+        .bind(end_date)
+        .bind(equity_account_id)
+        .bind(transaction.transaction.id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let closing = year_end_close_from_row(&row)?;
+        tx.commit().await?;
+
+        Ok(YearEndCloseResult { closing, transaction })
+    }
+
+    /// The active (not reopened) close for a fiscal year, if any
+    pub async fn get_year_end_close(&self, company_id: i64, fiscal_year: i32) -> Result<Option<YearEndClose>> {
+        let row = sqlx::query("SELECT * FROM year_end_closes WHERE company_id = ? AND fiscal_year = ? AND status = 'closed'")
+            .bind(company_id)
+            .bind(fiscal_year)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        row.as_ref().map(year_end_close_from_row).transpose()
+    }
+
+    pub async fn list_year_end_closes(&self, company_id: i64) -> Result<Vec<YearEndClose>> {
+        let rows = sqlx::query("SELECT * FROM year_end_closes WHERE company_id = ? ORDER BY fiscal_year, id")
+            .bind(company_id)
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.iter().map(year_end_close_from_row).collect()
+    }
+
+    /// Whether a transaction is a year-end closing entry or the reversal that reopened one;
+    /// these only change through closing and reopening the year
+    pub async fn is_year_end_transaction(&self, company_id: i64, id: i64) -> Result<bool> {
+        let found: bool = sqlx::query_scalar(
+            r#"
+            SELECT EXISTS (
+                SELECT 1 FROM year_end_closes
+                WHERE company_id = ? AND (transaction_id = ? OR reversal_transaction_id = ?)
+            )
+            "#,
+        )
+        .bind(company_id)
+        .bind(id)
+        .bind(id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(found)
+    }
+
+    /// Reverse the closing entry of a fiscal year on its closing date and mark the close as reopened.
+    /// Returns `None` when the year is not currently closed. Fails when the closing entry is no
+    /// longer posted or has already been reversed.
+    pub async fn reopen_year(&self, company_id: i64, fiscal_year: i32) -> Result<Option<YearEndCloseResult>> {
+        let mut tx = self.pool.begin().await?;
+
+        let row = sqlx::query("SELECT * FROM year_end_closes WHERE company_id = ? AND fiscal_year = ? AND status = 'closed'")
+            .bind(company_id)
+            .bind(fiscal_year)
+            .fetch_optional(&mut *tx)
+            .await?;
+        let closing = match row.as_ref().map(year_end_close_from_row).transpose()? {
+            Some(closing) => closing,
+            None => return Ok(None),
+        };
+
+        let closing_transaction = fetch_transaction(&mut tx, company_id, closing.transaction_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Closing transaction {} not found", closing.transaction_id))?;
+        if closing_transaction.transaction.status != TransactionStatus::Posted {
+            return Err(anyhow::anyhow!("Closing transaction {} is no longer posted", closing.transaction_id));
+        }
+        let reversed: bool = sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM transactions WHERE reversal_of_id = ?)")
+            .bind(closing.transaction_id)
+            .fetch_one(&mut *tx)
+            .await?;
+        if reversed {
+            return Err(anyhow::anyhow!("Closing transaction {} has already been reversed", closing.transaction_id));
+        }
+
+        let reversal = closing_transaction.reversal_request(&ReverseTransactionRequest {
+            transaction_date: closing.end_date,
+            description: Some(format!("Reopen fiscal year {}", fiscal_year)),
+            reference: None,
+        });
+        reversal.validate()?;
+
+        let transaction = insert_transaction(&mut tx, company_id, &reversal, Some(closing.transaction_id)).await?;
+
+        let row = sqlx::query(
+            r#"
+            UPDATE year_end_closes
+            SET status = 'reopened', reversal_transaction_id = ?, reopened_at = CURRENT_TIMESTAMP
+            WHERE id = ?
+            RETURNING *
+            "#,
+        )
+        .bind(transaction.transaction.id)
+        .bind(closing.id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let closing = year_end_close_from_row(&row)?;
+        tx.commit().await?;
+
+        Ok(Some(YearEndCloseResult { closing, transaction }))
+    }
+}
+
+async fn build_preview(
+    conn: &mut SqliteConnection,
+    company_id: i64,
+    fiscal_year: i32,
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    equity_account_id: i64,
+) -> Result<YearEndClosePreview> {
+    let lines = fetch_account_activity(conn, company_id, Some(start_date), Some(end_date), true)
+        .await?
+        .into_iter()
+        .filter(|activity| matches!(activity.account_type, AccountType::Revenue | AccountType::Expense))
+        .map(|activity| ClosingLine {
+            account_id: activity.account_id,
+            account_code: activity.account_code,
+            account_name: activity.account_name,
+            account_type: activity.account_type,
+            balance: activity.balance,
+        })
+        .collect();
+
+    Ok(YearEndClosePreview::new(fiscal_year, start_date, end_date, equity_account_id, lines))
+}
+
+fn year_end_close_from_row(row: &SqliteRow) -> Result<YearEndClose> {
+    Ok(YearEndClose {
+        id: row.try_get("id")?,
+        company_id: row.try_get("company_id")?,
+        fiscal_year: row.try_get("fiscal_year")?,
+        start_date: row.try_get("start_date")?,
+        end_date: row.try_get("end_date")?,
+        equity_account_id: row.try_get("equity_account_id")?,
+        transaction_id: row.try_get("transaction_id")?,
+        reversal_transaction_id: row.try_get("reversal_transaction_id")?,
+        status: row.try_get("status")?,
+        created_at: row.try_get("created_at")?,
+        reopened_at: row.try_get("reopened_at")?,
+    })
+}
@@ -0,0 +1,91 @@
+use anyhow::Result;
+use sqlx::{sqlite::SqliteRow, Row};
+
+use super::Database;
+use crate::models::*;
+
+impl Database {
+    // Company operations
+    /// Create a company and seed its chart of accounts from the account templates
+    pub async fn create_company(&self, request: CreateCompanyRequest) -> Result<Company> {
+        let mut tx = self.pool.begin().await?;
+
+        let row = sqlx::query("INSERT INTO companies (code, name, functional_currency) VALUES (?, ?, ?) RETURNING *")
+            .bind(&request.code)
+            .bind(&request.name)
+            .bind(request.functional_currency.as_deref().unwrap_or(DEFAULT_FUNCTIONAL_CURRENCY))
+            .fetch_one(&mut *tx)
+            .await?;
+        let company = company_from_row(&row)?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO accounts (company_id, code, name, account_type, is_header, cash_flow_category, control_party_type)
+            SELECT ?, code, name, account_type, is_header, cash_flow_category, control_party_type
+            FROM account_templates
+            ORDER BY code
+            "#,
+        )
+        .bind(company.id)
+        .execute(&mut *tx)
+        .await?;
+
+        // Link parents once every account of the new chart exists
+        sqlx::query(
+            r#"
+            UPDATE accounts
+            SET parent_id = (
+                SELECT parent.id
+                FROM account_templates t
+                JOIN accounts parent ON parent.company_id = accounts.company_id AND parent.code = t.parent_code
+                WHERE t.code = accounts.code
+            )
+            WHERE company_id = ?
+            "#,
+        )
+        .bind(company.id)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(company)
+    }
+
+    pub async fn get_company(&self, id: i64) -> Result<Option<Company>> {
+        let row = sqlx::query("SELECT * FROM companies WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        row.as_ref().map(company_from_row).transpose()
+    }
+
+    pub async fn get_company_by_code(&self, code: &str) -> Result<Option<Company>> {
+        let row = sqlx::query("SELECT * FROM companies WHERE code = ?")
+            .bind(code)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        row.as_ref().map(company_from_row).transpose()
+    }
+
+    pub async fn list_companies(&self) -> Result<Vec<Company>> {
+        let rows = sqlx::query("SELECT * FROM companies ORDER BY code")
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.iter().map(company_from_row).collect()
+    }
+}
+
+fn company_from_row(row: &SqliteRow) -> Result<Company> {
+    Ok(Company {
+        id: row.try_get("id")?,
+        code: row.try_get("code")?,
+        name: row.try_get("name")?,
+        functional_currency: row.try_get("functional_currency")?,
+        created_at: row.try_get("created_at")?,
+        updated_at: row.try_get("updated_at")?,
+    })
+}
@@ -0,0 +1,91 @@
+use anyhow::Result;
+use chrono::NaiveDate;
+use sqlx::{sqlite::SqliteRow, Row, SqliteConnection};
+
+use super::{parse_rate, Database};
+use crate::models::*;
+
+impl Database {
+    // Exchange rate operations
+    /// Record the rate for a currency on a date, replacing any rate already set for that day
+    pub async fn upsert_exchange_rate(&self, company_id: i64, request: CreateExchangeRateRequest) -> Result<ExchangeRate> {
+        let row = sqlx::query(
+            r#"
+            INSERT INTO exchange_rates (company_id, currency, rate_date, rate)
+            VALUES (?, ?, ?, ?)
+            ON CONFLICT (company_id, currency, rate_date)
+            DO UPDATE SET rate = excluded.rate, updated_at = CURRENT_TIMESTAMP
+            RETURNING *
+            "#,
+        )
+        .bind(company_id)
+        .bind(&request.currency)
+        .bind(request.rate_date)
+        .bind(request.rate.normalize().to_string())
+        .fetch_one(&self.pool)
+        .await?;
+
+        exchange_rate_from_row(&row)
+    }
+
+    pub async fn list_exchange_rates(&self, company_id: i64, currency: Option<&str>) -> Result<Vec<ExchangeRate>> {
+        let rows = match currency {
+            Some(currency) => {
+                sqlx::query("SELECT * FROM exchange_rates WHERE company_id = ? AND currency = ? ORDER BY rate_date")
+                    .bind(company_id)
+                    .bind(currency)
+                    .fetch_all(&self.pool)
+                    .await?
+            }
+            None => {
+                sqlx::query("SELECT * FROM exchange_rates WHERE company_id = ? ORDER BY currency, rate_date")
+                    .bind(company_id)
+                    .fetch_all(&self.pool)
+                    .await?
+            }
+        };
+
+        rows.iter().map(exchange_rate_from_row).collect()
+    }
+
+    /// The rate in effect on a date: the latest one recorded on or before it
+    pub async fn get_exchange_rate(&self, company_id: i64, currency: &str, date: NaiveDate) -> Result<Option<ExchangeRate>> {
+        let mut conn = self.pool.acquire().await?;
+        fetch_exchange_rate(&mut conn, company_id, currency, date).await
+    }
+}
+
+pub(super) async fn fetch_exchange_rate(
+    conn: &mut SqliteConnection,
+    company_id: i64,
+    currency: &str,
+    date: NaiveDate,
+) -> Result<Option<ExchangeRate>> {
+    let row = sqlx::query(
+        r#"
+        SELECT * FROM exchange_rates
+        WHERE company_id = ? AND currency = ? AND rate_date <= ?
+        ORDER BY rate_date DESC
+        LIMIT 1
+        "#,
+    )
+    .bind(company_id)
+    .bind(currency)
+    .bind(date)
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    row.as_ref().map(exchange_rate_from_row).transpose()
+}
+
+fn exchange_rate_from_row(row: &SqliteRow) -> Result<ExchangeRate> {
+    Ok(ExchangeRate {
+        id: row.try_get("id")?,
+        company_id: row.try_get("company_id")?,
+        currency: row.try_get("currency")?,
+        rate_date: row.try_get("rate_date")?,
+        rate: parse_rate(row.try_get("rate")?)?,
+        created_at: row.try_get("created_at")?,
+        updated_at: row.try_get("updated_at")?,
+    })
+}
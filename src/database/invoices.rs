@@ -0,0 +1,278 @@
+use anyhow::Result;
+use sqlx::{sqlite::SqliteRow, Row, SqliteConnection};
+
+use super::{insert_transaction, next_sequence_number, Database};
+use crate::models::*;
+
+impl Database {
+    // Invoice operations
+    /// Number the invoice if it has no number, post it and keep its lines, all in one database
+    /// transaction. Every account on the request must already have been resolved.
+    pub async fn create_invoice(&self, company_id: i64, request: CreateInvoiceRequest) -> Result<InvoiceWithDetails> {
+        request.validate()?;
+
+        let mut tx = self.pool.begin().await?;
+
+        let invoice_number = match request.invoice_number {
+            Some(ref number) => number.clone(),
+            None => next_invoice_number(&mut tx, company_id).await?,
+        };
+
+        let posting = request.transaction_request(&invoice_number)?;
+        posting.validate()?;
+        let transaction = insert_transaction(&mut tx, company_id, &posting, None).await?;
+
+        let row = sqlx::query(
+            r#"
+            INSERT INTO invoices
+                (company_id, invoice_number, party_id, invoice_date, due_date, description,
+                 receivable_account_id, tax_account_id, subtotal, tax_amount, total, transaction_id)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            RETURNING id
+            "#,
+        )
+        .bind(company_id)
+        .bind(&invoice_number)
+        .bind(request.party_id)
+        .bind(request.invoice_date)
+        .bind(request.due_date())
+        .bind(&request.description)
+        .bind(request.receivable_account_id)
+        .bind(request.tax_account_id.filter(|_| !request.tax_amount().is_zero()))
+        .bind(to_minor_units(request.subtotal())?)
+        .bind(to_minor_units(request.tax_amount())?)
+        .bind(to_minor_units(request.total())?)
+        .bind(transaction.transaction.id)
+        .fetch_one(&mut *tx)
+        .await?;
+        let id: i64 = row.try_get("id")?;
+
+        for (index, line) in request.lines.iter().enumerate() {
+            sqlx::query(
+                r#"
+                INSERT INTO invoice_lines
+                    (invoice_id, line_number, description, quantity, unit_price, account_id,
+                     tax_rate, amount, tax_amount)
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+                "#,
+            )
+            .bind(id)
+            .bind(index as i64 + 1)
+            .bind(&line.description)
+            .bind(line.quantity.normalize().to_string())
+            .bind(line.unit_price.normalize().to_string())
+            .bind(line.account_id)
+            .bind(line.tax_rate.map(|rate| rate.normalize().to_string()))
+            .bind(to_minor_units(line.amount())?)
+            .bind(to_minor_units(line.tax_amount())?)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        let invoice = fetch_invoice(&mut tx, company_id, id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Invoice {} disappeared after insert", id))?;
+        tx.commit().await?;
+
+        Ok(invoice)
+    }
+
+    pub async fn get_invoice(&self, company_id: i64, id: i64) -> Result<Option<InvoiceWithDetails>> {
+        let mut conn = self.pool.acquire().await?;
+        fetch_invoice(&mut conn, company_id, id).await
+    }
+
+    pub async fn get_invoice_by_number(&self, company_id: i64, invoice_number: &str) -> Result<Option<Invoice>> {
+        let row = sqlx::query("SELECT * FROM invoices WHERE company_id = ? AND invoice_number = ?")
+            .bind(company_id)
+            .bind(invoice_number)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        row.as_ref().map(invoice_from_row).transpose()
+    }
+
+    /// Invoices in number order, optionally for one customer and in one status
+    pub async fn list_invoices(
+        &self,
+        company_id: i64,
+        party_id: Option<i64>,
+        status: Option<InvoiceStatus>,
+    ) -> Result<Vec<InvoiceWithDetails>> {
+        let mut conn = self.pool.acquire().await?;
+
+        let ids: Vec<i64> = sqlx::query_scalar(
+            "SELECT id FROM invoices WHERE company_id = ? AND (? IS NULL OR party_id = ?) ORDER BY invoice_number"
+        )
+        .bind(company_id)
+        .bind(party_id)
+        .bind(party_id)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        let mut invoices = Vec::with_capacity(ids.len());
+        for id in ids {
+            if let Some(invoice) = fetch_invoice(&mut conn, company_id, id).await? {
+                if status.is_none_or(|status| invoice.status == status) {
+                    invoices.push(invoice);
+                }
+            }
+        }
+
+        Ok(invoices)
+    }
+
+    /// Post a payment against an invoice and allocate it. Fails when the invoice is voided or
+    /// the payment is more than its balance due at the time of writing.
+    pub async fn record_invoice_payment(
+        &self,
+        company_id: i64,
+        invoice_id: i64,
+        request: InvoicePaymentRequest,
+    ) -> Result<Option<InvoiceWithDetails>> {
+        request.validate()?;
+
+        let mut tx = self.pool.begin().await?;
+
+        let Some(invoice) = fetch_invoice(&mut tx, company_id, invoice_id).await? else {
+            return Ok(None);
+        };
+        if invoice.status == InvoiceStatus::Voided {
+            return Err(anyhow::anyhow!("Invoice {} has been voided", invoice.invoice.invoice_number));
+        }
+        if request.amount > invoice.balance_due {
+            return Err(anyhow::anyhow!(
+                "Payment of {} is more than the {} due on invoice {}",
+                request.amount, invoice.balance_due, invoice.invoice.invoice_number
+            ));
+        }
+
+        let posting = request.transaction_request(&invoice.invoice)?;
+        posting.validate()?;
+        let transaction = insert_transaction(&mut tx, company_id, &posting, None).await?;
+
+        sqlx::query("INSERT INTO invoice_payments (invoice_id, transaction_id, payment_date, amount) VALUES (?, ?, ?, ?)")
+            .bind(invoice_id)
+            .bind(transaction.transaction.id)
+            .bind(request.payment_date)
+            .bind(to_minor_units(request.amount)?)
+            .execute(&mut *tx)
+            .await?;
+
+        let invoice = fetch_invoice(&mut tx, company_id, invoice_id).await?;
+        tx.commit().await?;
+
+        Ok(invoice)
+    }
+}
+
+/// Hand out the next number of the company's invoice sequence, skipping numbers already
+/// given to invoices by hand
+async fn next_invoice_number(conn: &mut SqliteConnection, company_id: i64) -> Result<String> {
+    loop {
+        let invoice_number = format!("INV-{:05}", next_sequence_number(conn, company_id, "invoice").await?);
+        let taken: bool = sqlx::query_scalar(
+            "SELECT EXISTS (SELECT 1 FROM invoices WHERE company_id = ? AND invoice_number = ?)"
+        )
+        .bind(company_id)
+        .bind(&invoice_number)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        if !taken {
+            return Ok(invoice_number);
+        }
+    }
+}
+
+async fn fetch_invoice(conn: &mut SqliteConnection, company_id: i64, id: i64) -> Result<Option<InvoiceWithDetails>> {
+    let row = sqlx::query(
+        r#"
+        SELECT i.*, t.status AS transaction_status
+        FROM invoices i
+        JOIN transactions t ON i.transaction_id = t.id
+        WHERE i.id = ? AND i.company_id = ?
+        "#,
+    )
+    .bind(id)
+    .bind(company_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+    let Some(row) = row else {
+        return Ok(None);
+    };
+    let invoice = invoice_from_row(&row)?;
+    let invoice_status: TransactionStatus = row.try_get("transaction_status")?;
+
+    let rows = sqlx::query("SELECT * FROM invoice_lines WHERE invoice_id = ? ORDER BY line_number")
+        .bind(id)
+        .fetch_all(&mut *conn)
+        .await?;
+    let lines = rows.iter().map(invoice_line_from_row).collect::<Result<Vec<_>>>()?;
+
+    let rows = sqlx::query(
+        r#"
+        SELECT p.*, t.status AS transaction_status
+        FROM invoice_payments p
+        JOIN transactions t ON p.transaction_id = t.id
+        WHERE p.invoice_id = ?
+        ORDER BY p.payment_date, p.id
+        "#,
+    )
+    .bind(id)
+    .fetch_all(&mut *conn)
+    .await?;
+    let payments = rows
+        .iter()
+        .map(|row| Ok((invoice_payment_from_row(row)?, row.try_get("transaction_status")?)))
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(Some(InvoiceWithDetails::new(invoice, invoice_status, lines, payments)))
+}
+
+fn invoice_from_row(row: &SqliteRow) -> Result<Invoice> {
+    Ok(Invoice {
+        id: row.try_get("id")?,
+        company_id: row.try_get("company_id")?,
+        invoice_number: row.try_get("invoice_number")?,
+        party_id: row.try_get("party_id")?,
+        invoice_date: row.try_get("invoice_date")?,
+        due_date: row.try_get("due_date")?,
+        description: row.try_get("description")?,
+        receivable_account_id: row.try_get("receivable_account_id")?,
+        tax_account_id: row.try_get("tax_account_id")?,
+        subtotal: from_minor_units(row.try_get("subtotal")?),
+        tax_amount: from_minor_units(row.try_get("tax_amount")?),
+        total: from_minor_units(row.try_get("total")?),
+        transaction_id: row.try_get("transaction_id")?,
+        created_at: row.try_get("created_at")?,
+    })
+}
+
+fn invoice_line_from_row(row: &SqliteRow) -> Result<InvoiceLine> {
+    let tax_rate: Option<String> = row.try_get("tax_rate")?;
+
+    Ok(InvoiceLine {
+        id: row.try_get("id")?,
+        invoice_id: row.try_get("invoice_id")?,
+        line_number: row.try_get("line_number")?,
+        description: row.try_get("description")?,
+        quantity: row.try_get::<String, _>("quantity")?.parse()?,
+        unit_price: row.try_get::<String, _>("unit_price")?.parse()?,
+        account_id: row.try_get("account_id")?,
+        tax_rate: tax_rate.map(|rate| rate.parse()).transpose()?,
+        amount: from_minor_units(row.try_get("amount")?),
+        tax_amount: from_minor_units(row.try_get("tax_amount")?),
+    })
+}
+
+fn invoice_payment_from_row(row: &SqliteRow) -> Result<InvoicePayment> {
+    Ok(InvoicePayment {
+        id: row.try_get("id")?,
+        invoice_id: row.try_get("invoice_id")?,
+        transaction_id: row.try_get("transaction_id")?,
+        payment_date: row.try_get("payment_date")?,
+        amount: from_minor_units(row.try_get("amount")?),
+        created_at: row.try_get("created_at")?,
+    })
+}
@@ -1,11 +1,20 @@
-use sqlx::{SqlitePool, Row};
+use sqlx::{sqlite::SqliteRow, SqliteConnection, SqlitePool, Row};
 use anyhow::Result;
+use chrono::NaiveDate;
 use rust_decimal::Decimal;
-use chrono::{DateTime, Utc, NaiveDate};
-use std::str::FromStr;
 
 use crate::models::*;
 
+mod bills;
+mod clearing;
+mod closing;
+mod companies;
+mod currency;
+mod invoices;
+mod parties;
+mod periods;
+mod revaluation;
+
 pub struct Database {
     pool: SqlitePool,
 }
@@ -25,105 +34,60 @@ impl Database {
     }
 
     // Account operations
-    pub async fn create_account(&self, request: CreateAccountRequest) -> Result<Account> {
+    pub async fn create_account(&self, company_id: i64, request: CreateAccountRequest) -> Result<Account> {
         let row = sqlx::query(
             r#"
-            INSERT INTO accounts (code, name, account_type, parent_id)
-            VALUES (?, ?, ?, ?)
+            INSERT INTO accounts
+                (company_id, code, name, account_type, parent_id, currency, is_header, cash_flow_category, control_party_type)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
             RETURNING *
             "#,
         )
+        .bind(company_id)
         .bind(&request.code)
         .bind(&request.name)
         .bind(&request.account_type)
         .bind(request.parent_id)
+        .bind(&request.currency)
+        .bind(request.is_header)
+        .bind(request.cash_flow_category)
+        .bind(request.control_party_type)
         .fetch_one(&self.pool)
         .await?;
 
-        let account = Account {
-            id: row.get("id"),
-            code: row.get("code"),
-            name: row.get("name"),
-            account_type: row.get("account_type"),
-            parent_id: row.get("parent_id"),
-            balance: Decimal::try_from(row.get::<f64, _>("balance")).unwrap_or(Decimal::ZERO),
-            created_at: row.get("created_at"),
-            updated_at: row.get("updated_at"),
-        };
-
-        Ok(account)
+        account_from_row(&row)
     }
 
-    pub async fn get_account(&self, id: i64) -> Result<Option<Account>> {
-        let row = sqlx::query("SELECT * FROM accounts WHERE id = ?")
+    pub async fn get_account(&self, company_id: i64, id: i64) -> Result<Option<Account>> {
+        let row = sqlx::query("SELECT * FROM accounts WHERE id = ? AND company_id = ?")
             .bind(id)
+            .bind(company_id)
             .fetch_optional(&self.pool)
             .await?;
 
-        if let Some(row) = row {
-            let account = Account {
-                id: row.get("id"),
-                code: row.get("code"),
-                name: row.get("name"),
-                account_type: row.get("account_type"),
-                parent_id: row.get("parent_id"),
-                balance: Decimal::try_from(row.get::<f64, _>("balance")).unwrap_or(Decimal::ZERO),
-                created_at: row.get("created_at"),
-                updated_at: row.get("updated_at"),
-            };
-            Ok(Some(account))
-        } else {
-            Ok(None)
-        }
+        row.as_ref().map(account_from_row).transpose()
     }
 
-    pub async fn get_account_by_code(&self, code: &str) -> Result<Option<Account>> {
-        let row = sqlx::query("SELECT * FROM accounts WHERE code = ?")
+    pub async fn get_account_by_code(&self, company_id: i64, code: &str) -> Result<Option<Account>> {
+        let row = sqlx::query("SELECT * FROM accounts WHERE company_id = ? AND code = ?")
+            .bind(company_id)
             .bind(code)
             .fetch_optional(&self.pool)
             .await?;
 
-        if let Some(row) = row {
-            let account = Account {
-                id: row.get("id"),
-                code: row.get("code"),
-                name: row.get("name"),
-                account_type: row.get("account_type"),
-                parent_id: row.get("parent_id"),
-                balance: Decimal::try_from(row.get::<f64, _>("balance")).unwrap_or(Decimal::ZERO),
-                created_at: row.get("created_at"),
-                updated_at: row.get("updated_at"),
-            };
-            Ok(Some(account))
-        } else {
-            Ok(None)
-        }
+        row.as_ref().map(account_from_row).transpose()
     }
 
-    pub async fn list_accounts(&self) -> Result<Vec<Account>> {
-        let rows = sqlx::query("SELECT * FROM accounts ORDER BY code")
+    pub async fn list_accounts(&self, company_id: i64) -> Result<Vec<Account>> {
+        let rows = sqlx::query("SELECT * FROM accounts WHERE company_id = ? ORDER BY code")
+            .bind(company_id)
             .fetch_all(&self.pool)
             .await?;
 
-        let mut accounts = Vec::new();
-        for row in rows {
-            let account = Account {
-                id: row.get("id"),
-                code: row.get("code"),
-                name: row.get("name"),
-                account_type: row.get("account_type"),
-                parent_id: row.get("parent_id"),
-                balance: Decimal::try_from(row.get::<f64, _>("balance")).unwrap_or(Decimal::ZERO),
-                created_at: row.get("created_at"),
-                updated_at: row.get("updated_at"),
-            };
-            accounts.push(account);
-        }
-
-        Ok(accounts)
+        rows.iter().map(account_from_row).collect()
     }
 
-    pub async fn update_account(&self, id: i64, request: UpdateAccountRequest) -> Result<Option<Account>> {
+    pub async fn update_account(&self, company_id: i64, id: i64, request: UpdateAccountRequest) -> Result<Option<Account>> {
         let mut query = "UPDATE accounts SET updated_at = CURRENT_TIMESTAMP".to_string();
         let mut bind_values = Vec::new();
 
@@ -137,7 +101,26 @@ impl Database {
             bind_values.push(parent_id.to_string());
         }
 
-        query.push_str(" WHERE id = ? RETURNING *");
+        if let Some(is_header) = request.is_header {
+            query.push_str(", is_header = ?");
+            bind_values.push(i64::from(is_header).to_string());
+        }
+
+        if let Some(category) = request.cash_flow_category {
+            query.push_str(", cash_flow_category = ?");
+            bind_values.push(category.as_str().to_string());
+        }
+
+        match request.control_party_type {
+            Some(Some(party_type)) => {
+                query.push_str(", control_party_type = ?");
+                bind_values.push(party_type.as_str().to_string());
+            }
+            Some(None) => query.push_str(", control_party_type = NULL"),
+            None => {}
+        }
+
+        query.push_str(" WHERE id = ? AND company_id = ? RETURNING *");
 
         let mut sql_query = sqlx::query(&query);
         
@@ -145,231 +128,302 @@ impl Database {
             sql_query = sql_query.bind(value);
         }
         
-        sql_query = sql_query.bind(id);
+        sql_query = sql_query.bind(id).bind(company_id);
 
         let row = sql_query.fetch_optional(&self.pool).await?;
         
-        if let Some(row) = row {
-            let account = Account {
-                id: row.get("id"),
-                code: row.get("code"),
-                name: row.get("name"),
-                account_type: row.get("account_type"),
-                parent_id: row.get("parent_id"),
-                balance: Decimal::try_from(row.get::<f64, _>("balance")).unwrap_or(Decimal::ZERO),
-                created_at: row.get("created_at"),
-                updated_at: row.get("updated_at"),
-            };
-            Ok(Some(account))
-        } else {
-            Ok(None)
-        }
+        row.as_ref().map(account_from_row).transpose()
     }
 
-    pub async fn delete_account(&self, id: i64) -> Result<bool> {
-        let result = sqlx::query("DELETE FROM accounts WHERE id = ?")
+    pub async fn delete_account(&self, company_id: i64, id: i64) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM accounts WHERE id = ? AND company_id = ?")
             .bind(id)
+            .bind(company_id)
             .execute(&self.pool)
             .await?;
 
         Ok(result.rows_affected() > 0)
     }
 
-    // Transaction operations
-    pub async fn create_transaction(&self, request: CreateTransactionRequest) -> Result<TransactionWithEntries> {
-        // Validate the transaction first
-        request.validate()?;
+    /// Archive an account so it takes no new postings, or bring it back.
+    /// Returns `None` when the account does not exist.
+    pub async fn set_account_active(&self, company_id: i64, id: i64, is_active: bool) -> Result<Option<Account>> {
+        let row = sqlx::query(
+            r#"
+            UPDATE accounts
+            SET is_active = ?,
+                archived_at = CASE WHEN ? THEN NULL ELSE CURRENT_TIMESTAMP END,
+                updated_at = CURRENT_TIMESTAMP
+            WHERE id = ? AND company_id = ?
+            RETURNING *
+            "#,
+        )
+        .bind(is_active)
+        .bind(is_active)
+        .bind(id)
+        .bind(company_id)
+        .fetch_optional(&self.pool)
+        .await?;
 
-        let mut tx = self.pool.begin().await?;
+        row.as_ref().map(account_from_row).transpose()
+    }
 
-        // Create the transaction
+    /// Count the journal lines and child accounts that reference an account
+    pub async fn get_account_references(&self, company_id: i64, id: i64) -> Result<AccountReferences> {
         let row = sqlx::query(
             r#"
-            INSERT INTO transactions (description, reference, transaction_date)
-            VALUES (?, ?, ?)
-            RETURNING *
+            SELECT
+                (SELECT COUNT(*) FROM journal_entries WHERE account_id = ?) as journal_entries,
+                (SELECT COUNT(*) FROM accounts WHERE parent_id = ? AND company_id = ?) as child_accounts
             "#,
         )
-        .bind(&request.description)
-        .bind(&request.reference)
-        .bind(request.transaction_date)
-        .fetch_one(&mut *tx)
+        .bind(id)
+        .bind(id)
+        .bind(company_id)
+        .fetch_one(&self.pool)
         .await?;
 
-        let transaction = Transaction {
-            id: row.get("id"),
-            description: row.get("description"),
-            reference: row.get("reference"),
-            transaction_date: row.get("transaction_date"),
-            created_at: row.get("created_at"),
-            updated_at: row.get("updated_at"),
-        };
+        Ok(AccountReferences {
+            journal_entries: row.try_get("journal_entries")?,
+            child_accounts: row.try_get("child_accounts")?,
+        })
+    }
 
-        // Create journal entries
-        let mut journal_entries = Vec::new();
-        let mut total_debits = Decimal::ZERO;
-        let mut total_credits = Decimal::ZERO;
+    /// Ids of the account and each of its ancestors, nearest first
+    pub async fn get_account_chain(&self, company_id: i64, id: i64) -> Result<Vec<i64>> {
+        let mut conn = self.pool.acquire().await?;
+        fetch_account_chain(&mut conn, company_id, id).await
+    }
 
-        for entry_request in &request.journal_entries {
-            let debit_amount = entry_request.debit_amount.unwrap_or(Decimal::ZERO);
-            let credit_amount = entry_request.credit_amount.unwrap_or(Decimal::ZERO);
-
-            total_debits += debit_amount;
-            total_credits += credit_amount;
-
-            // Insert journal entry
-            sqlx::query(
-                "INSERT INTO journal_entries (transaction_id, account_id, debit_amount, credit_amount, description) VALUES (?, ?, ?, ?, ?)"
-            )
-            .bind(transaction.id)
-            .bind(entry_request.account_id)
-            .bind(f64::try_from(debit_amount).unwrap_or(0.0))
-            .bind(f64::try_from(credit_amount).unwrap_or(0.0))
-            .bind(&entry_request.description)
-            .execute(&mut *tx)
-            .await?;
+    /// Re-parent an account and, with it, its whole subtree in one database transaction.
+    /// The hierarchy is re-checked inside the transaction so concurrent moves cannot form a cycle.
+    /// Returns `None` when the account does not exist.
+    pub async fn move_account(&self, company_id: i64, id: i64, request: MoveAccountRequest) -> Result<Option<AccountMoveResult>> {
+        let mut tx = self.pool.begin().await?;
 
-            // Get the created entry with account details
-            let entry_row = sqlx::query(
-                r#"
-                SELECT
-                    je.id,
-                    je.transaction_id,
-                    je.account_id,
-                    a.code as account_code,
-                    a.name as account_name,
-                    je.debit_amount,
-                    je.credit_amount,
-                    je.description,
-                    je.created_at
-                FROM journal_entries je
-                JOIN accounts a ON je.account_id = a.id
-                WHERE je.id = last_insert_rowid()
-                "#,
-            )
-            .fetch_one(&mut *tx)
-            .await?;
+        let account = match fetch_account(&mut tx, company_id, id).await? {
+            Some(account) => account,
+            None => return Ok(None),
+        };
 
-            let entry = JournalEntryWithAccount {
-                id: entry_row.get("id"),
-                transaction_id: entry_row.get("transaction_id"),
-                account_id: entry_row.get("account_id"),
-                account_code: entry_row.get("account_code"),
-                account_name: entry_row.get("account_name"),
-                debit_amount: Decimal::try_from(entry_row.get::<f64, _>("debit_amount")).unwrap_or(Decimal::ZERO),
-                credit_amount: Decimal::try_from(entry_row.get::<f64, _>("credit_amount")).unwrap_or(Decimal::ZERO),
-                description: entry_row.get("description"),
-                created_at: entry_row.get("created_at"),
-            };
+        let descendants = fetch_descendants(&mut tx, company_id, id).await?;
 
-            journal_entries.push(entry);
+        if let Some(parent_id) = request.parent_id {
+            let parent = fetch_account(&mut tx, company_id, parent_id)
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("Parent account does not exist"))?;
+            let parent_chain = fetch_account_chain(&mut tx, company_id, parent_id).await?;
+            validate_parent(Some(id), &account.account_type, &parent, &parent_chain)?;
+
+            if let Some(other) = descendants.iter().find(|descendant| descendant.account_type != parent.account_type) {
+                return Err(anyhow::anyhow!(
+                    "Account {} below this account is of a different account type than {}",
+                    other.code, parent.code
+                ));
+            }
         }
 
+        let row = sqlx::query(
+            "UPDATE accounts SET parent_id = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ? AND company_id = ? RETURNING *"
+        )
+        .bind(request.parent_id)
+        .bind(id)
+        .bind(company_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
         tx.commit().await?;
 
-        Ok(TransactionWithEntries {
-            transaction,
-            journal_entries,
-            total_debits,
-            total_credits,
-        })
+        Ok(Some(AccountMoveResult {
+            account: account_from_row(&row)?,
+            previous_parent_id: account.parent_id,
+            moved_descendants: descendants,
+        }))
+    }
+
+    // Transaction operations
+    pub async fn create_transaction(&self, company_id: i64, request: CreateTransactionRequest) -> Result<TransactionWithEntries> {
+        // Validate the transaction first
+        request.validate()?;
+
+        let mut tx = self.pool.begin().await?;
+        let transaction = insert_transaction(&mut tx, company_id, &request, None).await?;
+        tx.commit().await?;
+
+        Ok(transaction)
+    }
+
+    /// Post a mirror of an existing transaction that swaps debits and credits on every line.
+    /// Returns `None` when the original transaction does not exist.
+    pub async fn reverse_transaction(
+        &self,
+        company_id: i64,
+        id: i64,
+        request: ReverseTransactionRequest,
+    ) -> Result<Option<TransactionWithEntries>> {
+        let original = match self.get_transaction(company_id, id).await? {
+            Some(original) => original,
+            None => return Ok(None),
+        };
+
+        let reversal = original.reversal_request(&request);
+        reversal.validate()?;
+
+        let mut tx = self.pool.begin().await?;
+        let transaction = insert_transaction(&mut tx, company_id, &reversal, Some(id)).await?;
+        tx.commit().await?;
+
+        Ok(Some(transaction))
     }
 
-    pub async fn get_transaction(&self, id: i64) -> Result<Option<TransactionWithEntries>> {
-        let transaction_row = sqlx::query("SELECT * FROM transactions WHERE id = ?")
+    /// Find the transaction that reverses the given one, if it has been reversed
+    pub async fn get_reversal(&self, company_id: i64, id: i64) -> Result<Option<Transaction>> {
+        let row = sqlx::query("SELECT * FROM transactions WHERE reversal_of_id = ? AND company_id = ?")
             .bind(id)
+            .bind(company_id)
             .fetch_optional(&self.pool)
             .await?;
 
-        if let Some(transaction_row) = transaction_row {
-            let transaction = Transaction {
-                id: transaction_row.get("id"),
-                description: transaction_row.get("description"),
-                reference: transaction_row.get("reference"),
-                transaction_date: transaction_row.get("transaction_date"),
-                created_at: transaction_row.get("created_at"),
-                updated_at: transaction_row.get("updated_at"),
-            };
+        row.as_ref().map(transaction_from_row).transpose()
+    }
 
-            let entry_rows = sqlx::query(
-                r#"
-                SELECT
-                    je.id,
-                    je.transaction_id,
-                    je.account_id,
-                    a.code as account_code,
-                    a.name as account_name,
-                    je.debit_amount,
-                    je.credit_amount,
-                    je.description,
-                    je.created_at
-                FROM journal_entries je
-                JOIN accounts a ON je.account_id = a.id
-                WHERE je.transaction_id = ?
-                ORDER BY je.id
-                "#,
-            )
-            .bind(transaction.id)
-            .fetch_all(&self.pool)
-            .await?;
+    pub async fn get_transaction(&self, company_id: i64, id: i64) -> Result<Option<TransactionWithEntries>> {
+        let mut conn = self.pool.acquire().await?;
+        fetch_transaction(&mut conn, company_id, id).await
+    }
+
+    /// Update a transaction header and, when `journal_entries` is given, replace all of its lines
+    /// in one database transaction. Returns `None` when the transaction does not exist.
+    pub async fn update_transaction(
+        &self,
+        company_id: i64,
+        id: i64,
+        request: UpdateTransactionRequest,
+    ) -> Result<Option<TransactionWithEntries>> {
+        let mut tx = self.pool.begin().await?;
 
-            let mut journal_entries = Vec::new();
-            let mut total_debits = Decimal::ZERO;
-            let mut total_credits = Decimal::ZERO;
-
-            for row in entry_rows {
-                let debit_amount = Decimal::try_from(row.get::<f64, _>("debit_amount")).unwrap_or(Decimal::ZERO);
-                let credit_amount = Decimal::try_from(row.get::<f64, _>("credit_amount")).unwrap_or(Decimal::ZERO);
-                
-                total_debits += debit_amount;
-                total_credits += credit_amount;
-
-                let entry = JournalEntryWithAccount {
-                    id: row.get("id"),
-                    transaction_id: row.get("transaction_id"),
-                    account_id: row.get("account_id"),
-                    account_code: row.get("account_code"),
-                    account_name: row.get("account_name"),
-                    debit_amount,
-                    credit_amount,
-                    description: row.get("description"),
-                    created_at: row.get("created_at"),
-                };
-                journal_entries.push(entry);
+        let existing = match fetch_transaction(&mut tx, company_id, id).await? {
+            Some(existing) => existing,
+            None => return Ok(None),
+        };
+
+        let updated = request.apply_to(&existing);
+        updated.validate()?;
+
+        sqlx::query(
+            r#"
+            UPDATE transactions
+            SET description = ?, reference = ?, transaction_date = ?, updated_at = CURRENT_TIMESTAMP
+            WHERE id = ?
+            "#,
+        )
+        .bind(&updated.description)
+        .bind(&updated.reference)
+        .bind(updated.transaction_date)
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+
+        if request.journal_entries.is_some() {
+            // Rewrite existing lines in place, then drop or append the difference, so the
+            // balance triggers on journal_entries keep accounts.balance in step
+            let mut new_lines = updated.journal_entries.iter();
+            for old_line in &existing.journal_entries {
+                match new_lines.next() {
+                    Some(line) => {
+                        sqlx::query(
+                            r#"
+                            UPDATE journal_entries
+                            SET account_id = ?, debit_amount = ?, credit_amount = ?, description = ?,
+                                currency = ?, currency_amount = ?, exchange_rate = ?, party_id = ?, due_date = ?
+                            WHERE id = ?
+                            "#,
+                        )
+                        .bind(line.account_id)
+                        .bind(to_minor_units(line.debit_amount.unwrap_or(Decimal::ZERO))?)
+                        .bind(to_minor_units(line.credit_amount.unwrap_or(Decimal::ZERO))?)
+                        .bind(&line.description)
+                        .bind(&line.currency)
+                        .bind(line.currency_amount.map(to_minor_units).transpose()?)
+                        .bind(line.exchange_rate.map(|rate| rate.normalize().to_string()))
+                        .bind(line.party_id)
+                        .bind(line.due_date)
+                        .bind(old_line.id)
+                        .execute(&mut *tx)
+                        .await?;
+                    }
+                    None => {
+                        sqlx::query("DELETE FROM journal_entries WHERE id = ?")
+                            .bind(old_line.id)
+                            .execute(&mut *tx)
+                            .await?;
+                    }
+                }
+            }
+
+            for line in new_lines {
+                insert_journal_entry(&mut tx, id, line).await?;
             }
+        }
+
+        let transaction = fetch_transaction(&mut tx, company_id, id).await?;
+        tx.commit().await?;
+
+        Ok(transaction)
+    }
 
-            Ok(Some(TransactionWithEntries {
-                transaction,
-                journal_entries,
-                total_debits,
-                total_credits,
-            }))
-        } else {
-            Ok(None)
+    /// Post a draft transaction so it counts towards balances and reports.
+    /// Returns `None` when the transaction does not exist.
+    pub async fn post_transaction(&self, company_id: i64, id: i64) -> Result<Option<TransactionWithEntries>> {
+        let mut tx = self.pool.begin().await?;
+        if fetch_transaction(&mut tx, company_id, id).await?.is_none() {
+            return Ok(None);
         }
+        mark_posted(&mut tx, id).await?;
+        let transaction = fetch_transaction(&mut tx, company_id, id).await?;
+        tx.commit().await?;
+
+        Ok(transaction)
+    }
+
+    /// Void a draft or posted transaction. Voiding a posted transaction removes its
+    /// effect on balances while keeping its lines for the audit trail.
+    /// Returns `None` when the transaction does not exist.
+    pub async fn void_transaction(&self, company_id: i64, id: i64) -> Result<Option<TransactionWithEntries>> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(
+            r#"
+            UPDATE transactions
+            SET status = 'voided', voided_at = CURRENT_TIMESTAMP, updated_at = CURRENT_TIMESTAMP
+            WHERE id = ? AND company_id = ?
+            "#,
+        )
+        .bind(id)
+        .bind(company_id)
+        .execute(&mut *tx)
+        .await?;
+
+        let transaction = fetch_transaction(&mut tx, company_id, id).await?;
+        tx.commit().await?;
+
+        Ok(transaction)
     }
 
-    pub async fn list_transactions(&self, filter: TransactionFilter) -> Result<Vec<TransactionWithEntries>> {
+    pub async fn list_transactions(&self, company_id: i64, filter: TransactionFilter) -> Result<Vec<TransactionWithEntries>> {
         // For simplicity, let's use a basic query and filter in memory for now
         // In a production system, you'd want to optimize this with proper SQL filtering
         let transaction_rows = sqlx::query(
-            "SELECT * FROM transactions ORDER BY transaction_date DESC, id DESC"
+            "SELECT * FROM transactions WHERE company_id = ? ORDER BY transaction_date DESC, id DESC"
         )
+        .bind(company_id)
         .fetch_all(&self.pool)
         .await?;
 
-        let mut transactions = Vec::new();
-        for row in transaction_rows {
-            let transaction = Transaction {
-                id: row.get("id"),
-                description: row.get("description"),
-                reference: row.get("reference"),
-                transaction_date: row.get("transaction_date"),
-                created_at: row.get("created_at"),
-                updated_at: row.get("updated_at"),
-            };
-            transactions.push(transaction);
-        }
+        let transactions = transaction_rows
+            .iter()
+            .map(transaction_from_row)
+            .collect::<Result<Vec<_>>>()?;
 
         let mut result = Vec::new();
         for transaction in transactions {
@@ -386,13 +440,19 @@ impl Database {
                 }
             }
             
+            if let Some(status) = filter.status {
+                if transaction.status != status {
+                    continue;
+                }
+            }
+
             if let Some(ref description_filter) = filter.description_contains {
                 if !transaction.description.to_lowercase().contains(&description_filter.to_lowercase()) {
                     continue;
                 }
             }
 
-            if let Some(transaction_with_entries) = self.get_transaction(transaction.id).await? {
+            if let Some(transaction_with_entries) = self.get_transaction(company_id, transaction.id).await? {
                 result.push(transaction_with_entries);
             }
             
@@ -407,8 +467,36 @@ impl Database {
         Ok(result)
     }
 
-    pub async fn get_account_summary(&self) -> Result<AccountSummary> {
-        let accounts = self.list_accounts().await?;
+    /// Accounts with `balance` recomputed from posted journal lines dated on or before `as_of`
+    pub async fn list_accounts_as_of(&self, company_id: i64, as_of: NaiveDate) -> Result<Vec<Account>> {
+        let mut conn = self.pool.acquire().await?;
+        let accounts = self.list_accounts(company_id).await?;
+        let activity = fetch_account_activity(&mut conn, company_id, None, Some(as_of), true).await?;
+
+        Ok(with_activity_balances(accounts, &activity))
+    }
+
+    /// Accounts with `balance` set to the net of posted journal lines within a date range,
+    /// leaving out year-end closing entries so revenue and expense accounts show the period's result
+    pub async fn list_accounts_for_period(
+        &self,
+        company_id: i64,
+        start_date: Option<NaiveDate>,
+        end_date: Option<NaiveDate>,
+    ) -> Result<Vec<Account>> {
+        let mut conn = self.pool.acquire().await?;
+        let accounts = self.list_accounts(company_id).await?;
+        let activity = fetch_account_activity(&mut conn, company_id, start_date, end_date, false).await?;
+
+        Ok(with_activity_balances(accounts, &activity))
+    }
+
+    /// Account totals from the cached balances, or as of a date when `as_of` is given
+    pub async fn get_account_summary(&self, company_id: i64, as_of: Option<NaiveDate>) -> Result<AccountSummary> {
+        let accounts = match as_of {
+            Some(as_of) => self.list_accounts_as_of(company_id, as_of).await?,
+            None => self.list_accounts(company_id).await?,
+        };
         let mut summary = AccountSummary::new();
 
         for account in accounts {
@@ -418,7 +506,142 @@ impl Database {
         Ok(summary)
     }
 
-    pub async fn get_trial_balance(&self) -> Result<TrialBalance> {
+    /// Sum posted journal lines per account, optionally limited to a transaction date range
+    pub async fn get_account_activity(
+        &self,
+        company_id: i64,
+        start_date: Option<NaiveDate>,
+        end_date: Option<NaiveDate>,
+    ) -> Result<Vec<AccountBalance>> {
+        let mut conn = self.pool.acquire().await?;
+        fetch_account_activity(&mut conn, company_id, start_date, end_date, true).await
+    }
+
+    /// Opening balance and movements of every postable equity account over a date range.
+    /// Year-end closing entries and their reversals count as net income allocation.
+    pub async fn get_equity_movements(
+        &self,
+        company_id: i64,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+    ) -> Result<Vec<EquityMovement>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT
+                a.id,
+                a.code,
+                a.name,
+                COALESCE(SUM(CASE WHEN je.transaction_date < ? THEN je.credit_amount - je.debit_amount END), 0) as opening,
+                COALESCE(SUM(CASE WHEN je.transaction_date >= ? AND NOT je.is_closing THEN je.credit_amount END), 0) as contributions,
+                COALESCE(SUM(CASE WHEN je.transaction_date >= ? AND NOT je.is_closing THEN je.debit_amount END), 0) as distributions,
+                COALESCE(SUM(CASE WHEN je.transaction_date >= ? AND je.is_closing THEN je.credit_amount - je.debit_amount END), 0) as allocation
+            FROM accounts a
+            LEFT JOIN (
+                SELECT
+                    je.account_id,
+                    je.debit_amount,
+                    je.credit_amount,
+                    t.transaction_date,
+                    t.id IN (
+                        SELECT transaction_id FROM year_end_closes
+                        UNION
+                        SELECT reversal_transaction_id FROM year_end_closes WHERE reversal_transaction_id IS NOT NULL
+                    ) as is_closing
+                FROM journal_entries je
+                JOIN transactions t ON je.transaction_id = t.id
+                WHERE t.status = 'posted' AND t.transaction_date <= ?
+            ) je ON a.id = je.account_id
+            WHERE a.company_id = ? AND a.account_type = 'equity' AND NOT a.is_header
+            GROUP BY a.id, a.code, a.name
+            ORDER BY a.code
+            "#,
+        )
+        .bind(start_date)
+        .bind(start_date)
+        .bind(start_date)
+        .bind(start_date)
+        .bind(end_date)
+        .bind(company_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter()
+            .map(|row| {
+                let opening_balance = from_minor_units(row.try_get("opening")?);
+                let contributions = from_minor_units(row.try_get("contributions")?);
+                let distributions = from_minor_units(row.try_get("distributions")?);
+                let net_income_allocation = from_minor_units(row.try_get("allocation")?);
+
+                Ok(EquityMovement {
+                    account_id: row.try_get("id")?,
+                    account_code: row.try_get("code")?,
+                    account_name: row.try_get("name")?,
+                    opening_balance,
+                    contributions,
+                    distributions,
+                    net_income_allocation,
+                    closing_balance: opening_balance + contributions - distributions + net_income_allocation,
+                })
+            })
+            .collect()
+    }
+
+    /// Posted lines of one account within a date range, with the opening balance brought
+    /// forward from earlier lines. Returns `None` when the account does not exist.
+    pub async fn get_account_statement(
+        &self,
+        company_id: i64,
+        account_id: i64,
+        start_date: Option<NaiveDate>,
+        end_date: Option<NaiveDate>,
+    ) -> Result<Option<AccountStatement>> {
+        let mut conn = self.pool.acquire().await?;
+        let Some(account) = fetch_account(&mut conn, company_id, account_id).await? else {
+            return Ok(None);
+        };
+
+        fetch_account_statement(&mut conn, &account, None, start_date, end_date).await.map(Some)
+    }
+
+    /// Account statements for every postable account with a code in `from_code..=to_code`,
+    /// either bound being optional
+    pub async fn get_general_ledger(
+        &self,
+        company_id: i64,
+        from_code: Option<&str>,
+        to_code: Option<&str>,
+        start_date: Option<NaiveDate>,
+        end_date: Option<NaiveDate>,
+    ) -> Result<Vec<AccountStatement>> {
+        let mut conn = self.pool.acquire().await?;
+        let rows = sqlx::query(
+            r#"
+            SELECT * FROM accounts
+            WHERE company_id = ? AND NOT is_header
+              AND (? IS NULL OR code >= ?)
+              AND (? IS NULL OR code <= ?)
+            ORDER BY code
+            "#,
+        )
+        .bind(company_id)
+        .bind(from_code)
+        .bind(from_code)
+        .bind(to_code)
+        .bind(to_code)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        let mut ledger = Vec::with_capacity(rows.len());
+        for row in &rows {
+            let account = account_from_row(row)?;
+            ledger.push(fetch_account_statement(&mut conn, &account, None, start_date, end_date).await?);
+        }
+
+        Ok(ledger)
+    }
+
+    /// Trial balance over all posted lines, or only those dated on or before `as_of`
+    pub async fn get_trial_balance(&self, company_id: i64, as_of: Option<NaiveDate>) -> Result<TrialBalance> {
         let rows = sqlx::query(
             r#"
             SELECT
@@ -426,14 +649,24 @@ impl Database {
                 a.code,
                 a.name,
                 a.account_type,
-                COALESCE(SUM(je.debit_amount), 0.0) as total_debits,
-                COALESCE(SUM(je.credit_amount), 0.0) as total_credits
+                COALESCE(SUM(je.debit_amount), 0) as total_debits,
+                COALESCE(SUM(je.credit_amount), 0) as total_credits
             FROM accounts a
-            LEFT JOIN journal_entries je ON a.id = je.account_id
+            LEFT JOIN (
+                SELECT je.account_id, je.debit_amount, je.credit_amount
+                FROM journal_entries je
+                JOIN transactions t ON je.transaction_id = t.id
+                WHERE t.status = 'posted'
+                  AND (? IS NULL OR t.transaction_date <= ?)
+            ) je ON a.id = je.account_id
+            WHERE a.company_id = ? AND NOT a.is_header
             GROUP BY a.id, a.code, a.name, a.account_type
             ORDER BY a.code
             "#,
         )
+        .bind(as_of)
+        .bind(as_of)
+        .bind(company_id)
         .fetch_all(&self.pool)
         .await?;
 
@@ -441,8 +674,8 @@ impl Database {
 
         for row in rows {
             let account_type: AccountType = row.get("account_type");
-            let total_debits = Decimal::try_from(row.get::<f64, _>("total_debits")).unwrap_or(Decimal::ZERO);
-            let total_credits = Decimal::try_from(row.get::<f64, _>("total_credits")).unwrap_or(Decimal::ZERO);
+            let total_debits = from_minor_units(row.try_get("total_debits")?);
+            let total_credits = from_minor_units(row.try_get("total_credits")?);
             
             let net_balance = total_debits - total_credits;
             let (debit_balance, credit_balance) = if net_balance >= Decimal::ZERO {
@@ -458,6 +691,7 @@ impl Database {
                 account_type,
                 debit_balance,
                 credit_balance,
+                depth: None,
             };
 
             trial_balance.add_entry(entry);
@@ -466,4 +700,450 @@ impl Database {
         trial_balance.sort_by_type_and_code();
         Ok(trial_balance)
     }
-}
\ No newline at end of file
+}
+
+/// Insert a transaction header and its journal lines on an open connection, as a draft
+/// unless the request asks for it to be posted.
+/// The caller is responsible for validating the request and committing.
+async fn insert_transaction(
+    conn: &mut SqliteConnection,
+    company_id: i64,
+    request: &CreateTransactionRequest,
+    reversal_of_id: Option<i64>,
+) -> Result<TransactionWithEntries> {
+    let row = sqlx::query(
+        r#"
+        INSERT INTO transactions (company_id, description, reference, transaction_date, reversal_of_id, status)
+        VALUES (?, ?, ?, ?, ?, 'draft')
+        RETURNING id
+        "#,
+    )
+    .bind(company_id)
+    .bind(&request.description)
+    .bind(&request.reference)
+    .bind(request.transaction_date)
+    .bind(reversal_of_id)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    let id: i64 = row.try_get("id")?;
+    for entry_request in &request.journal_entries {
+        insert_journal_entry(conn, id, entry_request).await?;
+    }
+
+    // Lines can only be added to drafts, so posting is always a separate step
+    if request.status.unwrap_or(TransactionStatus::Posted) == TransactionStatus::Posted {
+        mark_posted(conn, id).await?;
+    }
+
+    fetch_transaction(conn, company_id, id)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("Transaction {} disappeared after insert", id))
+}
+
+/// Advance the company's numbering sequence for a document type and return the new number
+async fn next_sequence_number(conn: &mut SqliteConnection, company_id: i64, document_type: &str) -> Result<i64> {
+    let number = sqlx::query_scalar(
+        r#"
+        INSERT INTO document_sequences (company_id, document_type, last_number)
+        VALUES (?, ?, 1)
+        ON CONFLICT (company_id, document_type) DO UPDATE SET last_number = last_number + 1
+        RETURNING last_number
+        "#,
+    )
+    .bind(company_id)
+    .bind(document_type)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    Ok(number)
+}
+
+/// Move a draft to posted; the status triggers apply its lines to account balances
+async fn mark_posted(conn: &mut SqliteConnection, id: i64) -> Result<()> {
+    sqlx::query(
+        "UPDATE transactions SET status = 'posted', posted_at = CURRENT_TIMESTAMP, updated_at = CURRENT_TIMESTAMP WHERE id = ?"
+    )
+    .bind(id)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+async fn insert_journal_entry(
+    conn: &mut SqliteConnection,
+    transaction_id: i64,
+    entry: &CreateJournalEntryRequest,
+) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO journal_entries
+            (transaction_id, account_id, debit_amount, credit_amount, description,
+             currency, currency_amount, exchange_rate, party_id, due_date)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(transaction_id)
+    .bind(entry.account_id)
+    .bind(to_minor_units(entry.debit_amount.unwrap_or(Decimal::ZERO))?)
+    .bind(to_minor_units(entry.credit_amount.unwrap_or(Decimal::ZERO))?)
+    .bind(&entry.description)
+    .bind(&entry.currency)
+    .bind(entry.currency_amount.map(to_minor_units).transpose()?)
+    .bind(entry.exchange_rate.map(|rate| rate.normalize().to_string()))
+    .bind(entry.party_id)
+    .bind(entry.due_date)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+async fn fetch_transaction(conn: &mut SqliteConnection, company_id: i64, id: i64) -> Result<Option<TransactionWithEntries>> {
+    let transaction_row = sqlx::query("SELECT * FROM transactions WHERE id = ? AND company_id = ?")
+        .bind(id)
+        .bind(company_id)
+        .fetch_optional(&mut *conn)
+        .await?;
+
+    if let Some(transaction_row) = transaction_row {
+        let transaction = transaction_from_row(&transaction_row)?;
+
+        let entry_rows = sqlx::query(
+            r#"
+            SELECT
+                je.id,
+                je.transaction_id,
+                je.account_id,
+                a.code as account_code,
+                a.name as account_name,
+                je.debit_amount,
+                je.credit_amount,
+                je.description,
+                je.currency,
+                je.currency_amount,
+                je.exchange_rate,
+                je.party_id,
+                je.due_date,
+                je.created_at
+            FROM journal_entries je
+            JOIN accounts a ON je.account_id = a.id
+            WHERE je.transaction_id = ?
+            ORDER BY je.id
+            "#,
+        )
+        .bind(transaction.id)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        let mut journal_entries = Vec::new();
+        let mut total_debits = Decimal::ZERO;
+        let mut total_credits = Decimal::ZERO;
+
+        for row in entry_rows {
+            let entry = journal_entry_from_row(&row)?;
+
+            total_debits += entry.debit_amount;
+            total_credits += entry.credit_amount;
+            journal_entries.push(entry);
+        }
+
+        Ok(Some(TransactionWithEntries {
+            transaction,
+            journal_entries,
+            total_debits,
+            total_credits,
+        }))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Sum posted journal lines per account within an optional date range.
+/// Year-end closing entries and their reversals are skipped unless `include_closing_entries` is set.
+async fn fetch_account_activity(
+    conn: &mut SqliteConnection,
+    company_id: i64,
+    start_date: Option<NaiveDate>,
+    end_date: Option<NaiveDate>,
+    include_closing_entries: bool,
+) -> Result<Vec<AccountBalance>> {
+    let rows = sqlx::query(
+        r#"
+        SELECT
+            a.id,
+            a.code,
+            a.name,
+            a.account_type,
+            COALESCE(SUM(je.debit_amount), 0) as total_debits,
+            COALESCE(SUM(je.credit_amount), 0) as total_credits
+        FROM accounts a
+        LEFT JOIN (
+            SELECT je.account_id, je.debit_amount, je.credit_amount
+            FROM journal_entries je
+            JOIN transactions t ON je.transaction_id = t.id
+            WHERE t.status = 'posted'
+              AND (? IS NULL OR t.transaction_date >= ?)
+              AND (? IS NULL OR t.transaction_date <= ?)
+              AND (? OR t.id NOT IN (
+                  SELECT transaction_id FROM year_end_closes
+                  UNION
+                  SELECT reversal_transaction_id FROM year_end_closes WHERE reversal_transaction_id IS NOT NULL
+              ))
+        ) je ON a.id = je.account_id
+        WHERE a.company_id = ?
+        GROUP BY a.id, a.code, a.name, a.account_type
+        ORDER BY a.code
+        "#,
+    )
+    .bind(start_date)
+    .bind(start_date)
+    .bind(end_date)
+    .bind(end_date)
+    .bind(include_closing_entries)
+    .bind(company_id)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    rows.iter()
+        .map(|row| {
+            let debit_total = from_minor_units(row.try_get("total_debits")?);
+            let credit_total = from_minor_units(row.try_get("total_credits")?);
+
+            Ok(AccountBalance {
+                account_id: row.try_get("id")?,
+                account_code: row.try_get("code")?,
+                account_name: row.try_get("name")?,
+                account_type: row.try_get("account_type")?,
+                balance: debit_total - credit_total,
+                debit_total,
+                credit_total,
+            })
+        })
+        .collect()
+}
+
+/// Build an account's statement from its posted lines, in date order,
+/// optionally only the lines of one party
+async fn fetch_account_statement(
+    conn: &mut SqliteConnection,
+    account: &Account,
+    party_id: Option<i64>,
+    start_date: Option<NaiveDate>,
+    end_date: Option<NaiveDate>,
+) -> Result<AccountStatement> {
+    let opening: i64 = sqlx::query_scalar(
+        r#"
+        SELECT COALESCE(SUM(je.debit_amount - je.credit_amount), 0)
+        FROM journal_entries je
+        JOIN transactions t ON je.transaction_id = t.id
+        WHERE je.account_id = ? AND t.status = 'posted' AND t.transaction_date < ?
+          AND (? IS NULL OR je.party_id = ?)
+        "#,
+    )
+    .bind(account.id)
+    .bind(start_date)
+    .bind(party_id)
+    .bind(party_id)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    let rows = sqlx::query(
+        r#"
+        SELECT
+            je.id,
+            je.transaction_id,
+            t.description as transaction_description,
+            t.reference as transaction_reference,
+            t.transaction_date,
+            je.account_id,
+            a.code as account_code,
+            a.name as account_name,
+            je.debit_amount,
+            je.credit_amount,
+            je.description,
+            je.party_id,
+            je.created_at
+        FROM journal_entries je
+        JOIN transactions t ON je.transaction_id = t.id
+        JOIN accounts a ON je.account_id = a.id
+        WHERE je.account_id = ? AND t.status = 'posted'
+          AND (? IS NULL OR je.party_id = ?)
+          AND (? IS NULL OR t.transaction_date >= ?)
+          AND (? IS NULL OR t.transaction_date <= ?)
+        ORDER BY t.transaction_date, t.id, je.id
+        "#,
+    )
+    .bind(account.id)
+    .bind(party_id)
+    .bind(party_id)
+    .bind(start_date)
+    .bind(start_date)
+    .bind(end_date)
+    .bind(end_date)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let mut statement = AccountStatement::new(
+        account.id,
+        account.code.clone(),
+        account.name.clone(),
+        account.account_type.clone(),
+        from_minor_units(opening),
+    );
+    statement.start_date = start_date;
+    statement.end_date = end_date;
+
+    for row in &rows {
+        statement.add_entry(JournalEntryWithDetails {
+            id: row.try_get("id")?,
+            transaction_id: row.try_get("transaction_id")?,
+            transaction_description: row.try_get("transaction_description")?,
+            transaction_reference: row.try_get("transaction_reference")?,
+            transaction_date: row.try_get("transaction_date")?,
+            account_id: row.try_get("account_id")?,
+            account_code: row.try_get("account_code")?,
+            account_name: row.try_get("account_name")?,
+            debit_amount: from_minor_units(row.try_get("debit_amount")?),
+            credit_amount: from_minor_units(row.try_get("credit_amount")?),
+            description: row.try_get("description")?,
+            party_id: row.try_get("party_id")?,
+            running_balance: Decimal::ZERO,
+            created_at: row.try_get("created_at")?,
+        });
+    }
+
+    Ok(statement)
+}
+
+async fn fetch_account(conn: &mut SqliteConnection, company_id: i64, id: i64) -> Result<Option<Account>> {
+    let row = sqlx::query("SELECT * FROM accounts WHERE id = ? AND company_id = ?")
+        .bind(id)
+        .bind(company_id)
+        .fetch_optional(&mut *conn)
+        .await?;
+
+    row.as_ref().map(account_from_row).transpose()
+}
+
+/// Walk up from an account to the top of its tree. UNION drops repeated rows,
+/// so the walk also ends on a parent cycle.
+async fn fetch_account_chain(conn: &mut SqliteConnection, company_id: i64, id: i64) -> Result<Vec<i64>> {
+    let rows = sqlx::query(
+        r#"
+        WITH RECURSIVE chain(id, parent_id, level) AS (
+            SELECT id, parent_id, 0 FROM accounts WHERE id = ? AND company_id = ?
+            UNION
+            SELECT a.id, a.parent_id, chain.level + 1
+            FROM accounts a
+            JOIN chain ON a.id = chain.parent_id
+            WHERE chain.level < (SELECT COUNT(*) FROM accounts)
+        )
+        SELECT id FROM chain ORDER BY level
+        "#,
+    )
+    .bind(id)
+    .bind(company_id)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    rows.iter().map(|row| Ok(row.try_get("id")?)).collect()
+}
+
+/// Every account below the given one, in code order
+async fn fetch_descendants(conn: &mut SqliteConnection, company_id: i64, id: i64) -> Result<Vec<Account>> {
+    let rows = sqlx::query(
+        r#"
+        WITH RECURSIVE subtree(id) AS (
+            SELECT id FROM accounts WHERE parent_id = ? AND company_id = ?
+            UNION
+            SELECT a.id FROM accounts a JOIN subtree ON a.parent_id = subtree.id
+        )
+        SELECT * FROM accounts WHERE id IN (SELECT id FROM subtree) AND id != ? ORDER BY code
+        "#,
+    )
+    .bind(id)
+    .bind(company_id)
+    .bind(id)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    rows.iter().map(account_from_row).collect()
+}
+
+/// Replace each account's cached balance with its net activity
+fn with_activity_balances(mut accounts: Vec<Account>, activity: &[AccountBalance]) -> Vec<Account> {
+    for account in &mut accounts {
+        account.balance = activity
+            .iter()
+            .find(|balance| balance.account_id == account.id)
+            .map(|balance| balance.balance)
+            .unwrap_or(Decimal::ZERO);
+    }
+
+    accounts
+}
+
+fn account_from_row(row: &SqliteRow) -> Result<Account> {
+    Ok(Account {
+        id: row.try_get("id")?,
+        company_id: row.try_get("company_id")?,
+        code: row.try_get("code")?,
+        name: row.try_get("name")?,
+        account_type: row.try_get("account_type")?,
+        parent_id: row.try_get("parent_id")?,
+        currency: row.try_get("currency")?,
+        balance: from_minor_units(row.try_get("balance")?),
+        is_header: row.try_get("is_header")?,
+        cash_flow_category: row.try_get("cash_flow_category")?,
+        control_party_type: row.try_get("control_party_type")?,
+        is_active: row.try_get("is_active")?,
+        archived_at: row.try_get("archived_at")?,
+        created_at: row.try_get("created_at")?,
+        updated_at: row.try_get("updated_at")?,
+    })
+}
+
+fn transaction_from_row(row: &SqliteRow) -> Result<Transaction> {
+    Ok(Transaction {
+        id: row.try_get("id")?,
+        company_id: row.try_get("company_id")?,
+        description: row.try_get("description")?,
+        reference: row.try_get("reference")?,
+        transaction_date: row.try_get("transaction_date")?,
+        reversal_of_id: row.try_get("reversal_of_id")?,
+        status: row.try_get("status")?,
+        posted_at: row.try_get("posted_at")?,
+        voided_at: row.try_get("voided_at")?,
+        created_at: row.try_get("created_at")?,
+        updated_at: row.try_get("updated_at")?,
+    })
+}
+
+fn journal_entry_from_row(row: &SqliteRow) -> Result<JournalEntryWithAccount> {
+    Ok(JournalEntryWithAccount {
+        id: row.try_get("id")?,
+        transaction_id: row.try_get("transaction_id")?,
+        account_id: row.try_get("account_id")?,
+        account_code: row.try_get("account_code")?,
+        account_name: row.try_get("account_name")?,
+        debit_amount: from_minor_units(row.try_get("debit_amount")?),
+        credit_amount: from_minor_units(row.try_get("credit_amount")?),
+        description: row.try_get("description")?,
+        currency: row.try_get("currency")?,
+        currency_amount: row.try_get::<Option<i64>, _>("currency_amount")?.map(from_minor_units),
+        exchange_rate: row.try_get::<Option<String>, _>("exchange_rate")?
+            .map(|rate| parse_rate(&rate))
+            .transpose()?,
+        party_id: row.try_get("party_id")?,
+        due_date: row.try_get("due_date")?,
+        created_at: row.try_get("created_at")?,
+    })
+}
+
+/// Parse an exchange rate stored as decimal text
+fn parse_rate(rate: &str) -> Result<Decimal> {
+    rate.parse::<Decimal>()
+        .map_err(|e| anyhow::anyhow!("Invalid stored exchange rate {:?}: {}", rate, e))
+}
@@ -0,0 +1,257 @@
+use anyhow::Result;
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use sqlx::{sqlite::SqliteRow, Row};
+
+use super::{account_from_row, fetch_account_statement, Database};
+use crate::models::*;
+
+impl Database {
+    // Party operations
+    pub async fn create_party(&self, company_id: i64, request: CreatePartyRequest) -> Result<Party> {
+        let row = sqlx::query(
+            "INSERT INTO parties (company_id, code, name, party_type) VALUES (?, ?, ?, ?) RETURNING *"
+        )
+        .bind(company_id)
+        .bind(&request.code)
+        .bind(&request.name)
+        .bind(request.party_type)
+        .fetch_one(&self.pool)
+        .await?;
+
+        party_from_row(&row)
+    }
+
+    pub async fn get_party(&self, company_id: i64, id: i64) -> Result<Option<Party>> {
+        let row = sqlx::query("SELECT * FROM parties WHERE id = ? AND company_id = ?")
+            .bind(id)
+            .bind(company_id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        row.as_ref().map(party_from_row).transpose()
+    }
+
+    pub async fn get_party_by_code(&self, company_id: i64, code: &str) -> Result<Option<Party>> {
+        let row = sqlx::query("SELECT * FROM parties WHERE company_id = ? AND code = ?")
+            .bind(company_id)
+            .bind(code)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        row.as_ref().map(party_from_row).transpose()
+    }
+
+    pub async fn list_parties(&self, company_id: i64, party_type: Option<PartyType>) -> Result<Vec<Party>> {
+        let rows = sqlx::query(
+            "SELECT * FROM parties WHERE company_id = ? AND (? IS NULL OR party_type = ?) ORDER BY code"
+        )
+        .bind(company_id)
+        .bind(party_type)
+        .bind(party_type)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(party_from_row).collect()
+    }
+
+    pub async fn update_party(&self, company_id: i64, id: i64, request: UpdatePartyRequest) -> Result<Option<Party>> {
+        let row = sqlx::query(
+            r#"
+            UPDATE parties
+            SET name = COALESCE(?, name), updated_at = CURRENT_TIMESTAMP
+            WHERE id = ? AND company_id = ?
+            RETURNING *
+            "#,
+        )
+        .bind(&request.name)
+        .bind(id)
+        .bind(company_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.as_ref().map(party_from_row).transpose()
+    }
+
+    /// Every control account's balance split by party, from posted lines dated on or before
+    /// `as_of`, or from all posted lines and the cached balances when it is not given
+    pub async fn get_control_account_balances(
+        &self,
+        company_id: i64,
+        as_of: Option<NaiveDate>,
+    ) -> Result<Vec<ControlAccountBalance>> {
+        let accounts = match as_of {
+            Some(as_of) => self.list_accounts_as_of(company_id, as_of).await?,
+            None => self.list_accounts(company_id).await?,
+        };
+
+        let rows = sqlx::query(
+            r#"
+            SELECT
+                je.account_id,
+                p.id as party_id,
+                p.code as party_code,
+                p.name as party_name,
+                SUM(je.debit_amount - je.credit_amount) as balance
+            FROM journal_entries je
+            JOIN transactions t ON je.transaction_id = t.id
+            JOIN accounts a ON je.account_id = a.id
+            LEFT JOIN parties p ON je.party_id = p.id
+            WHERE a.company_id = ? AND a.control_party_type IS NOT NULL
+              AND t.status = 'posted'
+              AND (? IS NULL OR t.transaction_date <= ?)
+            GROUP BY je.account_id, p.id, p.code, p.name
+            ORDER BY p.code
+            "#,
+        )
+        .bind(company_id)
+        .bind(as_of)
+        .bind(as_of)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut balances = Vec::new();
+        for account in accounts {
+            let Some(party_type) = account.control_party_type else {
+                continue;
+            };
+
+            let mut control = ControlAccountBalance {
+                account_id: account.id,
+                account_code: account.code,
+                account_name: account.name,
+                party_type,
+                balance: account.balance,
+                parties: Vec::new(),
+                unassigned: Decimal::ZERO,
+                is_reconciled: true,
+            };
+
+            for row in &rows {
+                if row.try_get::<i64, _>("account_id")? != control.account_id {
+                    continue;
+                }
+                let balance = from_minor_units(row.try_get("balance")?);
+                match row.try_get::<Option<i64>, _>("party_id")? {
+                    Some(party_id) => control.parties.push(PartyBalance {
+                        party_id,
+                        party_code: row.try_get("party_code")?,
+                        party_name: row.try_get("party_name")?,
+                        balance,
+                    }),
+                    None => control.unassigned += balance,
+                }
+            }
+
+            control.update_reconciliation();
+            balances.push(control);
+        }
+
+        Ok(balances)
+    }
+
+    /// Posted lines on the control accounts of one party type dated on or before `as_of`,
+    /// ordered by party and date
+    pub async fn get_control_lines(
+        &self,
+        company_id: i64,
+        party_type: PartyType,
+        as_of: NaiveDate,
+    ) -> Result<Vec<ControlLine>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT
+                je.id,
+                je.transaction_id,
+                je.account_id,
+                je.party_id,
+                p.code as party_code,
+                p.name as party_name,
+                t.transaction_date,
+                je.due_date,
+                t.reference,
+                COALESCE(je.description, t.description) as description,
+                je.debit_amount - je.credit_amount as amount
+            FROM journal_entries je
+            JOIN transactions t ON je.transaction_id = t.id
+            JOIN accounts a ON je.account_id = a.id
+            LEFT JOIN parties p ON je.party_id = p.id
+            WHERE a.company_id = ? AND a.control_party_type = ?
+              AND t.status = 'posted'
+              AND t.transaction_date <= ?
+            ORDER BY p.code, je.party_id, t.transaction_date, t.id, je.id
+            "#,
+        )
+        .bind(company_id)
+        .bind(party_type)
+        .bind(as_of)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(ControlLine {
+                    journal_entry_id: row.try_get("id")?,
+                    transaction_id: row.try_get("transaction_id")?,
+                    account_id: row.try_get("account_id")?,
+                    party_id: row.try_get("party_id")?,
+                    party_code: row.try_get("party_code")?,
+                    party_name: row.try_get("party_name")?,
+                    transaction_date: row.try_get("transaction_date")?,
+                    due_date: row.try_get("due_date")?,
+                    reference: row.try_get("reference")?,
+                    description: row.try_get("description")?,
+                    amount: from_minor_units(row.try_get("amount")?),
+                })
+            })
+            .collect()
+    }
+
+    /// A party's posted lines on the control accounts of its type within a date range.
+    /// Returns `None` when the party does not exist.
+    pub async fn get_party_statement(
+        &self,
+        company_id: i64,
+        party_id: i64,
+        start_date: Option<NaiveDate>,
+        end_date: Option<NaiveDate>,
+    ) -> Result<Option<PartyStatement>> {
+        let Some(party) = self.get_party(company_id, party_id).await? else {
+            return Ok(None);
+        };
+
+        let mut conn = self.pool.acquire().await?;
+        let rows = sqlx::query(
+            "SELECT * FROM accounts WHERE company_id = ? AND control_party_type = ? ORDER BY code"
+        )
+        .bind(company_id)
+        .bind(party.party_type)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        let mut accounts = Vec::with_capacity(rows.len());
+        for row in &rows {
+            let account = account_from_row(row)?;
+            accounts.push(fetch_account_statement(&mut conn, &account, Some(party.id), start_date, end_date).await?);
+        }
+        let closing_balance = accounts.iter().map(|statement| statement.closing_balance).sum();
+
+        Ok(Some(PartyStatement {
+            party,
+            accounts,
+            closing_balance,
+        }))
+    }
+}
+
+fn party_from_row(row: &SqliteRow) -> Result<Party> {
+    Ok(Party {
+        id: row.try_get("id")?,
+        company_id: row.try_get("company_id")?,
+        code: row.try_get("code")?,
+        name: row.try_get("name")?,
+        party_type: row.try_get("party_type")?,
+        created_at: row.try_get("created_at")?,
+        updated_at: row.try_get("updated_at")?,
+    })
+}
@@ -0,0 +1,103 @@
+use anyhow::Result;
+use chrono::NaiveDate;
+use sqlx::{sqlite::SqliteRow, Row};
+
+use super::Database;
+use crate::models::*;
+
+impl Database {
+    // Period operations
+    pub async fn create_period(&self, company_id: i64, request: CreatePeriodRequest) -> Result<Period> {
+        let row = sqlx::query(
+            r#"
+            INSERT INTO periods (company_id, name, start_date, end_date)
+            VALUES (?, ?, ?, ?)
+            RETURNING *
+            "#,
+        )
+        .bind(company_id)
+        .bind(&request.name)
+        .bind(request.start_date)
+        .bind(request.end_date)
+        .fetch_one(&self.pool)
+        .await?;
+
+        period_from_row(&row)
+    }
+
+    pub async fn get_period(&self, company_id: i64, id: i64) -> Result<Option<Period>> {
+        let row = sqlx::query("SELECT * FROM periods WHERE id = ? AND company_id = ?")
+            .bind(id)
+            .bind(company_id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        row.as_ref().map(period_from_row).transpose()
+    }
+
+    pub async fn list_periods(&self, company_id: i64) -> Result<Vec<Period>> {
+        let rows = sqlx::query("SELECT * FROM periods WHERE company_id = ? ORDER BY start_date")
+            .bind(company_id)
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.iter().map(period_from_row).collect()
+    }
+
+    /// Find the period a date falls into, if any period covers it
+    pub async fn get_period_for_date(&self, company_id: i64, date: NaiveDate) -> Result<Option<Period>> {
+        let row = sqlx::query("SELECT * FROM periods WHERE company_id = ? AND start_date <= ? AND end_date >= ?")
+            .bind(company_id)
+            .bind(date)
+            .bind(date)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        row.as_ref().map(period_from_row).transpose()
+    }
+
+    /// List periods that share at least one day with the given range
+    pub async fn find_overlapping_periods(
+        &self,
+        company_id: i64,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+    ) -> Result<Vec<Period>> {
+        let rows = sqlx::query(
+            "SELECT * FROM periods WHERE company_id = ? AND start_date <= ? AND end_date >= ? ORDER BY start_date"
+        )
+        .bind(company_id)
+        .bind(end_date)
+        .bind(start_date)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(period_from_row).collect()
+    }
+
+    pub async fn update_period_status(&self, company_id: i64, id: i64, status: PeriodStatus) -> Result<Option<Period>> {
+        let row = sqlx::query(
+            "UPDATE periods SET status = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ? AND company_id = ? RETURNING *"
+        )
+        .bind(status)
+        .bind(id)
+        .bind(company_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.as_ref().map(period_from_row).transpose()
+    }
+}
+
+fn period_from_row(row: &SqliteRow) -> Result<Period> {
+    Ok(Period {
+        id: row.try_get("id")?,
+        company_id: row.try_get("company_id")?,
+        name: row.try_get("name")?,
+        start_date: row.try_get("start_date")?,
+        end_date: row.try_get("end_date")?,
+        status: row.try_get("status")?,
+        created_at: row.try_get("created_at")?,
+        updated_at: row.try_get("updated_at")?,
+    })
+}
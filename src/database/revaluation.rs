@@ -0,0 +1,276 @@
+use anyhow::Result;
+use chrono::NaiveDate;
+use sqlx::{sqlite::SqliteRow, Row, SqliteConnection};
+
+use super::{currency::fetch_exchange_rate, fetch_transaction, insert_transaction, parse_rate, Database};
+use crate::models::*;
+
+impl Database {
+    // FX revaluation operations
+    pub async fn preview_fx_revaluation(
+        &self,
+        company_id: i64,
+        revaluation_date: NaiveDate,
+        reversal_date: NaiveDate,
+        gain_account_id: i64,
+        loss_account_id: i64,
+    ) -> Result<FxRevaluationPreview> {
+        let mut conn = self.pool.acquire().await?;
+        build_preview(&mut conn, company_id, revaluation_date, reversal_date, gain_account_id, loss_account_id).await
+    }
+
+    /// Post the revaluation adjustment and its reversal, and keep the per-account report.
+    /// The adjustment is recomputed inside the database transaction so it matches the ledger exactly.
+    pub async fn run_fx_revaluation(
+        &self,
+        company_id: i64,
+        revaluation_date: NaiveDate,
+        reversal_date: NaiveDate,
+        gain_account_id: i64,
+        loss_account_id: i64,
+    ) -> Result<FxRevaluationResult> {
+        let mut tx = self.pool.begin().await?;
+
+        let preview = build_preview(&mut tx, company_id, revaluation_date, reversal_date, gain_account_id, loss_account_id).await?;
+        if !preview.missing_rates.is_empty() {
+            return Err(anyhow::anyhow!(
+                "No exchange rate on or before {} for {}",
+                revaluation_date, preview.missing_rates.join(", ")
+            ));
+        }
+        if !preview.has_adjustments() {
+            return Err(anyhow::anyhow!("Nothing to revalue at {}", revaluation_date));
+        }
+        preview.transaction.validate()?;
+
+        let transaction = insert_transaction(&mut tx, company_id, &preview.transaction, None).await?;
+
+        let reversal_request = transaction.reversal_request(&ReverseTransactionRequest {
+            transaction_date: reversal_date,
+            description: Some(format!("Reverse FX revaluation at {}", revaluation_date)),
+            reference: None,
+        });
+        reversal_request.validate()?;
+        let reversal = insert_transaction(&mut tx, company_id, &reversal_request, Some(transaction.transaction.id)).await?;
+
+        let row = sqlx::query(
+            r#"
+            INSERT INTO fx_revaluations
+                (company_id, revaluation_date, reversal_date, gain_account_id, loss_account_id,
+                 transaction_id, reversal_transaction_id)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            RETURNING *
+            "#,
+        )
+        .bind(company_id)
+        .bind(revaluation_date)
+        .bind(reversal_date)
+        .bind(gain_account_id)
+        .bind(loss_account_id)
+        .bind(transaction.transaction.id)
+        .bind(reversal.transaction.id)
+        .fetch_one(&mut *tx)
+        .await?;
+        let revaluation = fx_revaluation_from_row(&row)?;
+
+        for line in &preview.lines {
+            sqlx::query(
+                r#"
+                INSERT INTO fx_revaluation_lines
+                    (revaluation_id, account_id, currency, currency_balance, exchange_rate,
+                     book_balance, revalued_balance, adjustment)
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+                "#,
+            )
+            .bind(revaluation.id)
+            .bind(line.account_id)
+            .bind(&line.currency)
+            .bind(to_minor_units(line.currency_balance)?)
+            .bind(line.exchange_rate.normalize().to_string())
+            .bind(to_minor_units(line.book_balance)?)
+            .bind(to_minor_units(line.revalued_balance)?)
+            .bind(to_minor_units(line.adjustment)?)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(FxRevaluationResult {
+            revaluation,
+            lines: preview.lines,
+            transaction,
+            reversal,
+        })
+    }
+
+    pub async fn get_fx_revaluation(&self, company_id: i64, id: i64) -> Result<Option<FxRevaluationResult>> {
+        let mut conn = self.pool.acquire().await?;
+
+        let row = sqlx::query("SELECT * FROM fx_revaluations WHERE id = ? AND company_id = ?")
+            .bind(id)
+            .bind(company_id)
+            .fetch_optional(&mut *conn)
+            .await?;
+        let revaluation = match row.as_ref().map(fx_revaluation_from_row).transpose()? {
+            Some(revaluation) => revaluation,
+            None => return Ok(None),
+        };
+
+        let rows = sqlx::query(
+            r#"
+            SELECT l.*, a.code AS account_code, a.name AS account_name
+            FROM fx_revaluation_lines l
+            JOIN accounts a ON l.account_id = a.id
+            WHERE l.revaluation_id = ?
+            ORDER BY a.code
+            "#,
+        )
+        .bind(revaluation.id)
+        .fetch_all(&mut *conn)
+        .await?;
+        let lines = rows.iter().map(revaluation_line_from_row).collect::<Result<Vec<_>>>()?;
+
+        let transaction = fetch_transaction(&mut conn, company_id, revaluation.transaction_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Revaluation transaction {} not found", revaluation.transaction_id))?;
+        let reversal = fetch_transaction(&mut conn, company_id, revaluation.reversal_transaction_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Reversal transaction {} not found", revaluation.reversal_transaction_id))?;
+
+        Ok(Some(FxRevaluationResult { revaluation, lines, transaction, reversal }))
+    }
+
+    pub async fn get_fx_revaluation_for_date(&self, company_id: i64, revaluation_date: NaiveDate) -> Result<Option<FxRevaluation>> {
+        let row = sqlx::query("SELECT * FROM fx_revaluations WHERE company_id = ? AND revaluation_date = ?")
+            .bind(company_id)
+            .bind(revaluation_date)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        row.as_ref().map(fx_revaluation_from_row).transpose()
+    }
+
+    pub async fn list_fx_revaluations(&self, company_id: i64) -> Result<Vec<FxRevaluation>> {
+        let rows = sqlx::query("SELECT * FROM fx_revaluations WHERE company_id = ? ORDER BY revaluation_date")
+            .bind(company_id)
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.iter().map(fx_revaluation_from_row).collect()
+    }
+}
+
+async fn build_preview(
+    conn: &mut SqliteConnection,
+    company_id: i64,
+    revaluation_date: NaiveDate,
+    reversal_date: NaiveDate,
+    gain_account_id: i64,
+    loss_account_id: i64,
+) -> Result<FxRevaluationPreview> {
+    // Assets and liabilities are monetary; equity, revenue and expenses stay at historical rates
+    let rows = sqlx::query(
+        r#"
+        SELECT
+            a.id,
+            a.code,
+            a.name,
+            a.currency,
+            COALESCE(SUM(
+                CASE
+                    WHEN je.currency IS NULL THEN 0
+                    WHEN je.debit_amount > 0 THEN je.currency_amount
+                    ELSE -je.currency_amount
+                END
+            ), 0) AS currency_balance,
+            COALESCE(SUM(je.debit_amount - je.credit_amount), 0) AS book_balance
+        FROM accounts a
+        LEFT JOIN (
+            SELECT je.account_id, je.debit_amount, je.credit_amount, je.currency, je.currency_amount
+            FROM journal_entries je
+            JOIN transactions t ON je.transaction_id = t.id
+            WHERE t.status = 'posted' AND t.transaction_date <= ?
+        ) je ON a.id = je.account_id
+        WHERE a.company_id = ? AND a.currency IS NOT NULL AND a.account_type IN ('asset', 'liability')
+        GROUP BY a.id, a.code, a.name, a.currency
+        ORDER BY a.code
+        "#,
+    )
+    .bind(revaluation_date)
+    .bind(company_id)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let mut lines = Vec::new();
+    let mut missing_rates: Vec<String> = Vec::new();
+
+    for row in rows {
+        let currency: String = row.try_get("currency")?;
+        let currency_balance = from_minor_units(row.try_get("currency_balance")?);
+        let book_balance = from_minor_units(row.try_get("book_balance")?);
+        if currency_balance.is_zero() && book_balance.is_zero() {
+            continue;
+        }
+
+        let exchange_rate = match fetch_exchange_rate(conn, company_id, &currency, revaluation_date).await? {
+            Some(rate) => rate.rate,
+            None => {
+                if !missing_rates.contains(&currency) {
+                    missing_rates.push(currency);
+                }
+                continue;
+            }
+        };
+
+        let revalued_balance = convert_to_functional(currency_balance, exchange_rate);
+        lines.push(RevaluationLine {
+            account_id: row.try_get("id")?,
+            account_code: row.try_get("code")?,
+            account_name: row.try_get("name")?,
+            currency,
+            currency_balance,
+            exchange_rate,
+            book_balance,
+            revalued_balance,
+            adjustment: revalued_balance - book_balance,
+        });
+    }
+
+    Ok(FxRevaluationPreview::new(
+        revaluation_date,
+        reversal_date,
+        gain_account_id,
+        loss_account_id,
+        lines,
+        missing_rates,
+    ))
+}
+
+fn fx_revaluation_from_row(row: &SqliteRow) -> Result<FxRevaluation> {
+    Ok(FxRevaluation {
+        id: row.try_get("id")?,
+        company_id: row.try_get("company_id")?,
+        revaluation_date: row.try_get("revaluation_date")?,
+        reversal_date: row.try_get("reversal_date")?,
+        gain_account_id: row.try_get("gain_account_id")?,
+        loss_account_id: row.try_get("loss_account_id")?,
+        transaction_id: row.try_get("transaction_id")?,
+        reversal_transaction_id: row.try_get("reversal_transaction_id")?,
+        created_at: row.try_get("created_at")?,
+    })
+}
+
+fn revaluation_line_from_row(row: &SqliteRow) -> Result<RevaluationLine> {
+    Ok(RevaluationLine {
+        account_id: row.try_get("account_id")?,
+        account_code: row.try_get("account_code")?,
+        account_name: row.try_get("account_name")?,
+        currency: row.try_get("currency")?,
+        currency_balance: from_minor_units(row.try_get("currency_balance")?),
+        exchange_rate: parse_rate(row.try_get("exchange_rate")?)?,
+        book_balance: from_minor_units(row.try_get("book_balance")?),
+        revalued_balance: from_minor_units(row.try_get("revalued_balance")?),
+        adjustment: from_minor_units(row.try_get("adjustment")?),
+    })
+}
@@ -4,47 +4,95 @@ use axum::{
     response::Json,
 };
 use serde::Deserialize;
-use std::sync::Arc;
 
 use crate::{
     models::*,
-    database::Database,
-    handlers::{ApiError, validation_error, not_found_error},
+    handlers::{ApiError, validation_error, not_found_error, conflict_error, reports::LedgerQuery, currency::functional_currency},
     AppState,
 };
 
 #[derive(Debug, Deserialize)]
 pub struct ListAccountsQuery {
     pub account_type: Option<AccountType>,
+    /// `true` lists active accounts only, `false` archived ones only
+    pub is_active: Option<bool>,
 }
 
 pub async fn list_accounts(
     Extension(state): Extension<AppState>,
+    Path(company_id): Path<i64>,
     Query(query): Query<ListAccountsQuery>,
 ) -> Result<Json<Vec<Account>>, ApiError> {
-    let mut accounts = state.database.list_accounts().await?;
+    let mut accounts = state.database.list_accounts(company_id).await?;
 
     // Filter by account type if specified
     if let Some(account_type) = query.account_type {
         accounts.retain(|account| account.account_type == account_type);
     }
 
+    if let Some(is_active) = query.is_active {
+        accounts.retain(|account| account.is_active == is_active);
+    }
+
     Ok(Json(accounts))
 }
 
+#[derive(Debug, Deserialize)]
+pub struct AccountTreeQuery {
+    pub account_type: Option<AccountType>,
+    /// Deepest level to include, where 0 returns top-level accounts only
+    pub depth: Option<usize>,
+}
+
+pub async fn account_tree(
+    Extension(state): Extension<AppState>,
+    Path(company_id): Path<i64>,
+    Query(query): Query<AccountTreeQuery>,
+) -> Result<Json<Vec<AccountTreeNode>>, ApiError> {
+    let mut accounts = state.database.list_accounts(company_id).await?;
+
+    if let Some(account_type) = query.account_type {
+        accounts.retain(|account| account.account_type == account_type);
+    }
+
+    let mut tree = AccountTreeNode::build_forest(accounts);
+    if let Some(depth) = query.depth {
+        for node in &mut tree {
+            node.prune(depth);
+        }
+    }
+
+    Ok(Json(tree))
+}
+
 pub async fn get_account(
     Extension(state): Extension<AppState>,
-    Path(id): Path<i64>,
+    Path((company_id, id)): Path<(i64, i64)>,
 ) -> Result<Json<Account>, ApiError> {
-    let account = state.database.get_account(id).await?
+    let account = state.database.get_account(company_id, id).await?
         .ok_or_else(|| not_found_error("Account"))?;
 
     Ok(Json(account))
 }
 
+/// Posted lines of the account between `start_date` and `end_date` with running balances
+pub async fn account_ledger(
+    Extension(state): Extension<AppState>,
+    Path((company_id, id)): Path<(i64, i64)>,
+    Query(query): Query<LedgerQuery>,
+) -> Result<Json<AccountStatement>, ApiError> {
+    query.validate()?;
+
+    let statement = state.database.get_account_statement(company_id, id, query.start_date, query.end_date).await?
+        .ok_or_else(|| not_found_error("Account"))?;
+
+    Ok(Json(statement))
+}
+
 pub async fn create_account(
     Extension(state): Extension<AppState>,
-    Json(request): Json<CreateAccountRequest>,
+    Path(company_id): Path<i64>,
+    Json(mut request): Json<CreateAccountRequest>,
 ) -> Result<(StatusCode, Json<Account>), ApiError> {
     // Validate account code format (basic validation)
     if request.code.is_empty() {
@@ -55,31 +103,44 @@ pub async fn create_account(
         return Err(validation_error("Account name cannot be empty"));
     }
 
+    // Accounts in the functional currency are stored without one
+    if let Some(ref currency) = request.currency {
+        if let Err(validation_err) = validate_currency_code(currency) {
+            return Err(validation_error(&validation_err.to_string()));
+        }
+        if *currency == functional_currency(&state, company_id).await? {
+            request.currency = None;
+        }
+    }
+
+    if let Some(category) = request.cash_flow_category {
+        if let Err(validation_err) = validate_cash_flow_category(&request.account_type, category) {
+            return Err(validation_error(&validation_err.to_string()));
+        }
+    }
+
     // Check if account code already exists
-    if let Some(_) = state.database.get_account_by_code(&request.code).await? {
+    if state.database.get_account_by_code(company_id, &request.code).await?.is_some() {
         return Err(validation_error("Account code already exists"));
     }
 
-    // Validate parent account exists if specified
+    // Validate the parent account if specified
     if let Some(parent_id) = request.parent_id {
-        if state.database.get_account(parent_id).await?.is_none() {
-            return Err(validation_error("Parent account does not exist"));
-        }
+        check_parent(&state, company_id, None, &request.account_type, parent_id).await?;
     }
 
-    let account = state.database.create_account(request).await?;
+    let account = state.database.create_account(company_id, request).await?;
     Ok((StatusCode::CREATED, Json(account)))
 }
 
 pub async fn update_account(
     Extension(state): Extension<AppState>,
-    Path(id): Path<i64>,
+    Path((company_id, id)): Path<(i64, i64)>,
     Json(request): Json<UpdateAccountRequest>,
 ) -> Result<Json<Account>, ApiError> {
     // Check if account exists
-    if state.database.get_account(id).await?.is_none() {
-        return Err(not_found_error("Account"));
-    }
+    let account = state.database.get_account(company_id, id).await?
+        .ok_or_else(|| not_found_error("Account"))?;
 
     // Validate name if provided
     if let Some(ref name) = request.name {
@@ -88,19 +149,94 @@ pub async fn update_account(
         }
     }
 
-    // Validate parent account exists if specified
+    // Validate the new parent if specified
     if let Some(parent_id) = request.parent_id {
-        if state.database.get_account(parent_id).await?.is_none() {
-            return Err(validation_error("Parent account does not exist"));
+        check_parent(&state, company_id, Some(id), &account.account_type, parent_id).await?;
+    }
+
+    if let Some(category) = request.cash_flow_category {
+        if let Err(validation_err) = validate_cash_flow_category(&account.account_type, category) {
+            return Err(validation_error(&validation_err.to_string()));
+        }
+    }
+
+    // Headers cannot carry journal lines of their own
+    if request.is_header == Some(true) && !account.is_header {
+        let references = state.database.get_account_references(company_id, id).await?;
+        if references.journal_entries > 0 {
+            return Err(conflict_error(&format!(
+                "Account {} has {} journal entries and cannot become a header account",
+                account.code, references.journal_entries
+            )));
         }
-        
-        // Prevent circular references (account cannot be its own parent)
-        if parent_id == id {
-            return Err(validation_error("Account cannot be its own parent"));
+    }
+
+    // Existing lines were checked against the current control party type
+    if let Some(party_type) = request.control_party_type {
+        if party_type != account.control_party_type {
+            let references = state.database.get_account_references(company_id, id).await?;
+            if references.journal_entries > 0 {
+                return Err(conflict_error(&format!(
+                    "Account {} has {} journal entries and cannot change its control party type",
+                    account.code, references.journal_entries
+                )));
+            }
         }
     }
 
-    let account = state.database.update_account(id, request).await?
+    let account = state.database.update_account(company_id, id, request).await?
+        .ok_or_else(|| not_found_error("Account"))?;
+
+    Ok(Json(account))
+}
+
+pub async fn move_account(
+    Extension(state): Extension<AppState>,
+    Path((company_id, id)): Path<(i64, i64)>,
+    Json(request): Json<MoveAccountRequest>,
+) -> Result<Json<AccountMoveResult>, ApiError> {
+    let account = state.database.get_account(company_id, id).await?
+        .ok_or_else(|| not_found_error("Account"))?;
+
+    if let Some(parent_id) = request.parent_id {
+        check_parent(&state, company_id, Some(id), &account.account_type, parent_id).await?;
+    }
+
+    let result = state.database.move_account(company_id, id, request).await?
+        .ok_or_else(|| not_found_error("Account"))?;
+
+    Ok(Json(result))
+}
+
+pub async fn archive_account(
+    Extension(state): Extension<AppState>,
+    Path((company_id, id)): Path<(i64, i64)>,
+) -> Result<Json<Account>, ApiError> {
+    let account = state.database.get_account(company_id, id).await?
+        .ok_or_else(|| not_found_error("Account"))?;
+
+    if !account.is_active {
+        return Err(conflict_error("Account is already archived"));
+    }
+
+    let account = state.database.set_account_active(company_id, id, false).await?
+        .ok_or_else(|| not_found_error("Account"))?;
+
+    Ok(Json(account))
+}
+
+pub async fn restore_account(
+    Extension(state): Extension<AppState>,
+    Path((company_id, id)): Path<(i64, i64)>,
+) -> Result<Json<Account>, ApiError> {
+    let account = state.database.get_account(company_id, id).await?
+        .ok_or_else(|| not_found_error("Account"))?;
+
+    if account.is_active {
+        return Err(conflict_error("Account is not archived"));
+    }
+
+    let account = state.database.set_account_active(company_id, id, true).await?
         .ok_or_else(|| not_found_error("Account"))?;
 
     Ok(Json(account))
@@ -108,21 +244,51 @@ pub async fn update_account(
 
 pub async fn delete_account(
     Extension(state): Extension<AppState>,
-    Path(id): Path<i64>,
+    Path((company_id, id)): Path<(i64, i64)>,
 ) -> Result<StatusCode, ApiError> {
     // Check if account exists
-    if state.database.get_account(id).await?.is_none() {
-        return Err(not_found_error("Account"));
-    }
+    let account = state.database.get_account(company_id, id).await?
+        .ok_or_else(|| not_found_error("Account"))?;
 
-    // TODO: Add validation to prevent deletion of accounts with transactions
-    // This would require additional database queries to check for journal entries
+    // Accounts with history or children are archived instead, so reports keep their lines
+    let references = state.database.get_account_references(company_id, id).await?;
+    if references.journal_entries > 0 {
+        return Err(conflict_error(&format!(
+            "Account {} has {} journal entries and cannot be deleted; archive it instead",
+            account.code, references.journal_entries
+        )));
+    }
+    if references.child_accounts > 0 {
+        return Err(conflict_error(&format!(
+            "Account {} has {} child accounts; move or delete them first",
+            account.code, references.child_accounts
+        )));
+    }
 
-    let deleted = state.database.delete_account(id).await?;
+    let deleted = state.database.delete_account(company_id, id).await?;
     
     if deleted {
         Ok(StatusCode::NO_CONTENT)
     } else {
         Err(not_found_error("Account"))
     }
-}
\ No newline at end of file
+}
+
+// Reject a parent that is missing, of another account type, or would close a cycle
+async fn check_parent(
+    state: &AppState,
+    company_id: i64,
+    account_id: Option<i64>,
+    account_type: &AccountType,
+    parent_id: i64,
+) -> Result<(), ApiError> {
+    let parent = state.database.get_account(company_id, parent_id).await?
+        .ok_or_else(|| validation_error("Parent account does not exist"))?;
+    let parent_chain = state.database.get_account_chain(company_id, parent_id).await?;
+
+    if let Err(validation_err) = validate_parent(account_id, account_type, &parent, &parent_chain) {
+        return Err(validation_error(&validation_err.to_string()));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,203 @@
+use axum::{
+    extract::{Extension, Path, Query},
+    http::{HeaderMap, StatusCode},
+    response::Json,
+};
+use serde::Deserialize;
+
+use crate::{
+    models::*,
+    handlers::{
+        ApiError, validation_error, not_found_error, conflict_error,
+        invoices::resolve_account, periods::ensure_period_open, transactions::check_journal_entries,
+    },
+    AppState,
+};
+
+/// Default accounts for bills and vendor payments
+const PAYABLE_CODE: &str = "2110";
+const CASH_CODE: &str = "1110";
+
+#[derive(Debug, Deserialize)]
+pub struct ListBillsQuery {
+    pub party_id: Option<i64>,
+    pub status: Option<InvoiceStatus>,
+}
+
+pub async fn list_bills(
+    Extension(state): Extension<AppState>,
+    Path(company_id): Path<i64>,
+    Query(query): Query<ListBillsQuery>,
+) -> Result<Json<Vec<BillWithDetails>>, ApiError> {
+    let bills = state.database.list_bills(company_id, query.party_id, query.status).await?;
+    Ok(Json(bills))
+}
+
+pub async fn get_bill(
+    Extension(state): Extension<AppState>,
+    Path((company_id, id)): Path<(i64, i64)>,
+) -> Result<Json<BillWithDetails>, ApiError> {
+    let bill = state.database.get_bill(company_id, id).await?
+        .ok_or_else(|| not_found_error("Bill"))?;
+
+    Ok(Json(bill))
+}
+
+/// Enter a vendor bill: number it and post its lines against the vendor's payable
+pub async fn create_bill(
+    Extension(state): Extension<AppState>,
+    Path(company_id): Path<i64>,
+    headers: HeaderMap,
+    Json(mut request): Json<CreateBillRequest>,
+) -> Result<(StatusCode, Json<BillWithDetails>), ApiError> {
+    if let Err(validation_err) = request.validate() {
+        return Err(validation_error(&validation_err.to_string()));
+    }
+
+    ensure_vendor(&state, company_id, request.party_id).await?;
+
+    if let Some(ref number) = request.bill_number {
+        if state.database.get_bill_by_number(company_id, number).await?.is_some() {
+            return Err(conflict_error(&format!("Bill number {} already exists", number)));
+        }
+    }
+
+    request.payable_account_id = Some(resolve_payable_account(&state, company_id, request.payable_account_id).await?);
+
+    for line in &request.lines {
+        let account = state.database.get_account(company_id, line.account_id).await?
+            .ok_or_else(|| validation_error(&format!("Account with ID {} does not exist", line.account_id)))?;
+        let is_cost = match account.account_type {
+            AccountType::Expense => true,
+            AccountType::Asset => {
+                account.control_party_type.is_none()
+                    && account.effective_cash_flow_category() != Some(CashFlowCategory::Cash)
+            }
+            _ => false,
+        };
+        if !is_cost {
+            return Err(validation_error(&format!("Bill lines must go to expense or inventory accounts; {} is not one", account.code)));
+        }
+    }
+
+    ensure_period_open(&state, company_id, &headers, request.bill_date).await?;
+    check_journal_entries(&state, company_id, &request.journal_entries()?).await?;
+
+    let bill = state.database.create_bill(company_id, request).await?;
+    Ok((StatusCode::CREATED, Json(bill)))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListVendorPaymentsQuery {
+    pub party_id: Option<i64>,
+}
+
+pub async fn list_vendor_payments(
+    Extension(state): Extension<AppState>,
+    Path(company_id): Path<i64>,
+    Query(query): Query<ListVendorPaymentsQuery>,
+) -> Result<Json<Vec<VendorPaymentWithAllocations>>, ApiError> {
+    let payments = state.database.list_vendor_payments(company_id, query.party_id).await?;
+    Ok(Json(payments))
+}
+
+pub async fn get_vendor_payment(
+    Extension(state): Extension<AppState>,
+    Path((company_id, id)): Path<(i64, i64)>,
+) -> Result<Json<VendorPaymentWithAllocations>, ApiError> {
+    let payment = state.database.get_vendor_payment(company_id, id).await?
+        .ok_or_else(|| not_found_error("Vendor payment"))?;
+
+    Ok(Json(payment))
+}
+
+/// Post a payment to a vendor and apply it to bills, holding any remainder as vendor credit
+pub async fn create_vendor_payment(
+    Extension(state): Extension<AppState>,
+    Path(company_id): Path<i64>,
+    headers: HeaderMap,
+    Json(mut request): Json<VendorPaymentRequest>,
+) -> Result<(StatusCode, Json<VendorPaymentWithAllocations>), ApiError> {
+    if let Err(validation_err) = request.validate() {
+        return Err(validation_error(&validation_err.to_string()));
+    }
+
+    ensure_vendor(&state, company_id, request.party_id).await?;
+
+    let account = resolve_account(&state, company_id, request.account_id, CASH_CODE).await?;
+    if account.account_type != AccountType::Asset || account.control_party_type.is_some() {
+        return Err(validation_error(&format!("Payments must be made from a cash or bank account; {} is not one", account.code)));
+    }
+    request.account_id = Some(account.id);
+    let payable_account_id = resolve_payable_account(&state, company_id, request.payable_account_id).await?;
+    request.payable_account_id = Some(payable_account_id);
+
+    check_allocations(&state, company_id, request.party_id, payable_account_id, &request.allocations).await?;
+
+    ensure_period_open(&state, company_id, &headers, request.payment_date).await?;
+    check_journal_entries(&state, company_id, &request.transaction_request()?.journal_entries).await?;
+
+    let payment = state.database.create_vendor_payment(company_id, request).await?;
+    Ok((StatusCode::CREATED, Json(payment)))
+}
+
+/// Apply vendor credit left on a payment to bills
+pub async fn allocate_vendor_payment(
+    Extension(state): Extension<AppState>,
+    Path((company_id, id)): Path<(i64, i64)>,
+    Json(request): Json<AllocateVendorPaymentRequest>,
+) -> Result<Json<VendorPaymentWithAllocations>, ApiError> {
+    let payment = state.database.get_vendor_payment(company_id, id).await?
+        .ok_or_else(|| not_found_error("Vendor payment"))?;
+    if payment.is_voided {
+        return Err(conflict_error(&format!("Vendor payment {} has been voided", id)));
+    }
+
+    if let Err(validation_err) = validate_allocations(&request.allocations, payment.unapplied) {
+        return Err(validation_error(&validation_err.to_string()));
+    }
+    check_allocations(&state, company_id, payment.payment.party_id, payment.payment.payable_account_id, &request.allocations).await?;
+
+    let payment = state.database.allocate_vendor_payment(company_id, id, &request.allocations).await?
+        .ok_or_else(|| not_found_error("Vendor payment"))?;
+
+    Ok(Json(payment))
+}
+
+async fn ensure_vendor(state: &AppState, company_id: i64, party_id: i64) -> Result<(), ApiError> {
+    let party = state.database.get_party(company_id, party_id).await?
+        .ok_or_else(|| validation_error(&format!("Party with ID {} does not exist", party_id)))?;
+    if party.party_type != PartyType::Vendor {
+        return Err(validation_error(&format!("Party {} is not a vendor", party.code)));
+    }
+
+    Ok(())
+}
+
+async fn resolve_payable_account(state: &AppState, company_id: i64, account_id: Option<i64>) -> Result<i64, ApiError> {
+    let account = resolve_account(state, company_id, account_id, PAYABLE_CODE).await?;
+    if account.control_party_type != Some(PartyType::Vendor) {
+        return Err(validation_error(&format!("Account {} is not a vendor control account", account.code)));
+    }
+
+    Ok(account.id)
+}
+
+// Every bill must exist and still take its share of the payment
+async fn check_allocations(
+    state: &AppState,
+    company_id: i64,
+    party_id: i64,
+    payable_account_id: i64,
+    allocations: &[BillAllocationRequest],
+) -> Result<(), ApiError> {
+    for allocation in allocations {
+        let bill = state.database.get_bill(company_id, allocation.bill_id).await?
+            .ok_or_else(|| validation_error(&format!("Bill with ID {} does not exist", allocation.bill_id)))?;
+        if let Some(message) = bill.allocation_rejection(party_id, payable_account_id, allocation.amount) {
+            return Err(validation_error(&message));
+        }
+    }
+
+    Ok(())
+}
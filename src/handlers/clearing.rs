@@ -0,0 +1,85 @@
+use axum::{
+    extract::{Extension, Path, Query},
+    http::StatusCode,
+    response::Json,
+};
+use serde::Deserialize;
+
+use crate::{
+    models::*,
+    handlers::{ApiError, validation_error, not_found_error},
+    AppState,
+};
+
+#[derive(Debug, Deserialize)]
+pub struct OpenItemsQuery {
+    pub party_id: Option<i64>,
+}
+
+/// The account's posted lines that are not fully cleared, with what is left of each
+pub async fn open_items(
+    Extension(state): Extension<AppState>,
+    Path((company_id, account_id)): Path<(i64, i64)>,
+    Query(query): Query<OpenItemsQuery>,
+) -> Result<Json<Vec<UnclearedLine>>, ApiError> {
+    state.database.get_account(company_id, account_id).await?
+        .ok_or_else(|| not_found_error("Account"))?;
+
+    let lines = state.database.get_uncleared_lines(company_id, account_id, query.party_id).await?;
+    Ok(Json(lines))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListClearingGroupsQuery {
+    pub account_id: Option<i64>,
+}
+
+pub async fn list_clearing_groups(
+    Extension(state): Extension<AppState>,
+    Path(company_id): Path<i64>,
+    Query(query): Query<ListClearingGroupsQuery>,
+) -> Result<Json<Vec<ClearingGroupWithLines>>, ApiError> {
+    let groups = state.database.list_clearing_groups(company_id, query.account_id).await?;
+    Ok(Json(groups))
+}
+
+pub async fn get_clearing_group(
+    Extension(state): Extension<AppState>,
+    Path((company_id, id)): Path<(i64, i64)>,
+) -> Result<Json<ClearingGroupWithLines>, ApiError> {
+    let group = state.database.get_clearing_group(company_id, id).await?
+        .ok_or_else(|| not_found_error("Clearing group"))?;
+
+    Ok(Json(group))
+}
+
+/// Match debit and credit lines of one account against each other, in full or in part
+pub async fn create_clearing_group(
+    Extension(state): Extension<AppState>,
+    Path(company_id): Path<i64>,
+    Json(request): Json<CreateClearingGroupRequest>,
+) -> Result<(StatusCode, Json<ClearingGroupWithLines>), ApiError> {
+    state.database.get_account(company_id, request.account_id).await?
+        .ok_or_else(|| validation_error(&format!("Account with ID {} does not exist", request.account_id)))?;
+
+    let open = state.database.get_uncleared_lines(company_id, request.account_id, None).await?;
+    if let Err(validation_err) = clearing_amounts(&open, &request.lines) {
+        return Err(validation_error(&validation_err.to_string()));
+    }
+
+    let group = state.database.create_clearing_group(company_id, &request).await?;
+    Ok((StatusCode::CREATED, Json(group)))
+}
+
+/// Clear open lines of an account that share a party and reference and offset each other
+pub async fn auto_match(
+    Extension(state): Extension<AppState>,
+    Path(company_id): Path<i64>,
+    Json(request): Json<AutoMatchRequest>,
+) -> Result<Json<Vec<ClearingGroupWithLines>>, ApiError> {
+    state.database.get_account(company_id, request.account_id).await?
+        .ok_or_else(|| validation_error(&format!("Account with ID {} does not exist", request.account_id)))?;
+
+    let groups = state.database.auto_match(company_id, &request).await?;
+    Ok(Json(groups))
+}
@@ -0,0 +1,125 @@
+use axum::{
+    extract::{Extension, Path},
+    http::{HeaderMap, StatusCode},
+    response::Json,
+};
+use chrono::NaiveDate;
+
+use crate::{
+    models::*,
+    handlers::{ApiError, validation_error, not_found_error, conflict_error, periods::ensure_period_open},
+    AppState,
+};
+
+/// Default equity account for the year's result
+const RETAINED_EARNINGS_CODE: &str = "3200";
+
+pub async fn list_year_end_closes(
+    Extension(state): Extension<AppState>,
+    Path(company_id): Path<i64>,
+) -> Result<Json<Vec<YearEndClose>>, ApiError> {
+    let closes = state.database.list_year_end_closes(company_id).await?;
+    Ok(Json(closes))
+}
+
+pub async fn preview_year_end_close(
+    Extension(state): Extension<AppState>,
+    Path(company_id): Path<i64>,
+    Json(request): Json<YearEndCloseRequest>,
+) -> Result<Json<YearEndClosePreview>, ApiError> {
+    let equity_account_id = resolve_equity_account(&state, company_id, request.equity_account_id).await?;
+    let (start_date, end_date) = fiscal_year_range(&state, request.fiscal_year)?;
+
+    let preview = state.database
+        .preview_year_end_close(company_id, request.fiscal_year, start_date, end_date, equity_account_id)
+        .await?;
+
+    Ok(Json(preview))
+}
+
+pub async fn close_year(
+    Extension(state): Extension<AppState>,
+    Path(company_id): Path<i64>,
+    headers: HeaderMap,
+    Json(request): Json<YearEndCloseRequest>,
+) -> Result<(StatusCode, Json<YearEndCloseResult>), ApiError> {
+    // Closing is idempotent per year: a closed year must be reopened before closing again
+    if let Some(existing) = state.database.get_year_end_close(company_id, request.fiscal_year).await? {
+        return Err(conflict_error(&format!(
+            "Fiscal year {} is already closed by transaction {}",
+            request.fiscal_year, existing.transaction_id
+        )));
+    }
+
+    let equity_account_id = resolve_equity_account(&state, company_id, request.equity_account_id).await?;
+    let (start_date, end_date) = fiscal_year_range(&state, request.fiscal_year)?;
+
+    ensure_period_open(&state, company_id, &headers, end_date).await?;
+
+    let preview = state.database
+        .preview_year_end_close(company_id, request.fiscal_year, start_date, end_date, equity_account_id)
+        .await?;
+    if !preview.has_activity() {
+        return Err(validation_error(&format!(
+            "Fiscal year {} has no revenue or expense activity to close",
+            request.fiscal_year
+        )));
+    }
+
+    let result = state.database
+        .close_year(company_id, request.fiscal_year, start_date, end_date, equity_account_id)
+        .await?;
+
+    Ok((StatusCode::CREATED, Json(result)))
+}
+
+pub async fn reopen_year(
+    Extension(state): Extension<AppState>,
+    headers: HeaderMap,
+    Path((company_id, fiscal_year)): Path<(i64, i32)>,
+) -> Result<Json<YearEndCloseResult>, ApiError> {
+    let closing = state.database.get_year_end_close(company_id, fiscal_year).await?
+        .ok_or_else(|| not_found_error("Year-end close"))?;
+
+    let closing_transaction = state.database.get_transaction(company_id, closing.transaction_id).await?
+        .ok_or_else(|| not_found_error("Transaction"))?;
+    if closing_transaction.transaction.status != TransactionStatus::Posted {
+        return Err(conflict_error(&format!(
+            "Closing transaction {} is no longer posted",
+            closing.transaction_id
+        )));
+    }
+    if let Some(reversal) = state.database.get_reversal(company_id, closing.transaction_id).await? {
+        return Err(conflict_error(&format!(
+            "Closing transaction {} has already been reversed by transaction {}",
+            closing.transaction_id, reversal.id
+        )));
+    }
+
+    ensure_period_open(&state, company_id, &headers, closing.end_date).await?;
+
+    let result = state.database.reopen_year(company_id, fiscal_year).await?
+        .ok_or_else(|| not_found_error("Year-end close"))?;
+
+    Ok(Json(result))
+}
+
+fn fiscal_year_range(state: &AppState, fiscal_year: i32) -> Result<(NaiveDate, NaiveDate), ApiError> {
+    state.fiscal_calendar
+        .year_range(fiscal_year)
+        .ok_or_else(|| validation_error(&format!("Fiscal year {} is out of range", fiscal_year)))
+}
+
+async fn resolve_equity_account(state: &AppState, company_id: i64, equity_account_id: Option<i64>) -> Result<i64, ApiError> {
+    let account = match equity_account_id {
+        Some(id) => state.database.get_account(company_id, id).await?,
+        None => state.database.get_account_by_code(company_id, RETAINED_EARNINGS_CODE).await?,
+    }
+    .ok_or_else(|| validation_error("Closing equity account does not exist"))?;
+
+    if account.account_type != AccountType::Equity {
+        return Err(validation_error("Year-end result must be closed into an equity account"));
+    }
+
+    Ok(account.id)
+}
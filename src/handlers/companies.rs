@@ -0,0 +1,71 @@
+use std::collections::HashMap;
+
+use axum::{
+    extract::{Extension, Path, Request},
+    http::StatusCode,
+    middleware::Next,
+    response::{Json, Response},
+};
+
+use crate::{
+    models::*,
+    handlers::{ApiError, validation_error, not_found_error},
+    AppState,
+};
+
+pub async fn list_companies(
+    Extension(state): Extension<AppState>,
+) -> Result<Json<Vec<Company>>, ApiError> {
+    let companies = state.database.list_companies().await?;
+    Ok(Json(companies))
+}
+
+pub async fn get_company(
+    Extension(state): Extension<AppState>,
+    Path(id): Path<i64>,
+) -> Result<Json<Company>, ApiError> {
+    let company = state.database.get_company(id).await?
+        .ok_or_else(|| not_found_error("Company"))?;
+
+    Ok(Json(company))
+}
+
+pub async fn create_company(
+    Extension(state): Extension<AppState>,
+    Json(mut request): Json<CreateCompanyRequest>,
+) -> Result<(StatusCode, Json<Company>), ApiError> {
+    if let Err(validation_err) = request.validate() {
+        return Err(validation_error(&validation_err.to_string()));
+    }
+
+    if state.database.get_company_by_code(&request.code).await?.is_some() {
+        return Err(validation_error("Company code already exists"));
+    }
+
+    if request.functional_currency.is_none() {
+        request.functional_currency = Some(state.functional_currency.to_string());
+    }
+
+    let company = state.database.create_company(request).await?;
+    Ok((StatusCode::CREATED, Json(company)))
+}
+
+// Middleware for routes under /api/companies/:cid that answers 404 for unknown companies,
+// so handlers can take the company id from the path as given
+pub async fn require_company(
+    Extension(state): Extension<AppState>,
+    Path(params): Path<HashMap<String, String>>,
+    request: Request,
+    next: Next,
+) -> Result<Response, ApiError> {
+    let company_id = params
+        .get("cid")
+        .and_then(|cid| cid.parse::<i64>().ok())
+        .ok_or_else(|| not_found_error("Company"))?;
+
+    if state.database.get_company(company_id).await?.is_none() {
+        return Err(not_found_error("Company"));
+    }
+
+    Ok(next.run(request).await)
+}
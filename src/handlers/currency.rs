@@ -0,0 +1,105 @@
+use axum::{
+    extract::{Extension, Path, Query},
+    http::StatusCode,
+    response::Json,
+};
+use chrono::NaiveDate;
+use serde::Deserialize;
+
+use crate::{
+    models::*,
+    handlers::{ApiError, validation_error, not_found_error},
+    AppState,
+};
+
+#[derive(Debug, Deserialize)]
+pub struct ListExchangeRatesQuery {
+    pub currency: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExchangeRateQuery {
+    /// Defaults to today
+    pub date: Option<NaiveDate>,
+}
+
+pub async fn list_exchange_rates(
+    Extension(state): Extension<AppState>,
+    Path(company_id): Path<i64>,
+    Query(query): Query<ListExchangeRatesQuery>,
+) -> Result<Json<Vec<ExchangeRate>>, ApiError> {
+    let rates = state.database.list_exchange_rates(company_id, query.currency.as_deref()).await?;
+    Ok(Json(rates))
+}
+
+pub async fn create_exchange_rate(
+    Extension(state): Extension<AppState>,
+    Path(company_id): Path<i64>,
+    Json(request): Json<CreateExchangeRateRequest>,
+) -> Result<(StatusCode, Json<ExchangeRate>), ApiError> {
+    if let Err(validation_err) = request.validate() {
+        return Err(validation_error(&validation_err.to_string()));
+    }
+
+    if request.currency == functional_currency(&state, company_id).await? {
+        return Err(validation_error("The functional currency does not take an exchange rate"));
+    }
+
+    let rate = state.database.upsert_exchange_rate(company_id, request).await?;
+    Ok((StatusCode::CREATED, Json(rate)))
+}
+
+pub async fn get_exchange_rate(
+    Extension(state): Extension<AppState>,
+    Path((company_id, currency)): Path<(i64, String)>,
+    Query(query): Query<ExchangeRateQuery>,
+) -> Result<Json<ExchangeRate>, ApiError> {
+    let date = query.date.unwrap_or_else(|| chrono::Utc::now().date_naive());
+
+    let rate = state.database.get_exchange_rate(company_id, &currency, date).await?
+        .ok_or_else(|| not_found_error("Exchange rate"))?;
+
+    Ok(Json(rate))
+}
+
+/// The currency the company keeps its books in
+pub async fn functional_currency(state: &AppState, company_id: i64) -> Result<String, ApiError> {
+    let company = state.database.get_company(company_id).await?
+        .ok_or_else(|| not_found_error("Company"))?;
+
+    Ok(company.functional_currency)
+}
+
+// Treat lines in the functional currency as plain lines and fill in missing rates
+// from the rate table as of the transaction date
+pub async fn resolve_exchange_rates(
+    state: &AppState,
+    company_id: i64,
+    transaction_date: NaiveDate,
+    journal_entries: &mut [CreateJournalEntryRequest],
+) -> Result<(), ApiError> {
+    let functional_currency = functional_currency(state, company_id).await?;
+
+    for (index, entry) in journal_entries.iter_mut().enumerate() {
+        if entry.currency.as_deref() == Some(&*functional_currency) {
+            entry.currency = None;
+            entry.currency_amount = None;
+            entry.exchange_rate = None;
+            continue;
+        }
+
+        let currency = match entry.currency {
+            Some(ref currency) if entry.exchange_rate.is_none() => currency,
+            _ => continue,
+        };
+
+        let rate = state.database.get_exchange_rate(company_id, currency, transaction_date).await?
+            .ok_or_else(|| validation_error(&format!(
+                "Journal entry {}: no {} exchange rate on or before {}",
+                index + 1, currency, transaction_date
+            )))?;
+        entry.exchange_rate = Some(rate.rate);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,154 @@
+use axum::{
+    extract::{Extension, Path, Query},
+    http::{HeaderMap, StatusCode},
+    response::Json,
+};
+use serde::Deserialize;
+
+use crate::{
+    models::*,
+    handlers::{
+        ApiError, validation_error, not_found_error, conflict_error,
+        periods::ensure_period_open, transactions::check_journal_entries,
+    },
+    AppState,
+};
+
+/// Default accounts for invoices and the payments against them
+const RECEIVABLE_CODE: &str = "1120";
+const SALES_REVENUE_CODE: &str = "4100";
+const SALES_TAX_CODE: &str = "2130";
+const CASH_CODE: &str = "1110";
+
+#[derive(Debug, Deserialize)]
+pub struct ListInvoicesQuery {
+    pub party_id: Option<i64>,
+    pub status: Option<InvoiceStatus>,
+}
+
+pub async fn list_invoices(
+    Extension(state): Extension<AppState>,
+    Path(company_id): Path<i64>,
+    Query(query): Query<ListInvoicesQuery>,
+) -> Result<Json<Vec<InvoiceWithDetails>>, ApiError> {
+    let invoices = state.database.list_invoices(company_id, query.party_id, query.status).await?;
+    Ok(Json(invoices))
+}
+
+pub async fn get_invoice(
+    Extension(state): Extension<AppState>,
+    Path((company_id, id)): Path<(i64, i64)>,
+) -> Result<Json<InvoiceWithDetails>, ApiError> {
+    let invoice = state.database.get_invoice(company_id, id).await?
+        .ok_or_else(|| not_found_error("Invoice"))?;
+
+    Ok(Json(invoice))
+}
+
+/// Issue an invoice: number it and post the receivable, revenue and tax lines
+pub async fn create_invoice(
+    Extension(state): Extension<AppState>,
+    Path(company_id): Path<i64>,
+    headers: HeaderMap,
+    Json(mut request): Json<CreateInvoiceRequest>,
+) -> Result<(StatusCode, Json<InvoiceWithDetails>), ApiError> {
+    if let Err(validation_err) = request.validate() {
+        return Err(validation_error(&validation_err.to_string()));
+    }
+
+    let party = state.database.get_party(company_id, request.party_id).await?
+        .ok_or_else(|| validation_error(&format!("Party with ID {} does not exist", request.party_id)))?;
+    if party.party_type != PartyType::Customer {
+        return Err(validation_error(&format!("Party {} is not a customer", party.code)));
+    }
+
+    if let Some(ref number) = request.invoice_number {
+        if state.database.get_invoice_by_number(company_id, number).await?.is_some() {
+            return Err(conflict_error(&format!("Invoice number {} already exists", number)));
+        }
+    }
+
+    let receivable = resolve_account(&state, company_id, request.receivable_account_id, RECEIVABLE_CODE).await?;
+    if receivable.control_party_type != Some(PartyType::Customer) {
+        return Err(validation_error(&format!("Account {} is not a customer control account", receivable.code)));
+    }
+    request.receivable_account_id = Some(receivable.id);
+
+    for line in &mut request.lines {
+        let account = resolve_account(&state, company_id, line.account_id, SALES_REVENUE_CODE).await?;
+        if account.account_type != AccountType::Revenue {
+            return Err(validation_error(&format!("Invoice lines must go to revenue accounts; {} is not one", account.code)));
+        }
+        line.account_id = Some(account.id);
+    }
+
+    if !request.tax_amount().is_zero() {
+        let account = resolve_account(&state, company_id, request.tax_account_id, SALES_TAX_CODE).await?;
+        if account.account_type != AccountType::Liability {
+            return Err(validation_error(&format!("Sales tax must go to a liability account; {} is not one", account.code)));
+        }
+        request.tax_account_id = Some(account.id);
+    }
+
+    ensure_period_open(&state, company_id, &headers, request.invoice_date).await?;
+    check_journal_entries(&state, company_id, &request.journal_entries()?).await?;
+
+    let invoice = state.database.create_invoice(company_id, request).await?;
+    Ok((StatusCode::CREATED, Json(invoice)))
+}
+
+/// Post a payment received against an invoice and allocate it to the invoice
+pub async fn record_invoice_payment(
+    Extension(state): Extension<AppState>,
+    Path((company_id, id)): Path<(i64, i64)>,
+    headers: HeaderMap,
+    Json(mut request): Json<InvoicePaymentRequest>,
+) -> Result<(StatusCode, Json<InvoiceWithDetails>), ApiError> {
+    if let Err(validation_err) = request.validate() {
+        return Err(validation_error(&validation_err.to_string()));
+    }
+
+    let invoice = state.database.get_invoice(company_id, id).await?
+        .ok_or_else(|| not_found_error("Invoice"))?;
+    if invoice.status == InvoiceStatus::Voided {
+        return Err(conflict_error(&format!("Invoice {} has been voided", invoice.invoice.invoice_number)));
+    }
+    if request.amount > invoice.balance_due {
+        return Err(validation_error(&format!(
+            "Payment of {} is more than the {} due on invoice {}",
+            request.amount, invoice.balance_due, invoice.invoice.invoice_number
+        )));
+    }
+    if request.payment_date < invoice.invoice.invoice_date {
+        return Err(validation_error("Payment date cannot be before the invoice date"));
+    }
+
+    let account = resolve_account(&state, company_id, request.account_id, CASH_CODE).await?;
+    if account.account_type != AccountType::Asset || account.control_party_type.is_some() {
+        return Err(validation_error(&format!("Payments must be received into a cash or bank account; {} is not one", account.code)));
+    }
+    request.account_id = Some(account.id);
+
+    ensure_period_open(&state, company_id, &headers, request.payment_date).await?;
+    check_journal_entries(&state, company_id, &request.transaction_request(&invoice.invoice)?.journal_entries).await?;
+
+    let invoice = state.database.record_invoice_payment(company_id, id, request).await?
+        .ok_or_else(|| not_found_error("Invoice"))?;
+
+    Ok((StatusCode::CREATED, Json(invoice)))
+}
+
+// The given account, or the seeded account with the default code
+pub async fn resolve_account(
+    state: &AppState,
+    company_id: i64,
+    account_id: Option<i64>,
+    default_code: &str,
+) -> Result<Account, ApiError> {
+    match account_id {
+        Some(id) => state.database.get_account(company_id, id).await?
+            .ok_or_else(|| validation_error(&format!("Account with ID {} does not exist", id))),
+        None => state.database.get_account_by_code(company_id, default_code).await?
+            .ok_or_else(|| validation_error(&format!("Default account {} does not exist", default_code))),
+    }
+}
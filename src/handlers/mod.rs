@@ -1,15 +1,26 @@
+pub mod companies;
 pub mod accounts;
+pub mod parties;
+pub mod invoices;
+pub mod bills;
+pub mod clearing;
 pub mod transactions;
 pub mod reports;
+pub mod periods;
+pub mod closing;
+pub mod currency;
+pub mod revaluation;
 pub mod web;
 
 use axum::{
-    http::StatusCode,
+    http::{HeaderMap, StatusCode},
     response::{IntoResponse, Response},
     Json,
 };
 use serde_json::json;
 
+use crate::{models::{Period, PeriodStatus}, AppState};
+
 // Custom error type for API responses
 #[derive(Debug)]
 pub struct ApiError {
@@ -59,4 +70,36 @@ pub fn not_found_error(resource: &str) -> ApiError {
         status: StatusCode::NOT_FOUND,
         message: format!("{} not found", resource),
     }
-}
\ No newline at end of file
+}
+
+// Helper function to create conflict errors
+pub fn conflict_error(message: &str) -> ApiError {
+    ApiError {
+        status: StatusCode::CONFLICT,
+        message: message.to_string(),
+    }
+}
+
+// Helper function to reject postings dated inside a locked accounting period
+pub fn period_locked_error(period: &Period) -> ApiError {
+    let reason = match period.status {
+        PeriodStatus::SoftClosed => "is soft-closed; only admins can post to it",
+        _ => "is hard-closed",
+    };
+
+    ApiError {
+        status: StatusCode::LOCKED,
+        message: format!(
+            "Accounting period {} ({} to {}) {}",
+            period.name, period.start_date, period.end_date, reason
+        ),
+    }
+}
+
+// Requests are treated as coming from an admin when they carry the configured X-Admin-Token
+pub fn is_admin(state: &AppState, headers: &HeaderMap) -> bool {
+    match (&state.admin_token, headers.get("x-admin-token")) {
+        (Some(token), Some(value)) => value.as_bytes() == token.as_bytes(),
+        _ => false,
+    }
+}
@@ -0,0 +1,100 @@
+use axum::{
+    extract::{Extension, Path, Query},
+    http::StatusCode,
+    response::Json,
+};
+use chrono::NaiveDate;
+use serde::Deserialize;
+
+use crate::{
+    models::*,
+    handlers::{ApiError, validation_error, not_found_error, reports::LedgerQuery},
+    AppState,
+};
+
+#[derive(Debug, Deserialize)]
+pub struct ListPartiesQuery {
+    pub party_type: Option<PartyType>,
+}
+
+pub async fn list_parties(
+    Extension(state): Extension<AppState>,
+    Path(company_id): Path<i64>,
+    Query(query): Query<ListPartiesQuery>,
+) -> Result<Json<Vec<Party>>, ApiError> {
+    let parties = state.database.list_parties(company_id, query.party_type).await?;
+    Ok(Json(parties))
+}
+
+pub async fn get_party(
+    Extension(state): Extension<AppState>,
+    Path((company_id, id)): Path<(i64, i64)>,
+) -> Result<Json<Party>, ApiError> {
+    let party = state.database.get_party(company_id, id).await?
+        .ok_or_else(|| not_found_error("Party"))?;
+
+    Ok(Json(party))
+}
+
+pub async fn create_party(
+    Extension(state): Extension<AppState>,
+    Path(company_id): Path<i64>,
+    Json(request): Json<CreatePartyRequest>,
+) -> Result<(StatusCode, Json<Party>), ApiError> {
+    if let Err(validation_err) = request.validate() {
+        return Err(validation_error(&validation_err.to_string()));
+    }
+
+    if state.database.get_party_by_code(company_id, &request.code).await?.is_some() {
+        return Err(validation_error("Party code already exists"));
+    }
+
+    let party = state.database.create_party(company_id, request).await?;
+    Ok((StatusCode::CREATED, Json(party)))
+}
+
+pub async fn update_party(
+    Extension(state): Extension<AppState>,
+    Path((company_id, id)): Path<(i64, i64)>,
+    Json(request): Json<UpdatePartyRequest>,
+) -> Result<Json<Party>, ApiError> {
+    if let Some(ref name) = request.name {
+        if name.trim().is_empty() {
+            return Err(validation_error("Party name cannot be empty"));
+        }
+    }
+
+    let party = state.database.update_party(company_id, id, request).await?
+        .ok_or_else(|| not_found_error("Party"))?;
+
+    Ok(Json(party))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PartyBalancesQuery {
+    pub as_of: Option<NaiveDate>,
+}
+
+/// Each control account's balance split by party, with any lines that name no party
+pub async fn party_balances(
+    Extension(state): Extension<AppState>,
+    Path(company_id): Path<i64>,
+    Query(query): Query<PartyBalancesQuery>,
+) -> Result<Json<Vec<ControlAccountBalance>>, ApiError> {
+    let balances = state.database.get_control_account_balances(company_id, query.as_of).await?;
+    Ok(Json(balances))
+}
+
+/// The party's lines on its control accounts with running balances
+pub async fn party_statement(
+    Extension(state): Extension<AppState>,
+    Path((company_id, id)): Path<(i64, i64)>,
+    Query(query): Query<LedgerQuery>,
+) -> Result<Json<PartyStatement>, ApiError> {
+    query.validate()?;
+
+    let statement = state.database.get_party_statement(company_id, id, query.start_date, query.end_date).await?
+        .ok_or_else(|| not_found_error("Party"))?;
+
+    Ok(Json(statement))
+}
@@ -0,0 +1,112 @@
+use axum::{
+    extract::{Extension, Path},
+    http::{HeaderMap, StatusCode},
+    response::Json,
+};
+use chrono::NaiveDate;
+
+use crate::{
+    models::*,
+    handlers::{ApiError, validation_error, not_found_error, conflict_error, period_locked_error, is_admin},
+    AppState,
+};
+
+pub async fn list_periods(
+    Extension(state): Extension<AppState>,
+    Path(company_id): Path<i64>,
+) -> Result<Json<Vec<Period>>, ApiError> {
+    let periods = state.database.list_periods(company_id).await?;
+    Ok(Json(periods))
+}
+
+pub async fn get_period(
+    Extension(state): Extension<AppState>,
+    Path((company_id, id)): Path<(i64, i64)>,
+) -> Result<Json<Period>, ApiError> {
+    let period = state.database.get_period(company_id, id).await?
+        .ok_or_else(|| not_found_error("Period"))?;
+
+    Ok(Json(period))
+}
+
+pub async fn create_period(
+    Extension(state): Extension<AppState>,
+    Path(company_id): Path<i64>,
+    Json(request): Json<CreatePeriodRequest>,
+) -> Result<(StatusCode, Json<Period>), ApiError> {
+    if let Err(validation_err) = request.validate() {
+        return Err(validation_error(&validation_err.to_string()));
+    }
+
+    // Every date belongs to at most one period
+    let overlapping = state.database
+        .find_overlapping_periods(company_id, request.start_date, request.end_date)
+        .await?;
+    if let Some(existing) = overlapping.first() {
+        return Err(validation_error(&format!(
+            "Period overlaps with existing period {} ({} to {})",
+            existing.name, existing.start_date, existing.end_date
+        )));
+    }
+
+    let period = state.database.create_period(company_id, request).await?;
+    Ok((StatusCode::CREATED, Json(period)))
+}
+
+pub async fn update_period_status(
+    Extension(state): Extension<AppState>,
+    headers: HeaderMap,
+    Path((company_id, id)): Path<(i64, i64)>,
+    Json(request): Json<UpdatePeriodStatusRequest>,
+) -> Result<Json<Period>, ApiError> {
+    let period = state.database.get_period(company_id, id).await?
+        .ok_or_else(|| not_found_error("Period"))?;
+
+    // Hard-closed periods are final
+    if period.status == PeriodStatus::HardClosed && request.status != PeriodStatus::HardClosed {
+        return Err(conflict_error("Hard-closed periods cannot be reopened"));
+    }
+
+    // Reopening a soft-closed period would bypass the soft lock
+    if period.status == PeriodStatus::SoftClosed
+        && request.status == PeriodStatus::Open
+        && !is_admin(&state, &headers)
+    {
+        return Err(ApiError {
+            status: StatusCode::FORBIDDEN,
+            message: "Only admins can reopen a soft-closed period".to_string(),
+        });
+    }
+
+    // A hard close cannot be undone, so only admins may lock a period for good
+    if period.status != PeriodStatus::HardClosed
+        && request.status == PeriodStatus::HardClosed
+        && !is_admin(&state, &headers)
+    {
+        return Err(ApiError {
+            status: StatusCode::FORBIDDEN,
+            message: "Only admins can hard-close a period".to_string(),
+        });
+    }
+
+    let period = state.database.update_period_status(company_id, id, request.status).await?
+        .ok_or_else(|| not_found_error("Period"))?;
+
+    Ok(Json(period))
+}
+
+/// Reject writes dated inside a period that is locked for the current user
+pub async fn ensure_period_open(
+    state: &AppState,
+    company_id: i64,
+    headers: &HeaderMap,
+    date: NaiveDate,
+) -> Result<(), ApiError> {
+    if let Some(period) = state.database.get_period_for_date(company_id, date).await? {
+        if !period.status.accepts_postings(is_admin(state, headers)) {
+            return Err(period_locked_error(&period));
+        }
+    }
+
+    Ok(())
+}
@@ -1,56 +1,415 @@
 use axum::{
-    extract::Extension,
+    extract::{Extension, Path, Query},
     response::Json,
 };
-use std::sync::Arc;
+use chrono::NaiveDate;
+use serde::Deserialize;
 
 use crate::{
     models::*,
-    database::Database,
-    handlers::ApiError,
+    handlers::{ApiError, validation_error},
     AppState,
 };
 
+/// Options shared by the reports: the date they are drawn up at and how they treat the account hierarchy
+#[derive(Debug, Default, Deserialize)]
+pub struct ReportQuery {
+    /// Compute balances from posted journal lines dated on or before this date
+    /// instead of the current account balances
+    pub as_of: Option<NaiveDate>,
+    /// Date range of the income statement
+    pub start_date: Option<NaiveDate>,
+    pub end_date: Option<NaiveDate>,
+    /// Income statement range running up to `end_date` (default today) from the start of
+    /// the current fiscal month, quarter or year
+    pub period: Option<PeriodPreset>,
+    /// Roll child balances up into their parents, with a subtotal on every level
+    #[serde(default)]
+    pub rollup: bool,
+    /// Deepest level to list when rolling up, where 0 lists top-level accounts only.
+    /// Setting a depth implies `rollup`.
+    pub depth: Option<usize>,
+    /// Spacing of the columns of a comparative statement (default `previous_year`)
+    pub compare: Option<Comparison>,
+    /// Number of columns of a comparative statement, the requested period included (default 2)
+    pub periods: Option<u32>,
+}
+
+impl ReportQuery {
+    pub fn is_rollup(&self) -> bool {
+        self.rollup || self.depth.is_some()
+    }
+
+    /// Arrange accounts into trees cut off at the requested depth
+    pub fn forest(&self, accounts: Vec<Account>) -> Vec<AccountTreeNode> {
+        let mut forest = AccountTreeNode::build_forest(accounts);
+        if let Some(depth) = self.depth {
+            for node in &mut forest {
+                node.prune(depth);
+            }
+        }
+        forest
+    }
+
+    /// Resolve the income statement range from explicit dates or a preset.
+    /// `as_of` stands in for a missing `end_date`.
+    pub fn date_range(&self, calendar: &FiscalCalendar) -> Result<(Option<NaiveDate>, Option<NaiveDate>), ApiError> {
+        let end_date = self.end_date.or(self.as_of);
+
+        let (start_date, end_date) = match self.period {
+            Some(preset) => {
+                if self.start_date.is_some() {
+                    return Err(validation_error("Use either period or start_date, not both"));
+                }
+                let end_date = end_date.unwrap_or_else(|| chrono::Utc::now().date_naive());
+                let (start_date, end_date) = preset
+                    .resolve(calendar, end_date)
+                    .ok_or_else(|| validation_error("The period starts before the earliest supported date"))?;
+                (Some(start_date), Some(end_date))
+            }
+            None => (self.start_date, end_date),
+        };
+
+        if let (Some(start_date), Some(end_date)) = (start_date, end_date) {
+            if start_date > end_date {
+                return Err(validation_error("Start date must not be after end date"));
+            }
+        }
+
+        Ok((start_date, end_date))
+    }
+
+    /// A range that must have a start, from `start_date` or `period`, ending at `end_date` or today
+    pub fn bounded_date_range(&self, calendar: &FiscalCalendar) -> Result<(NaiveDate, NaiveDate), ApiError> {
+        let (start_date, end_date) = self.date_range(calendar)?;
+        let start_date = start_date.ok_or_else(|| validation_error("This report needs a start_date or period"))?;
+        let end_date = end_date.unwrap_or_else(|| chrono::Utc::now().date_naive());
+        if start_date > end_date {
+            return Err(validation_error("Start date must not be after end date"));
+        }
+
+        Ok((start_date, end_date))
+    }
+
+    /// Column spacing and count of a comparative statement
+    pub fn comparison(&self) -> Result<(Comparison, u32), ApiError> {
+        let periods = self.periods.unwrap_or(2);
+        if !(2..=12).contains(&periods) {
+            return Err(validation_error("Comparative statements take between 2 and 12 periods"));
+        }
+
+        Ok((self.compare.unwrap_or(Comparison::PreviousYear), periods))
+    }
+
+    /// Lay out a balance sheet from accounts with their balances at the report date
+    pub fn balance_sheet(&self, accounts: Vec<Account>) -> BalanceSheet {
+        let mut balance_sheet = BalanceSheet::new();
+
+        if self.is_rollup() {
+            for node in self.forest(accounts) {
+                balance_sheet.add_tree(&node);
+            }
+        } else {
+            // Headers only appear as roll-up nodes
+            for account in accounts.iter().filter(|account| !account.is_header) {
+                balance_sheet.add_account(account);
+            }
+        }
+
+        balance_sheet
+    }
+
+    /// Lay out an income statement from accounts with their activity over the given range
+    pub fn income_statement(
+        &self,
+        mut accounts: Vec<Account>,
+        start_date: Option<NaiveDate>,
+        end_date: Option<NaiveDate>,
+    ) -> IncomeStatement {
+        accounts.retain(|account| matches!(account.account_type, AccountType::Revenue | AccountType::Expense));
+        let mut income_statement = IncomeStatement::new();
+        income_statement.start_date = start_date;
+        income_statement.end_date = end_date;
+
+        if self.is_rollup() {
+            for node in self.forest(accounts) {
+                income_statement.add_tree(&node);
+            }
+        } else {
+            for account in accounts.iter().filter(|account| !account.is_header) {
+                income_statement.add_account(account);
+            }
+        }
+
+        income_statement
+    }
+
+    /// The company's accounts with balances as of the requested date
+    async fn accounts(&self, state: &AppState, company_id: i64) -> anyhow::Result<Vec<Account>> {
+        match self.as_of {
+            Some(as_of) => state.database.list_accounts_as_of(company_id, as_of).await,
+            None => state.database.list_accounts(company_id).await,
+        }
+    }
+}
+
+/// Range of a ledger and, for the general ledger, the account codes it covers
+#[derive(Debug, Default, Deserialize)]
+pub struct LedgerQuery {
+    pub start_date: Option<NaiveDate>,
+    pub end_date: Option<NaiveDate>,
+    pub from_code: Option<String>,
+    pub to_code: Option<String>,
+    /// Also list accounts with neither an opening balance nor lines in the range
+    #[serde(default)]
+    pub include_empty: bool,
+}
+
+impl LedgerQuery {
+    pub fn validate(&self) -> Result<(), ApiError> {
+        if let (Some(start_date), Some(end_date)) = (self.start_date, self.end_date) {
+            if start_date > end_date {
+                return Err(validation_error("Start date must not be after end date"));
+            }
+        }
+        if let (Some(from_code), Some(to_code)) = (&self.from_code, &self.to_code) {
+            if from_code > to_code {
+                return Err(validation_error("from_code must not come after to_code"));
+            }
+        }
+
+        Ok(())
+    }
+}
+
 pub async fn account_summary(
     Extension(state): Extension<AppState>,
+    Path(company_id): Path<i64>,
+    Query(query): Query<ReportQuery>,
 ) -> Result<Json<AccountSummary>, ApiError> {
-    let summary = state.database.get_account_summary().await?;
+    let summary = state.database.get_account_summary(company_id, query.as_of).await?;
     Ok(Json(summary))
 }
 
 pub async fn trial_balance(
     Extension(state): Extension<AppState>,
+    Path(company_id): Path<i64>,
+    Query(query): Query<ReportQuery>,
 ) -> Result<Json<TrialBalance>, ApiError> {
-    let trial_balance = state.database.get_trial_balance().await?;
+    if !query.is_rollup() {
+        let trial_balance = state.database.get_trial_balance(company_id, query.as_of).await?;
+        return Ok(Json(trial_balance));
+    }
+
+    // Account balances only include posted lines, like the flat trial balance
+    let accounts = query.accounts(&state, company_id).await?;
+    let mut trial_balance = TrialBalance::new();
+
+    for node in query.forest(accounts) {
+        trial_balance.add_tree(&node);
+    }
+
     Ok(Json(trial_balance))
 }
 
 pub async fn balance_sheet(
     Extension(state): Extension<AppState>,
+    Path(company_id): Path<i64>,
+    Query(query): Query<ReportQuery>,
 ) -> Result<Json<BalanceSheet>, ApiError> {
-    let accounts = state.database.list_accounts().await?;
-    let mut balance_sheet = BalanceSheet::new();
+    let accounts = query.accounts(&state, company_id).await?;
+    Ok(Json(query.balance_sheet(accounts)))
+}
+
+pub async fn income_statement(
+    Extension(state): Extension<AppState>,
+    Path(company_id): Path<i64>,
+    Query(query): Query<ReportQuery>,
+) -> Result<Json<IncomeStatement>, ApiError> {
+    let (start_date, end_date) = query.date_range(&state.fiscal_calendar)?;
+    let accounts = if start_date.is_some() || end_date.is_some() {
+        state.database.list_accounts_for_period(company_id, start_date, end_date).await?
+    } else {
+        state.database.list_accounts(company_id).await?
+    };
 
-    for account in accounts {
-        balance_sheet.add_account(&account);
+    Ok(Json(query.income_statement(accounts, start_date, end_date)))
+}
+
+/// Account statements for a range of account codes over a date range
+pub async fn general_ledger(
+    Extension(state): Extension<AppState>,
+    Path(company_id): Path<i64>,
+    Query(query): Query<LedgerQuery>,
+) -> Result<Json<Vec<AccountStatement>>, ApiError> {
+    query.validate()?;
+
+    let mut ledger = state.database.get_general_ledger(
+        company_id,
+        query.from_code.as_deref(),
+        query.to_code.as_deref(),
+        query.start_date,
+        query.end_date,
+    ).await?;
+
+    if !query.include_empty {
+        ledger.retain(|statement| !statement.is_empty());
     }
 
-    Ok(Json(balance_sheet))
+    Ok(Json(ledger))
 }
 
-pub async fn income_statement(
+/// Date and basis of an aging report
+#[derive(Debug, Default, Deserialize)]
+pub struct AgingQuery {
+    /// Report date, default today
+    pub as_of: Option<NaiveDate>,
+    /// `due_date` (default) or `document_date`
+    #[serde(default)]
+    pub age_by: AgingBasis,
+}
+
+pub async fn aged_receivables(
     Extension(state): Extension<AppState>,
-) -> Result<Json<IncomeStatement>, ApiError> {
-    let accounts = state.database.list_accounts().await?;
-    let mut income_statement = IncomeStatement::new();
+    Path(company_id): Path<i64>,
+    Query(query): Query<AgingQuery>,
+) -> Result<Json<AgingReport>, ApiError> {
+    let report = aging_report(&state, company_id, PartyType::Customer, &query).await?;
+    Ok(Json(report))
+}
 
-    for account in accounts {
-        if matches!(account.account_type, AccountType::Revenue | AccountType::Expense) {
-            income_statement.add_account(&account);
-        }
+pub async fn aged_payables(
+    Extension(state): Extension<AppState>,
+    Path(company_id): Path<i64>,
+    Query(query): Query<AgingQuery>,
+) -> Result<Json<AgingReport>, ApiError> {
+    let report = aging_report(&state, company_id, PartyType::Vendor, &query).await?;
+    Ok(Json(report))
+}
+
+/// Age the open items on the control accounts of one party type and tie them to the
+/// control balance in the trial balance
+pub async fn aging_report(
+    state: &AppState,
+    company_id: i64,
+    party_type: PartyType,
+    query: &AgingQuery,
+) -> anyhow::Result<AgingReport> {
+    let as_of = query.as_of.unwrap_or_else(|| chrono::Utc::now().date_naive());
+    let lines = state.database.get_control_lines(company_id, party_type, as_of).await?;
+    let mut report = AgingReport::build(as_of, party_type, query.age_by, lines);
+
+    let control_accounts: Vec<i64> = state.database.list_accounts(company_id).await?
+        .into_iter()
+        .filter(|account| account.control_party_type == Some(party_type))
+        .map(|account| account.id)
+        .collect();
+    let trial_balance = state.database.get_trial_balance(company_id, Some(as_of)).await?;
+    let balance: rust_decimal::Decimal = trial_balance.entries
+        .iter()
+        .filter(|entry| control_accounts.contains(&entry.account_id))
+        .map(|entry| entry.debit_balance - entry.credit_balance)
+        .sum();
+    report.set_control_balance(match party_type {
+        PartyType::Customer => balance,
+        PartyType::Vendor => -balance,
+    });
+
+    Ok(report)
+}
+
+/// Statement of cash flows for a range (`start_date` or `period`, up to `end_date` or today)
+/// by the indirect method
+pub async fn cash_flow_statement(
+    Extension(state): Extension<AppState>,
+    Path(company_id): Path<i64>,
+    Query(query): Query<ReportQuery>,
+) -> Result<Json<CashFlowStatement>, ApiError> {
+    let (start_date, end_date) = query.bounded_date_range(&state.fiscal_calendar)?;
+
+    let accounts = state.database.list_accounts_for_period(company_id, Some(start_date), Some(end_date)).await?;
+    let mut statement = CashFlowStatement::new(start_date, end_date);
+    for account in accounts.iter().filter(|account| !account.is_header) {
+        statement.add_account(account);
+    }
+
+    let opening = match start_date.pred_opt() {
+        Some(day_before) => state.database.list_accounts_as_of(company_id, day_before).await?,
+        None => Vec::new(),
+    };
+    let closing = state.database.list_accounts_as_of(company_id, end_date).await?;
+    statement.set_cash_balances(&opening, &closing);
+
+    Ok(Json(statement))
+}
+
+/// Equity roll-forward for a range (`start_date` or `period`, up to `end_date` or today)
+pub async fn changes_in_equity(
+    Extension(state): Extension<AppState>,
+    Path(company_id): Path<i64>,
+    Query(query): Query<ReportQuery>,
+) -> Result<Json<ChangesInEquity>, ApiError> {
+    let (start_date, end_date) = query.bounded_date_range(&state.fiscal_calendar)?;
+    let movements = state.database.get_equity_movements(company_id, start_date, end_date).await?;
+    let mut statement = ChangesInEquity::new(start_date, end_date);
+    for movement in movements {
+        statement.add_movement(movement);
+    }
+
+    let accounts = state.database.list_accounts_for_period(company_id, Some(start_date), Some(end_date)).await?;
+    statement.net_income = query.income_statement(accounts, Some(start_date), Some(end_date)).net_income;
+
+    Ok(Json(statement))
+}
+
+/// Balance sheets at `as_of` (default today) and at the same point of earlier periods
+pub async fn comparative_balance_sheet(
+    Extension(state): Extension<AppState>,
+    Path(company_id): Path<i64>,
+    Query(query): Query<ReportQuery>,
+) -> Result<Json<ComparativeBalanceSheet>, ApiError> {
+    let (comparison, periods) = query.comparison()?;
+    let as_of = query.as_of.unwrap_or_else(|| chrono::Utc::now().date_naive());
+
+    let mut columns = Vec::new();
+    let mut statements = Vec::new();
+    for step in 0..periods {
+        let as_of = shift_back(comparison, as_of, step)?;
+        let accounts = state.database.list_accounts_as_of(company_id, as_of).await?;
+        statements.push(query.balance_sheet(accounts));
+        columns.push(ComparativeColumn { start_date: None, end_date: as_of });
     }
 
-    Ok(Json(income_statement))
+    Ok(Json(ComparativeBalanceSheet::new(columns, &statements)))
+}
+
+/// Income statements for the requested range and the same range in earlier periods
+pub async fn comparative_income_statement(
+    Extension(state): Extension<AppState>,
+    Path(company_id): Path<i64>,
+    Query(query): Query<ReportQuery>,
+) -> Result<Json<ComparativeIncomeStatement>, ApiError> {
+    let (comparison, periods) = query.comparison()?;
+    let (start_date, end_date) = query.bounded_date_range(&state.fiscal_calendar)?;
+
+    let mut columns = Vec::new();
+    let mut statements = Vec::new();
+    for step in 0..periods {
+        let start_date = shift_back(comparison, start_date, step)?;
+        let end_date = shift_back(comparison, end_date, step)?;
+        let accounts = state.database.list_accounts_for_period(company_id, Some(start_date), Some(end_date)).await?;
+        statements.push(query.income_statement(accounts, Some(start_date), Some(end_date)));
+        columns.push(ComparativeColumn { start_date: Some(start_date), end_date });
+    }
+
+    Ok(Json(ComparativeIncomeStatement::new(columns, &statements)))
+}
+
+// A comparison column's date, rejected when it falls before the earliest supported date
+fn shift_back(comparison: Comparison, date: NaiveDate, steps: u32) -> Result<NaiveDate, ApiError> {
+    comparison
+        .shift_back(date, steps)
+        .ok_or_else(|| validation_error("Comparison periods reach before the earliest supported date"))
 }
 
 // Additional report structures
@@ -61,9 +420,14 @@ pub struct BalanceSheet {
     pub equity: BalanceSheetSection,
     pub total_assets: rust_decimal::Decimal,
     pub total_liabilities_and_equity: rust_decimal::Decimal,
+    /// Revenue less expenses not yet closed into retained earnings; also listed in equity
+    pub current_year_earnings: rust_decimal::Decimal,
     pub is_balanced: bool,
 }
 
+/// Id of the synthetic current year earnings line, which belongs to no account
+pub const CURRENT_YEAR_EARNINGS_ID: i64 = 0;
+
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
 pub struct BalanceSheetSection {
     pub accounts: Vec<BalanceSheetAccount>,
@@ -76,6 +440,14 @@ pub struct BalanceSheetAccount {
     pub code: String,
     pub name: String,
     pub balance: rust_decimal::Decimal,
+    /// Level in the account tree when balances are rolled up
+    pub depth: Option<usize>,
+}
+
+impl Default for BalanceSheet {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl BalanceSheet {
@@ -95,6 +467,7 @@ impl BalanceSheet {
             },
             total_assets: rust_decimal::Decimal::ZERO,
             total_liabilities_and_equity: rust_decimal::Decimal::ZERO,
+            current_year_earnings: rust_decimal::Decimal::ZERO,
             is_balanced: true,
         }
     }
@@ -104,7 +477,8 @@ impl BalanceSheet {
             id: account.id,
             code: account.code.clone(),
             name: account.name.clone(),
-            balance: account.normal_balance().abs(),
+            balance: account.normal_balance(),
+            depth: None,
         };
 
         match account.account_type {
@@ -118,11 +492,81 @@ impl BalanceSheet {
             }
             AccountType::Equity => {
                 self.equity.total += balance_sheet_account.balance;
-                self.equity.accounts.push(balance_sheet_account);
+                self.push_equity_line(balance_sheet_account);
             }
-            _ => {} // Revenue and Expense accounts don't appear on balance sheet
+            // Revenue and expenses only show through current year earnings
+            AccountType::Revenue | AccountType::Expense => self.add_earnings(-account.balance, None),
+        }
+
+        self.update_totals();
+    }
+
+    /// Add a top-level account with rolled-up balances, followed by its listed descendants.
+    /// Only the top-level balance counts towards the section total.
+    pub fn add_tree(&mut self, node: &AccountTreeNode) {
+        let account_type = &node.account.account_type;
+        if matches!(account_type, AccountType::Revenue | AccountType::Expense) {
+            // Revenue and expenses only show through current year earnings
+            self.add_earnings(-node.total_balance, Some(0));
+            self.update_totals();
+            return;
         }
 
+        let lines = node.flatten().into_iter().map(|line| BalanceSheetAccount {
+            id: line.account.id,
+            code: line.account.code.clone(),
+            name: line.account.name.clone(),
+            balance: line.normal_total_balance(),
+            depth: Some(line.depth),
+        });
+        let total = node.normal_total_balance();
+
+        match account_type {
+            AccountType::Asset => {
+                self.assets.total += total;
+                self.assets.accounts.extend(lines);
+            }
+            AccountType::Liability => {
+                self.liabilities.total += total;
+                self.liabilities.accounts.extend(lines);
+            }
+            _ => {
+                self.equity.total += total;
+                for line in lines {
+                    self.push_equity_line(line);
+                }
+            }
+        }
+
+        self.update_totals();
+    }
+
+    /// Add to the current year earnings line, which stays the last line of equity
+    fn add_earnings(&mut self, amount: rust_decimal::Decimal, depth: Option<usize>) {
+        self.current_year_earnings += amount;
+        self.equity.total += amount;
+
+        match self.equity.accounts.iter_mut().find(|line| line.id == CURRENT_YEAR_EARNINGS_ID) {
+            Some(line) => line.balance = self.current_year_earnings,
+            None => self.equity.accounts.push(BalanceSheetAccount {
+                id: CURRENT_YEAR_EARNINGS_ID,
+                code: String::new(),
+                name: "Current Year Earnings".to_string(),
+                balance: self.current_year_earnings,
+                depth,
+            }),
+        }
+    }
+
+    fn push_equity_line(&mut self, line: BalanceSheetAccount) {
+        let position = self.equity.accounts
+            .iter()
+            .position(|line| line.id == CURRENT_YEAR_EARNINGS_ID)
+            .unwrap_or(self.equity.accounts.len());
+        self.equity.accounts.insert(position, line);
+    }
+
+    fn update_totals(&mut self) {
         self.total_assets = self.assets.total;
         self.total_liabilities_and_equity = self.liabilities.total + self.equity.total;
         self.is_balanced = self.total_assets == self.total_liabilities_and_equity;
@@ -131,6 +575,9 @@ impl BalanceSheet {
 
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
 pub struct IncomeStatement {
+    /// Range the statement covers; `None` on both ends means lifetime balances
+    pub start_date: Option<NaiveDate>,
+    pub end_date: Option<NaiveDate>,
     pub revenue: IncomeStatementSection,
     pub expenses: IncomeStatementSection,
     pub gross_profit: rust_decimal::Decimal,
@@ -149,11 +596,21 @@ pub struct IncomeStatementAccount {
     pub code: String,
     pub name: String,
     pub amount: rust_decimal::Decimal,
+    /// Level in the account tree when balances are rolled up
+    pub depth: Option<usize>,
+}
+
+impl Default for IncomeStatement {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl IncomeStatement {
     pub fn new() -> Self {
         Self {
+            start_date: None,
+            end_date: None,
             revenue: IncomeStatementSection {
                 accounts: Vec::new(),
                 total: rust_decimal::Decimal::ZERO,
@@ -173,6 +630,7 @@ impl IncomeStatement {
             code: account.code.clone(),
             name: account.name.clone(),
             amount: account.normal_balance().abs(),
+            depth: None,
         };
 
         match account.account_type {
@@ -187,7 +645,373 @@ impl IncomeStatement {
             _ => {} // Only revenue and expense accounts appear on income statement
         }
 
+        self.update_totals();
+    }
+
+    /// Add a top-level account with rolled-up amounts, followed by its listed descendants.
+    /// Only the top-level amount counts towards the section total.
+    pub fn add_tree(&mut self, node: &AccountTreeNode) {
+        let section = match node.account.account_type {
+            AccountType::Revenue => &mut self.revenue,
+            AccountType::Expense => &mut self.expenses,
+            _ => return, // Only revenue and expense accounts appear on income statement
+        };
+
+        section.total += node.normal_total_balance().abs();
+        for line in node.flatten() {
+            section.accounts.push(IncomeStatementAccount {
+                id: line.account.id,
+                code: line.account.code.clone(),
+                name: line.account.name.clone(),
+                amount: line.normal_total_balance().abs(),
+                depth: Some(line.depth),
+            });
+        }
+
+        self.update_totals();
+    }
+
+    fn update_totals(&mut self) {
         self.gross_profit = self.revenue.total;
         self.net_income = self.revenue.total - self.expenses.total;
     }
-}
\ No newline at end of file
+}
+/// Statement of cash flows by the indirect method: net income, adjusted for the change in
+/// every non-cash balance sheet account, explains the change in the cash accounts
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct CashFlowStatement {
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub net_income: rust_decimal::Decimal,
+    /// Net income and the working-capital adjustments; the total includes net income
+    pub operating: CashFlowSection,
+    pub investing: CashFlowSection,
+    pub financing: CashFlowSection,
+    pub net_change_in_cash: rust_decimal::Decimal,
+    /// Balance of the cash accounts at the end of the day before `start_date`
+    pub opening_cash: rust_decimal::Decimal,
+    /// Balance of the cash accounts at the end of `end_date`
+    pub closing_cash: rust_decimal::Decimal,
+    /// Whether opening cash plus the net change equals closing cash
+    pub is_reconciled: bool,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct CashFlowSection {
+    pub lines: Vec<CashFlowLine>,
+    pub total: rust_decimal::Decimal,
+}
+
+/// Cash effect of one account's change over the period: positive when it brought cash in
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct CashFlowLine {
+    pub id: i64,
+    pub code: String,
+    pub name: String,
+    pub amount: rust_decimal::Decimal,
+}
+
+impl CashFlowSection {
+    fn new() -> Self {
+        Self {
+            lines: Vec::new(),
+            total: rust_decimal::Decimal::ZERO,
+        }
+    }
+}
+
+impl CashFlowStatement {
+    pub fn new(start_date: NaiveDate, end_date: NaiveDate) -> Self {
+        Self {
+            start_date,
+            end_date,
+            net_income: rust_decimal::Decimal::ZERO,
+            operating: CashFlowSection::new(),
+            investing: CashFlowSection::new(),
+            financing: CashFlowSection::new(),
+            net_change_in_cash: rust_decimal::Decimal::ZERO,
+            opening_cash: rust_decimal::Decimal::ZERO,
+            closing_cash: rust_decimal::Decimal::ZERO,
+            is_reconciled: true,
+        }
+    }
+
+    /// Add an account whose balance is its net activity over the period
+    pub fn add_account(&mut self, account: &Account) {
+        // A debit change to a non-cash account uses cash, a credit change provides it
+        let amount = -account.balance;
+
+        let section = match account.effective_cash_flow_category() {
+            None => {
+                self.net_income += amount;
+                self.operating.total += amount;
+                self.update_totals();
+                return;
+            }
+            Some(CashFlowCategory::Cash) => return,
+            Some(CashFlowCategory::Operating) => &mut self.operating,
+            Some(CashFlowCategory::Investing) => &mut self.investing,
+            Some(CashFlowCategory::Financing) => &mut self.financing,
+        };
+
+        if !amount.is_zero() {
+            section.total += amount;
+            section.lines.push(CashFlowLine {
+                id: account.id,
+                code: account.code.clone(),
+                name: account.name.clone(),
+                amount,
+            });
+        }
+
+        self.update_totals();
+    }
+
+    /// Take the opening and closing cash from accounts with their balances at either date
+    pub fn set_cash_balances(&mut self, opening: &[Account], closing: &[Account]) {
+        let cash = |accounts: &[Account]| {
+            accounts
+                .iter()
+                .filter(|account| account.effective_cash_flow_category() == Some(CashFlowCategory::Cash))
+                .map(|account| account.balance)
+                .sum()
+        };
+
+        self.opening_cash = cash(opening);
+        self.closing_cash = cash(closing);
+        self.update_totals();
+    }
+
+    fn update_totals(&mut self) {
+        self.net_change_in_cash = self.operating.total + self.investing.total + self.financing.total;
+        self.is_reconciled = self.opening_cash + self.net_change_in_cash == self.closing_cash;
+    }
+}
+
+/// Statement of changes in equity: each equity account rolled forward from its opening to
+/// its closing balance
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct ChangesInEquity {
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub accounts: Vec<EquityMovement>,
+    pub total_opening_balance: rust_decimal::Decimal,
+    pub total_contributions: rust_decimal::Decimal,
+    pub total_distributions: rust_decimal::Decimal,
+    pub total_net_income_allocation: rust_decimal::Decimal,
+    pub total_closing_balance: rust_decimal::Decimal,
+    /// Net income of the range; what has not been closed into equity yet is the difference
+    /// to `total_net_income_allocation`
+    pub net_income: rust_decimal::Decimal,
+}
+
+impl ChangesInEquity {
+    pub fn new(start_date: NaiveDate, end_date: NaiveDate) -> Self {
+        Self {
+            start_date,
+            end_date,
+            accounts: Vec::new(),
+            total_opening_balance: rust_decimal::Decimal::ZERO,
+            total_contributions: rust_decimal::Decimal::ZERO,
+            total_distributions: rust_decimal::Decimal::ZERO,
+            total_net_income_allocation: rust_decimal::Decimal::ZERO,
+            total_closing_balance: rust_decimal::Decimal::ZERO,
+            net_income: rust_decimal::Decimal::ZERO,
+        }
+    }
+
+    pub fn add_movement(&mut self, movement: EquityMovement) {
+        self.total_opening_balance += movement.opening_balance;
+        self.total_contributions += movement.contributions;
+        self.total_distributions += movement.distributions;
+        self.total_net_income_allocation += movement.net_income_allocation;
+        self.total_closing_balance += movement.closing_balance;
+        self.accounts.push(movement);
+    }
+}
+
+/// One period of a comparative statement; balance sheet columns have no start date
+#[derive(Debug, Clone, Copy, serde::Serialize, serde::Deserialize)]
+pub struct ComparativeColumn {
+    pub start_date: Option<NaiveDate>,
+    pub end_date: NaiveDate,
+}
+
+/// How far the first column of a comparative statement is from a later one
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Variance {
+    pub amount: rust_decimal::Decimal,
+    /// Change relative to the later column, in percent; `None` when that column is zero
+    pub percent: Option<rust_decimal::Decimal>,
+}
+
+impl Variance {
+    pub fn between(current: rust_decimal::Decimal, previous: rust_decimal::Decimal) -> Self {
+        let amount = current - previous;
+        let percent = if previous.is_zero() {
+            None
+        } else {
+            Some((amount / previous.abs() * rust_decimal::Decimal::ONE_HUNDRED).round_dp(2))
+        };
+
+        Self { amount, percent }
+    }
+
+    /// Variances of the first amount against each of the others
+    fn against_first(amounts: &[rust_decimal::Decimal]) -> Vec<Self> {
+        match amounts.split_first() {
+            Some((current, previous)) => previous.iter().map(|previous| Self::between(*current, *previous)).collect(),
+            None => Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct ComparativeRow {
+    pub id: i64,
+    pub code: String,
+    pub name: String,
+    /// Level in the account tree when balances are rolled up
+    pub depth: Option<usize>,
+    /// One amount per column; zero for periods the account does not appear in
+    pub amounts: Vec<rust_decimal::Decimal>,
+    /// The first column against each later column
+    pub variances: Vec<Variance>,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct ComparativeSection {
+    pub accounts: Vec<ComparativeRow>,
+    pub totals: Vec<rust_decimal::Decimal>,
+    pub total_variances: Vec<Variance>,
+}
+
+impl ComparativeSection {
+    /// Align the lines of one section across periods. An account missing from some periods
+    /// keeps a row, placed after the line it follows in the period it does appear in.
+    fn new<'a>(columns: Vec<(Vec<AccountLine<'a>>, rust_decimal::Decimal)>) -> Self {
+        let column_count = columns.len();
+        let mut accounts: Vec<ComparativeRow> = Vec::new();
+        let mut totals = Vec::with_capacity(column_count);
+
+        for (column, (lines, total)) in columns.into_iter().enumerate() {
+            let mut previous: Option<usize> = None;
+            for line in lines {
+                let index = match accounts.iter().position(|row| row.id == line.id) {
+                    Some(index) => index,
+                    None => {
+                        let index = previous.map_or(0, |previous| previous + 1);
+                        accounts.insert(index, ComparativeRow {
+                            id: line.id,
+                            code: line.code.to_string(),
+                            name: line.name.to_string(),
+                            depth: line.depth,
+                            amounts: vec![rust_decimal::Decimal::ZERO; column_count],
+                            variances: Vec::new(),
+                        });
+                        index
+                    }
+                };
+                accounts[index].amounts[column] = line.amount;
+                previous = Some(index);
+            }
+            totals.push(total);
+        }
+
+        for row in &mut accounts {
+            row.variances = Variance::against_first(&row.amounts);
+        }
+
+        Self {
+            total_variances: Variance::against_first(&totals),
+            accounts,
+            totals,
+        }
+    }
+}
+
+/// A statement line as the comparative layout needs it
+struct AccountLine<'a> {
+    id: i64,
+    code: &'a str,
+    name: &'a str,
+    depth: Option<usize>,
+    amount: rust_decimal::Decimal,
+}
+
+impl<'a> From<&'a BalanceSheetAccount> for AccountLine<'a> {
+    fn from(account: &'a BalanceSheetAccount) -> Self {
+        Self { id: account.id, code: &account.code, name: &account.name, depth: account.depth, amount: account.balance }
+    }
+}
+
+impl<'a> From<&'a IncomeStatementAccount> for AccountLine<'a> {
+    fn from(account: &'a IncomeStatementAccount) -> Self {
+        Self { id: account.id, code: &account.code, name: &account.name, depth: account.depth, amount: account.amount }
+    }
+}
+
+/// One section of every statement, as `(lines, total)` per column
+fn comparative_section<'a, A: 'a>(
+    columns: impl Iterator<Item = (&'a [A], rust_decimal::Decimal)>,
+) -> ComparativeSection
+where
+    AccountLine<'a>: From<&'a A>,
+{
+    ComparativeSection::new(
+        columns
+            .map(|(accounts, total)| (accounts.iter().map(AccountLine::from).collect(), total))
+            .collect(),
+    )
+}
+
+/// Balance sheets side by side, the first column being the one compared against the others
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct ComparativeBalanceSheet {
+    pub columns: Vec<ComparativeColumn>,
+    pub assets: ComparativeSection,
+    pub liabilities: ComparativeSection,
+    pub equity: ComparativeSection,
+    pub total_assets: Vec<rust_decimal::Decimal>,
+    pub total_liabilities_and_equity: Vec<rust_decimal::Decimal>,
+    /// Whether every column balances
+    pub is_balanced: bool,
+}
+
+impl ComparativeBalanceSheet {
+    pub fn new(columns: Vec<ComparativeColumn>, statements: &[BalanceSheet]) -> Self {
+        Self {
+            columns,
+            assets: comparative_section(statements.iter().map(|statement| (statement.assets.accounts.as_slice(), statement.assets.total))),
+            liabilities: comparative_section(statements.iter().map(|statement| (statement.liabilities.accounts.as_slice(), statement.liabilities.total))),
+            equity: comparative_section(statements.iter().map(|statement| (statement.equity.accounts.as_slice(), statement.equity.total))),
+            total_assets: statements.iter().map(|statement| statement.total_assets).collect(),
+            total_liabilities_and_equity: statements.iter().map(|statement| statement.total_liabilities_and_equity).collect(),
+            is_balanced: statements.iter().all(|statement| statement.is_balanced),
+        }
+    }
+}
+
+/// Income statements side by side, the first column being the one compared against the others
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct ComparativeIncomeStatement {
+    pub columns: Vec<ComparativeColumn>,
+    pub revenue: ComparativeSection,
+    pub expenses: ComparativeSection,
+    pub net_income: Vec<rust_decimal::Decimal>,
+    pub net_income_variances: Vec<Variance>,
+}
+
+impl ComparativeIncomeStatement {
+    pub fn new(columns: Vec<ComparativeColumn>, statements: &[IncomeStatement]) -> Self {
+        let net_income: Vec<rust_decimal::Decimal> = statements.iter().map(|statement| statement.net_income).collect();
+
+        Self {
+            columns,
+            revenue: comparative_section(statements.iter().map(|statement| (statement.revenue.accounts.as_slice(), statement.revenue.total))),
+            expenses: comparative_section(statements.iter().map(|statement| (statement.expenses.accounts.as_slice(), statement.expenses.total))),
+            net_income_variances: Variance::against_first(&net_income),
+            net_income,
+        }
+    }
+}
@@ -0,0 +1,123 @@
+use axum::{
+    extract::{Extension, Path},
+    http::{HeaderMap, StatusCode},
+    response::Json,
+};
+use chrono::{Duration, NaiveDate};
+
+use crate::{
+    models::*,
+    handlers::{ApiError, validation_error, not_found_error, conflict_error, periods::ensure_period_open},
+    AppState,
+};
+
+/// Default accounts for unrealized gains and losses
+const UNREALIZED_GAIN_CODE: &str = "4900";
+const UNREALIZED_LOSS_CODE: &str = "5900";
+
+pub async fn list_fx_revaluations(
+    Extension(state): Extension<AppState>,
+    Path(company_id): Path<i64>,
+) -> Result<Json<Vec<FxRevaluation>>, ApiError> {
+    let revaluations = state.database.list_fx_revaluations(company_id).await?;
+    Ok(Json(revaluations))
+}
+
+pub async fn get_fx_revaluation(
+    Extension(state): Extension<AppState>,
+    Path((company_id, id)): Path<(i64, i64)>,
+) -> Result<Json<FxRevaluationResult>, ApiError> {
+    let revaluation = state.database.get_fx_revaluation(company_id, id).await?
+        .ok_or_else(|| not_found_error("FX revaluation"))?;
+
+    Ok(Json(revaluation))
+}
+
+pub async fn preview_fx_revaluation(
+    Extension(state): Extension<AppState>,
+    Path(company_id): Path<i64>,
+    Json(request): Json<FxRevaluationRequest>,
+) -> Result<Json<FxRevaluationPreview>, ApiError> {
+    let gain_account_id = resolve_fx_account(&state, company_id, request.gain_account_id, UNREALIZED_GAIN_CODE).await?;
+    let loss_account_id = resolve_fx_account(&state, company_id, request.loss_account_id, UNREALIZED_LOSS_CODE).await?;
+    let reversal_date = next_period_start(&state, company_id, request.revaluation_date).await?;
+
+    let preview = state.database
+        .preview_fx_revaluation(company_id, request.revaluation_date, reversal_date, gain_account_id, loss_account_id)
+        .await?;
+
+    Ok(Json(preview))
+}
+
+pub async fn run_fx_revaluation(
+    Extension(state): Extension<AppState>,
+    Path(company_id): Path<i64>,
+    headers: HeaderMap,
+    Json(request): Json<FxRevaluationRequest>,
+) -> Result<(StatusCode, Json<FxRevaluationResult>), ApiError> {
+    if let Some(existing) = state.database.get_fx_revaluation_for_date(company_id, request.revaluation_date).await? {
+        return Err(conflict_error(&format!(
+            "Foreign-currency balances have already been revalued at {} by transaction {}",
+            request.revaluation_date, existing.transaction_id
+        )));
+    }
+
+    let gain_account_id = resolve_fx_account(&state, company_id, request.gain_account_id, UNREALIZED_GAIN_CODE).await?;
+    let loss_account_id = resolve_fx_account(&state, company_id, request.loss_account_id, UNREALIZED_LOSS_CODE).await?;
+    let reversal_date = next_period_start(&state, company_id, request.revaluation_date).await?;
+
+    // Both the adjustment and its reversal must land in open periods
+    ensure_period_open(&state, company_id, &headers, request.revaluation_date).await?;
+    ensure_period_open(&state, company_id, &headers, reversal_date).await?;
+
+    let preview = state.database
+        .preview_fx_revaluation(company_id, request.revaluation_date, reversal_date, gain_account_id, loss_account_id)
+        .await?;
+    if !preview.missing_rates.is_empty() {
+        return Err(validation_error(&format!(
+            "No exchange rate on or before {} for {}",
+            request.revaluation_date, preview.missing_rates.join(", ")
+        )));
+    }
+    if !preview.has_adjustments() {
+        return Err(validation_error(&format!(
+            "Foreign-currency balances are already stated at the rates in effect on {}",
+            request.revaluation_date
+        )));
+    }
+
+    let result = state.database
+        .run_fx_revaluation(company_id, request.revaluation_date, reversal_date, gain_account_id, loss_account_id)
+        .await?;
+
+    Ok((StatusCode::CREATED, Json(result)))
+}
+
+// The day after the period containing the date ends, or simply the next day outside defined periods
+async fn next_period_start(state: &AppState, company_id: i64, date: NaiveDate) -> Result<NaiveDate, ApiError> {
+    let period_end = match state.database.get_period_for_date(company_id, date).await? {
+        Some(period) => period.end_date,
+        None => date,
+    };
+
+    Ok(period_end + Duration::days(1))
+}
+
+async fn resolve_fx_account(
+    state: &AppState,
+    company_id: i64,
+    account_id: Option<i64>,
+    default_code: &str,
+) -> Result<i64, ApiError> {
+    let account = match account_id {
+        Some(id) => state.database.get_account(company_id, id).await?,
+        None => state.database.get_account_by_code(company_id, default_code).await?,
+    }
+    .ok_or_else(|| validation_error("Unrealized FX gain/loss account does not exist"))?;
+
+    if !matches!(account.account_type, AccountType::Revenue | AccountType::Expense) {
+        return Err(validation_error("Unrealized FX gains and losses must go to revenue or expense accounts"));
+    }
+
+    Ok(account.id)
+}
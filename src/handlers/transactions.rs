@@ -1,15 +1,13 @@
 use axum::{
     extract::{Extension, Path, Query},
-    http::StatusCode,
+    http::{HeaderMap, StatusCode},
     response::Json,
 };
 use serde::Deserialize;
-use std::sync::Arc;
 
 use crate::{
     models::*,
-    database::Database,
-    handlers::{ApiError, validation_error, not_found_error},
+    handlers::{ApiError, validation_error, not_found_error, conflict_error, currency::resolve_exchange_rates, periods::ensure_period_open},
     AppState,
 };
 
@@ -19,6 +17,7 @@ pub struct ListTransactionsQuery {
     pub end_date: Option<chrono::NaiveDate>,
     pub account_id: Option<i64>,
     pub description_contains: Option<String>,
+    pub status: Option<TransactionStatus>,
     pub limit: Option<i64>,
     pub offset: Option<i64>,
 }
@@ -30,6 +29,7 @@ impl From<ListTransactionsQuery> for TransactionFilter {
             end_date: query.end_date,
             account_id: query.account_id,
             description_contains: query.description_contains,
+            status: query.status,
             min_amount: None,
             max_amount: None,
             limit: query.limit.or(Some(50)),
@@ -40,18 +40,19 @@ impl From<ListTransactionsQuery> for TransactionFilter {
 
 pub async fn list_transactions(
     Extension(state): Extension<AppState>,
+    Path(company_id): Path<i64>,
     Query(query): Query<ListTransactionsQuery>,
 ) -> Result<Json<Vec<TransactionWithEntries>>, ApiError> {
     let filter = TransactionFilter::from(query);
-    let transactions = state.database.list_transactions(filter).await?;
+    let transactions = state.database.list_transactions(company_id, filter).await?;
     Ok(Json(transactions))
 }
 
 pub async fn get_transaction(
     Extension(state): Extension<AppState>,
-    Path(id): Path<i64>,
+    Path((company_id, id)): Path<(i64, i64)>,
 ) -> Result<Json<TransactionWithEntries>, ApiError> {
-    let transaction = state.database.get_transaction(id).await?
+    let transaction = state.database.get_transaction(company_id, id).await?
         .ok_or_else(|| not_found_error("Transaction"))?;
 
     Ok(Json(transaction))
@@ -59,46 +60,38 @@ pub async fn get_transaction(
 
 pub async fn create_transaction(
     Extension(state): Extension<AppState>,
-    Json(request): Json<CreateTransactionRequest>,
+    Path(company_id): Path<i64>,
+    headers: HeaderMap,
+    Json(mut request): Json<CreateTransactionRequest>,
 ) -> Result<(StatusCode, Json<TransactionWithEntries>), ApiError> {
+    resolve_exchange_rates(&state, company_id, request.transaction_date, &mut request.journal_entries).await?;
+
     // Validate the transaction request
     if let Err(validation_err) = request.validate() {
         return Err(validation_error(&validation_err.to_string()));
     }
 
-    // Validate that all referenced accounts exist
-    for entry in &request.journal_entries {
-        if state.database.get_account(entry.account_id).await?.is_none() {
-            return Err(validation_error(&format!(
-                "Account with ID {} does not exist", 
-                entry.account_id
-            )));
-        }
-    }
+    ensure_period_open(&state, company_id, &headers, request.transaction_date).await?;
 
-    // Validate individual journal entries
-    for (index, entry) in request.journal_entries.iter().enumerate() {
-        if let Err(validation_msg) = entry.validate() {
-            return Err(validation_error(&format!(
-                "Journal entry {}: {}", 
-                index + 1, 
-                validation_msg
-            )));
-        }
-    }
+    check_journal_entries(&state, company_id, &request.journal_entries).await?;
 
-    let transaction = state.database.create_transaction(request).await?;
+    let transaction = state.database.create_transaction(company_id, request).await?;
     Ok((StatusCode::CREATED, Json(transaction)))
 }
 
 pub async fn update_transaction(
     Extension(state): Extension<AppState>,
-    Path(id): Path<i64>,
-    Json(request): Json<UpdateTransactionRequest>,
+    headers: HeaderMap,
+    Path((company_id, id)): Path<(i64, i64)>,
+    Json(mut request): Json<UpdateTransactionRequest>,
 ) -> Result<Json<TransactionWithEntries>, ApiError> {
     // Check if transaction exists
-    if state.database.get_transaction(id).await?.is_none() {
-        return Err(not_found_error("Transaction"));
+    let existing = state.database.get_transaction(company_id, id).await?
+        .ok_or_else(|| not_found_error("Transaction"))?;
+
+    // Only drafts can be edited; posted transactions are corrected by reversal
+    if !existing.transaction.status.is_editable() {
+        return Err(conflict_error("Only draft transactions can be edited"));
     }
 
     // Validate description if provided
@@ -108,38 +101,225 @@ pub async fn update_transaction(
         }
     }
 
-    // TODO: Implement transaction update logic
-    // This is more complex because it involves updating journal entries
-    // For now, we'll return an error indicating this feature is not implemented
-    Err(ApiError {
-        status: StatusCode::NOT_IMPLEMENTED,
-        message: "Transaction updates are not yet implemented".to_string(),
-    })
+    if let Some(ref mut journal_entries) = request.journal_entries {
+        let transaction_date = request.transaction_date.unwrap_or(existing.transaction.transaction_date);
+        resolve_exchange_rates(&state, company_id, transaction_date, journal_entries).await?;
+    }
+
+    // Re-validate the transaction as it will look after the update
+    if let Err(validation_err) = request.apply_to(&existing).validate() {
+        return Err(validation_error(&validation_err.to_string()));
+    }
+
+    // Neither the current nor the new date may sit in a locked period
+    ensure_period_open(&state, company_id, &headers, existing.transaction.transaction_date).await?;
+    if let Some(transaction_date) = request.transaction_date {
+        ensure_period_open(&state, company_id, &headers, transaction_date).await?;
+    }
+
+    if let Some(ref journal_entries) = request.journal_entries {
+        check_journal_entries(&state, company_id, journal_entries).await?;
+    }
+
+    let transaction = state.database.update_transaction(company_id, id, request).await?
+        .ok_or_else(|| not_found_error("Transaction"))?;
+
+    Ok(Json(transaction))
 }
 
-pub async fn delete_transaction(
+pub async fn reverse_transaction(
     Extension(state): Extension<AppState>,
-    Path(id): Path<i64>,
-) -> Result<StatusCode, ApiError> {
+    headers: HeaderMap,
+    Path((company_id, id)): Path<(i64, i64)>,
+    Json(request): Json<ReverseTransactionRequest>,
+) -> Result<(StatusCode, Json<TransactionWithEntries>), ApiError> {
     // Check if transaction exists
-    if state.database.get_transaction(id).await?.is_none() {
-        return Err(not_found_error("Transaction"));
+    let original = state.database.get_transaction(company_id, id).await?
+        .ok_or_else(|| not_found_error("Transaction"))?;
+
+    if original.transaction.status != TransactionStatus::Posted {
+        return Err(conflict_error("Only posted transactions can be reversed"));
+    }
+
+    if state.database.is_year_end_transaction(company_id, id).await? {
+        return Err(conflict_error("Year-end closing entries can only be undone by reopening the fiscal year"));
     }
 
-    // TODO: Implement transaction deletion logic
-    // This involves deleting all associated journal entries and updating account balances
-    // For now, we'll return an error indicating this feature is not implemented
-    Err(ApiError {
-        status: StatusCode::NOT_IMPLEMENTED,
-        message: "Transaction deletion is not yet implemented".to_string(),
-    })
+    // Posted entries are never deleted; each transaction can be reversed only once
+    if let Some(reversal) = state.database.get_reversal(company_id, id).await? {
+        return Err(conflict_error(&format!(
+            "Transaction {} has already been reversed by transaction {}",
+            id, reversal.id
+        )));
+    }
+
+    if request.transaction_date < original.transaction.transaction_date {
+        return Err(validation_error("Reversal date cannot be before the original transaction date"));
+    }
+
+    ensure_period_open(&state, company_id, &headers, request.transaction_date).await?;
+
+    if let Some(ref description) = request.description {
+        if description.is_empty() {
+            return Err(validation_error("Transaction description cannot be empty"));
+        }
+    }
+
+    let reversal = state.database.reverse_transaction(company_id, id, request).await?
+        .ok_or_else(|| not_found_error("Transaction"))?;
+
+    Ok((StatusCode::CREATED, Json(reversal)))
+}
+
+pub async fn post_transaction(
+    Extension(state): Extension<AppState>,
+    headers: HeaderMap,
+    Path((company_id, id)): Path<(i64, i64)>,
+) -> Result<Json<TransactionWithEntries>, ApiError> {
+    let existing = state.database.get_transaction(company_id, id).await?
+        .ok_or_else(|| not_found_error("Transaction"))?;
+
+    if !existing.transaction.status.can_transition_to(TransactionStatus::Posted) {
+        return Err(conflict_error("Only draft transactions can be posted"));
+    }
+
+    ensure_period_open(&state, company_id, &headers, existing.transaction.transaction_date).await?;
+
+    // A draft may have been written before one of its accounts was archived
+    for entry in &existing.journal_entries {
+        let account = state.database.get_account(company_id, entry.account_id).await?
+            .ok_or_else(|| not_found_error("Account"))?;
+        if let Some(message) = account.posting_restriction() {
+            return Err(validation_error(&message));
+        }
+    }
+
+    let transaction = state.database.post_transaction(company_id, id).await?
+        .ok_or_else(|| not_found_error("Transaction"))?;
+
+    Ok(Json(transaction))
+}
+
+pub async fn void_transaction(
+    Extension(state): Extension<AppState>,
+    headers: HeaderMap,
+    Path((company_id, id)): Path<(i64, i64)>,
+) -> Result<Json<TransactionWithEntries>, ApiError> {
+    let existing = state.database.get_transaction(company_id, id).await?
+        .ok_or_else(|| not_found_error("Transaction"))?;
+
+    if !existing.transaction.status.can_transition_to(TransactionStatus::Voided) {
+        return Err(conflict_error("Transaction has already been voided"));
+    }
+
+    if state.database.is_year_end_transaction(company_id, id).await? {
+        return Err(conflict_error("Year-end closing entries can only be undone by reopening the fiscal year"));
+    }
+
+    // A reversal pair cancels out; voiding either half would leave the other one live
+    if existing.transaction.reversal_of_id.is_some() {
+        return Err(conflict_error("Reversal transactions cannot be voided"));
+    }
+    if let Some(reversal) = state.database.get_reversal(company_id, id).await? {
+        return Err(conflict_error(&format!(
+            "Transaction {} has been reversed by transaction {} and cannot be voided",
+            id, reversal.id
+        )));
+    }
+
+    ensure_period_open(&state, company_id, &headers, existing.transaction.transaction_date).await?;
+
+    let transaction = state.database.void_transaction(company_id, id).await?
+        .ok_or_else(|| not_found_error("Transaction"))?;
+
+    Ok(Json(transaction))
+}
+
+// Checks shared by every path that writes journal lines
+pub async fn check_journal_entries(
+    state: &AppState,
+    company_id: i64,
+    journal_entries: &[CreateJournalEntryRequest],
+) -> Result<(), ApiError> {
+    // Validate that all referenced accounts exist and take lines in their currency
+    for entry in journal_entries {
+        let account = state.database.get_account(company_id, entry.account_id).await?
+            .ok_or_else(|| validation_error(&format!(
+                "Account with ID {} does not exist", 
+                entry.account_id
+            )))?;
+        if let Some(message) = line_rejection(&account, entry) {
+            return Err(validation_error(&message));
+        }
+        if let Some(message) = party_rejection(state, company_id, &account, entry).await? {
+            return Err(validation_error(&message));
+        }
+    }
+
+    // Validate individual journal entries
+    for (index, entry) in journal_entries.iter().enumerate() {
+        if let Err(validation_msg) = entry.validate() {
+            return Err(validation_error(&format!(
+                "Journal entry {}: {}", 
+                index + 1, 
+                validation_msg
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+// Why an account cannot take a journal line, if it cannot
+fn line_rejection(account: &Account, entry: &CreateJournalEntryRequest) -> Option<String> {
+    account.posting_restriction().or_else(|| currency_mismatch(account, entry))
+}
+
+// Why a line's party does not fit its account, if it does not.
+// Control accounts need a party of their type and other accounts take none.
+async fn party_rejection(
+    state: &AppState,
+    company_id: i64,
+    account: &Account,
+    entry: &CreateJournalEntryRequest,
+) -> Result<Option<String>, ApiError> {
+    let party = match entry.party_id {
+        Some(party_id) => match state.database.get_party(company_id, party_id).await? {
+            Some(party) => Some(party),
+            None => return Ok(Some(format!("Party with ID {} does not exist", party_id))),
+        },
+        None => None,
+    };
+
+    Ok(account.party_restriction(party.as_ref()))
+}
+
+// Accounts kept in a foreign currency only take lines in that currency
+fn currency_mismatch(account: &Account, entry: &CreateJournalEntryRequest) -> Option<String> {
+    let account_currency = account.currency.as_ref()?;
+    if entry.currency.as_ref() == Some(account_currency) {
+        return None;
+    }
+
+    Some(format!(
+        "Account {} is kept in {}; its lines must be in {}",
+        account.code, account_currency, account_currency
+    ))
 }
 
 // Helper endpoint to validate a transaction before creating it
 pub async fn validate_transaction(
     Extension(state): Extension<AppState>,
-    Json(request): Json<CreateTransactionRequest>,
+    Path(company_id): Path<i64>,
+    Json(mut request): Json<CreateTransactionRequest>,
 ) -> Result<Json<serde_json::Value>, ApiError> {
+    if let Err(rate_err) = resolve_exchange_rates(&state, company_id, request.transaction_date, &mut request.journal_entries).await {
+        return Ok(Json(serde_json::json!({
+            "valid": false,
+            "errors": [rate_err.message]
+        })));
+    }
+
     // Validate the transaction request
     if let Err(validation_err) = request.validate() {
         return Ok(Json(serde_json::json!({
@@ -152,11 +332,15 @@ pub async fn validate_transaction(
 
     // Validate that all referenced accounts exist
     for entry in &request.journal_entries {
-        if state.database.get_account(entry.account_id).await?.is_none() {
-            errors.push(format!(
+        match state.database.get_account(company_id, entry.account_id).await? {
+            Some(account) => {
+                errors.extend(line_rejection(&account, entry));
+                errors.extend(party_rejection(&state, company_id, &account, entry).await?);
+            }
+            None => errors.push(format!(
                 "Account with ID {} does not exist", 
                 entry.account_id
-            ));
+            )),
         }
     }
 
@@ -2,7 +2,6 @@ use axum::{
     extract::Extension,
     response::Html,
 };
-use std::sync::Arc;
 
 use crate::{
     handlers::ApiError,
@@ -10,7 +9,7 @@ use crate::{
 };
 
 pub async fn dashboard(
-    Extension(state): Extension<AppState>,
+    Extension(_state): Extension<AppState>,
 ) -> Result<Html<String>, ApiError> {
     let html = r#"
 <!DOCTYPE html>
@@ -88,7 +87,7 @@ pub async fn dashboard(
 }
 
 pub async fn accounts_page(
-    Extension(state): Extension<AppState>,
+    Extension(_state): Extension<AppState>,
 ) -> Result<Html<String>, ApiError> {
     let html = r#"
 <!DOCTYPE html>
@@ -182,7 +181,7 @@ pub async fn accounts_page(
 }
 
 pub async fn transactions_page(
-    Extension(state): Extension<AppState>,
+    Extension(_state): Extension<AppState>,
 ) -> Result<Html<String>, ApiError> {
     let html = r#"
 <!DOCTYPE html>
@@ -279,7 +278,7 @@ pub async fn transactions_page(
 }
 
 pub async fn reports_page(
-    Extension(state): Extension<AppState>,
+    Extension(_state): Extension<AppState>,
 ) -> Result<Html<String>, ApiError> {
     let html = r#"
 <!DOCTYPE html>
@@ -350,7 +349,7 @@ pub async fn reports_page(
 }
 
 pub async fn trial_balance_page(
-    Extension(state): Extension<AppState>,
+    Extension(_state): Extension<AppState>,
 ) -> Result<Html<String>, ApiError> {
     let html = r#"
 <!DOCTYPE html>
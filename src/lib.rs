@@ -15,6 +15,46 @@ mod tests {
     use rust_decimal::Decimal;
     use chrono::NaiveDate;
 
+    /// The company that pre-existing books were migrated into
+    const MAIN: i64 = 1;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    async fn account_id(db: &Database, code: &str) -> i64 {
+        db.get_account_by_code(MAIN, code).await.unwrap().unwrap().id
+    }
+
+    async fn balance_of(db: &Database, code: &str) -> Decimal {
+        db.get_account_by_code(MAIN, code).await.unwrap().unwrap().balance
+    }
+
+    fn simple_transaction(debit_account: i64, credit_account: i64, amount: Decimal, transaction_date: NaiveDate) -> CreateTransactionRequest {
+        CreateTransactionRequest {
+            description: "Test transaction".to_string(),
+            reference: None,
+            transaction_date,
+            status: None,
+            journal_entries: vec![
+                CreateJournalEntryRequest {
+                    account_id: debit_account,
+                    debit_amount: Some(amount),
+                    credit_amount: None,
+                    description: None,
+                    ..Default::default()
+                },
+                CreateJournalEntryRequest {
+                    account_id: credit_account,
+                    debit_amount: None,
+                    credit_amount: Some(amount),
+                    description: None,
+                    ..Default::default()
+                },
+            ],
+        }
+    }
+
     #[tokio::test]
     async fn test_account_creation() {
         let db = Database::new(":memory:").await.unwrap();
@@ -24,9 +64,13 @@ mod tests {
             name: "Test Account".to_string(),
             account_type: AccountType::Asset,
             parent_id: None,
+            currency: None,
+            is_header: false,
+            cash_flow_category: None,
+            control_party_type: None,
         };
         
-        let account = db.create_account(request).await.unwrap();
+        let account = db.create_account(MAIN, request).await.unwrap();
         
         assert_eq!(account.code, "9999");
         assert_eq!(account.name, "Test Account");
@@ -40,18 +84,21 @@ mod tests {
             description: "Test transaction".to_string(),
             reference: None,
             transaction_date: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            status: None,
             journal_entries: vec![
                 CreateJournalEntryRequest {
                     account_id: 1,
                     debit_amount: Some(Decimal::new(10000, 2)), // $100.00
                     credit_amount: None,
                     description: None,
+                    ..Default::default()
                 },
                 CreateJournalEntryRequest {
                     account_id: 2,
                     debit_amount: None,
                     credit_amount: Some(Decimal::new(10000, 2)), // $100.00
                     description: None,
+                    ..Default::default()
                 },
             ],
         };
@@ -66,18 +113,21 @@ mod tests {
             description: "Unbalanced transaction".to_string(),
             reference: None,
             transaction_date: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            status: None,
             journal_entries: vec![
                 CreateJournalEntryRequest {
                     account_id: 1,
                     debit_amount: Some(Decimal::new(10000, 2)), // $100.00
                     credit_amount: None,
                     description: None,
+                    ..Default::default()
                 },
                 CreateJournalEntryRequest {
                     account_id: 2,
                     debit_amount: None,
                     credit_amount: Some(Decimal::new(5000, 2)), // $50.00
                     description: None,
+                    ..Default::default()
                 },
             ],
         };
@@ -86,15 +136,1447 @@ mod tests {
         assert!(request.validate().is_err());
     }
 
+    #[tokio::test]
+    async fn test_amounts_round_trip_exactly() {
+        let db = Database::new(":memory:").await.unwrap();
+        let cash = db.get_account_by_code(MAIN, "1110").await.unwrap().unwrap();
+        let equity = db.get_account_by_code(MAIN, "3100").await.unwrap().unwrap();
+
+        // 0.10 + 0.20 and a large ledger amount both lose precision as f64
+        for amount in [Decimal::new(10, 2), Decimal::new(20, 2), Decimal::new(9_007_199_254_740_993, 2)] {
+            let request = CreateTransactionRequest {
+                description: "Owner contribution".to_string(),
+                reference: None,
+                transaction_date: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+                status: None,
+                journal_entries: vec![
+                    CreateJournalEntryRequest {
+                        account_id: cash.id,
+                        debit_amount: Some(amount),
+                        credit_amount: None,
+                        description: None,
+                        ..Default::default()
+                    },
+                    CreateJournalEntryRequest {
+                        account_id: equity.id,
+                        debit_amount: None,
+                        credit_amount: Some(amount),
+                        description: None,
+                        ..Default::default()
+                    },
+                ],
+            };
+
+            let created = db.create_transaction(MAIN, request).await.unwrap();
+            let loaded = db.get_transaction(MAIN, created.transaction.id).await.unwrap().unwrap();
+            assert_eq!(loaded.journal_entries[0].debit_amount, amount);
+            assert_eq!(loaded.total_credits, amount);
+        }
+
+        let cash = db.get_account(MAIN, cash.id).await.unwrap().unwrap();
+        assert_eq!(cash.balance, Decimal::new(9_007_199_254_741_023, 2));
+    }
+
+    #[tokio::test]
+    async fn test_sub_cent_amounts_are_rejected() {
+        assert_eq!(to_minor_units(Decimal::new(12345, 2)).unwrap(), 12345);
+        assert_eq!(to_minor_units(Decimal::new(1_000, 3)).unwrap(), 100);
+        assert!(to_minor_units(Decimal::new(1005, 3)).is_err());
+
+        let request = CreateTransactionRequest {
+            description: "Sub-cent transaction".to_string(),
+            reference: None,
+            transaction_date: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            status: None,
+            journal_entries: vec![
+                CreateJournalEntryRequest {
+                    account_id: 1,
+                    debit_amount: Some(Decimal::new(1005, 3)),
+                    credit_amount: None,
+                    description: None,
+                    ..Default::default()
+                },
+                CreateJournalEntryRequest {
+                    account_id: 2,
+                    debit_amount: None,
+                    credit_amount: Some(Decimal::new(1005, 3)),
+                    description: None,
+                    ..Default::default()
+                },
+            ],
+        };
+
+        assert!(request.validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_transaction_reversal() {
+        let db = Database::new(":memory:").await.unwrap();
+        let cash = db.get_account_by_code(MAIN, "1110").await.unwrap().unwrap();
+        let sales = db.get_account_by_code(MAIN, "4100").await.unwrap().unwrap();
+
+        let original = db
+            .create_transaction(MAIN, simple_transaction(cash.id, sales.id, Decimal::new(25000, 2), date(2024, 3, 1)))
+            .await
+            .unwrap();
+
+        let request = ReverseTransactionRequest {
+            transaction_date: date(2024, 3, 15),
+            description: None,
+            reference: None,
+        };
+        let reversal = db.reverse_transaction(MAIN, original.transaction.id, request).await.unwrap().unwrap();
+
+        assert_eq!(reversal.transaction.reversal_of_id, Some(original.transaction.id));
+        assert_eq!(reversal.transaction.transaction_date, date(2024, 3, 15));
+        assert_eq!(reversal.journal_entries[0].account_id, cash.id);
+        assert_eq!(reversal.journal_entries[0].credit_amount, Decimal::new(25000, 2));
+        assert_eq!(reversal.journal_entries[1].debit_amount, Decimal::new(25000, 2));
+
+        let cash = db.get_account(MAIN, cash.id).await.unwrap().unwrap();
+        assert_eq!(cash.balance, Decimal::ZERO);
+
+        let found = db.get_reversal(MAIN, original.transaction.id).await.unwrap().unwrap();
+        assert_eq!(found.id, reversal.transaction.id);
+
+        // The unique index refuses a second reversal even if the handler check is bypassed
+        let again = ReverseTransactionRequest {
+            transaction_date: date(2024, 3, 16),
+            description: None,
+            reference: None,
+        };
+        assert!(db.reverse_transaction(MAIN, original.transaction.id, again).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_transaction_update_replaces_lines() {
+        let db = Database::new(":memory:").await.unwrap();
+        let cash = db.get_account_by_code(MAIN, "1110").await.unwrap().unwrap();
+        let sales = db.get_account_by_code(MAIN, "4100").await.unwrap().unwrap();
+        let service = db.get_account_by_code(MAIN, "4200").await.unwrap().unwrap();
+
+        let mut draft = simple_transaction(cash.id, sales.id, Decimal::new(10000, 2), date(2024, 4, 1));
+        draft.status = Some(TransactionStatus::Draft);
+        let original = db.create_transaction(MAIN, draft).await.unwrap();
+
+        let request = UpdateTransactionRequest {
+            description: Some("Mixed sale".to_string()),
+            reference: None,
+            transaction_date: Some(date(2024, 4, 2)),
+            journal_entries: Some(vec![
+                CreateJournalEntryRequest {
+                    account_id: cash.id,
+                    debit_amount: Some(Decimal::new(15000, 2)),
+                    credit_amount: None,
+                    description: None,
+                    ..Default::default()
+                },
+                CreateJournalEntryRequest {
+                    account_id: sales.id,
+                    debit_amount: None,
+                    credit_amount: Some(Decimal::new(10000, 2)),
+                    description: None,
+                    ..Default::default()
+                },
+                CreateJournalEntryRequest {
+                    account_id: service.id,
+                    debit_amount: None,
+                    credit_amount: Some(Decimal::new(5000, 2)),
+                    description: None,
+                    ..Default::default()
+                },
+            ]),
+        };
+
+        let updated = db.update_transaction(MAIN, original.transaction.id, request).await.unwrap().unwrap();
+        assert_eq!(updated.transaction.description, "Mixed sale");
+        assert_eq!(updated.transaction.transaction_date, date(2024, 4, 2));
+        assert_eq!(updated.journal_entries.len(), 3);
+        assert_eq!(updated.total_debits, Decimal::new(15000, 2));
+
+        db.post_transaction(MAIN, original.transaction.id).await.unwrap();
+        let cash = db.get_account(MAIN, cash.id).await.unwrap().unwrap();
+        let service = db.get_account(MAIN, service.id).await.unwrap().unwrap();
+        assert_eq!(cash.balance, Decimal::new(15000, 2));
+        assert_eq!(service.balance, Decimal::new(-5000, 2));
+
+        // Unbalanced replacements are rejected and leave the transaction untouched
+        let unbalanced = UpdateTransactionRequest {
+            description: None,
+            reference: None,
+            transaction_date: None,
+            journal_entries: Some(vec![CreateJournalEntryRequest {
+                account_id: cash.id,
+                debit_amount: Some(Decimal::new(100, 2)),
+                credit_amount: None,
+                description: None,
+                ..Default::default()
+            }]),
+        };
+        assert!(db.update_transaction(MAIN, original.transaction.id, unbalanced).await.is_err());
+        let cash = db.get_account(MAIN, cash.id).await.unwrap().unwrap();
+        assert_eq!(cash.balance, Decimal::new(15000, 2));
+    }
+
+    #[tokio::test]
+    async fn test_transaction_lifecycle() {
+        let db = Database::new(":memory:").await.unwrap();
+        let cash = db.get_account_by_code(MAIN, "1110").await.unwrap().unwrap();
+        let sales = db.get_account_by_code(MAIN, "4100").await.unwrap().unwrap();
+
+        let mut request = simple_transaction(cash.id, sales.id, Decimal::new(40000, 2), date(2024, 5, 1));
+        request.status = Some(TransactionStatus::Draft);
+        let draft = db.create_transaction(MAIN, request).await.unwrap();
+        assert_eq!(draft.transaction.status, TransactionStatus::Draft);
+
+        // Drafts stay out of balances and the trial balance
+        assert_eq!(balance_of(&db, "1110").await, Decimal::ZERO);
+        assert_eq!(db.get_trial_balance(MAIN, None).await.unwrap().total_debits, Decimal::ZERO);
+
+        let posted = db.post_transaction(MAIN, draft.transaction.id).await.unwrap().unwrap();
+        assert_eq!(posted.transaction.status, TransactionStatus::Posted);
+        assert!(posted.transaction.posted_at.is_some());
+        assert_eq!(balance_of(&db, "1110").await, Decimal::new(40000, 2));
+        assert_eq!(db.get_trial_balance(MAIN, None).await.unwrap().total_debits, Decimal::new(40000, 2));
+
+        // Posted rows are immutable at the database layer
+        let edit = UpdateTransactionRequest {
+            description: Some("Changed".to_string()),
+            reference: None,
+            transaction_date: None,
+            journal_entries: None,
+        };
+        assert!(db.update_transaction(MAIN, draft.transaction.id, edit).await.is_err());
+        assert!(sqlx::query("DELETE FROM journal_entries WHERE transaction_id = ?")
+            .bind(draft.transaction.id)
+            .execute(db.pool())
+            .await
+            .is_err());
+
+        let voided = db.void_transaction(MAIN, draft.transaction.id).await.unwrap().unwrap();
+        assert_eq!(voided.transaction.status, TransactionStatus::Voided);
+        assert_eq!(balance_of(&db, "1110").await, Decimal::ZERO);
+        assert_eq!(db.get_trial_balance(MAIN, None).await.unwrap().total_debits, Decimal::ZERO);
+
+        // Voided is terminal
+        assert!(db.post_transaction(MAIN, draft.transaction.id).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_period_locks() {
+        use axum::{extract::{Extension, Path}, http::{HeaderMap, HeaderValue, StatusCode}, Json};
+        use handlers::periods::{ensure_period_open, update_period_status};
+
+        let db = Database::new(":memory:").await.unwrap();
+        let period = db
+            .create_period(MAIN, CreatePeriodRequest {
+                name: "2024-01".to_string(),
+                start_date: date(2024, 1, 1),
+                end_date: date(2024, 1, 31),
+            })
+            .await
+            .unwrap();
+        let state = AppState::new(db).with_admin_token(Some("secret".to_string()));
+
+        let user = HeaderMap::new();
+        let mut admin = HeaderMap::new();
+        admin.insert("x-admin-token", HeaderValue::from_static("secret"));
+
+        assert!(ensure_period_open(&state, MAIN, &user, date(2024, 1, 15)).await.is_ok());
+
+        state.database.update_period_status(MAIN, period.id, PeriodStatus::SoftClosed).await.unwrap();
+        let err = ensure_period_open(&state, MAIN, &user, date(2024, 1, 15)).await.unwrap_err();
+        assert_eq!(err.status, StatusCode::LOCKED);
+        assert!(ensure_period_open(&state, MAIN, &admin, date(2024, 1, 15)).await.is_ok());
+
+        // Only admins can hard-close, since a hard close is final
+        let hard_close = || Json(UpdatePeriodStatusRequest { status: PeriodStatus::HardClosed });
+        let err = update_period_status(Extension(state.clone()), user.clone(), Path((MAIN, period.id)), hard_close()).await.unwrap_err();
+        assert_eq!(err.status, StatusCode::FORBIDDEN);
+        assert_eq!(state.database.get_period(MAIN, period.id).await.unwrap().unwrap().status, PeriodStatus::SoftClosed);
+        let Json(closed) = update_period_status(Extension(state.clone()), admin.clone(), Path((MAIN, period.id)), hard_close()).await.unwrap();
+        assert_eq!(closed.status, PeriodStatus::HardClosed);
+        assert!(ensure_period_open(&state, MAIN, &admin, date(2024, 1, 31)).await.is_err());
+
+        // Dates outside every defined period are unrestricted
+        assert!(ensure_period_open(&state, MAIN, &user, date(2024, 2, 1)).await.is_ok());
+        assert_eq!(state.database.find_overlapping_periods(MAIN, date(2023, 12, 1), date(2024, 1, 1)).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_year_end_close_and_reopen() {
+        use axum::{extract::{Extension, Path}, http::{HeaderMap, StatusCode}, Json};
+        use handlers::closing::{close_year, preview_year_end_close};
+        use handlers::transactions::{reverse_transaction, void_transaction};
+
+        let db = Database::new(":memory:").await.unwrap();
+        let cash = account_id(&db, "1110").await;
+        let sales = account_id(&db, "4100").await;
+        let rent = account_id(&db, "5210").await;
+        let retained = account_id(&db, "3200").await;
+
+        db.create_transaction(MAIN, simple_transaction(cash, sales, Decimal::new(100000, 2), date(2024, 6, 1))).await.unwrap();
+        db.create_transaction(MAIN, simple_transaction(rent, cash, Decimal::new(30000, 2), date(2024, 7, 1))).await.unwrap();
+        db.create_transaction(MAIN, simple_transaction(cash, sales, Decimal::new(5000, 2), date(2025, 1, 5))).await.unwrap();
+
+        let (start, end) = FiscalCalendar::default().year_range(2024).unwrap();
+        let preview = db.preview_year_end_close(MAIN, 2024, start, end, retained).await.unwrap();
+        assert_eq!(preview.lines.len(), 2);
+        assert_eq!(preview.net_income, Decimal::new(70000, 2));
+        assert!(preview.transaction.validate().is_ok());
+        // Previewing posts nothing
+        assert_eq!(balance_of(&db, "3200").await, Decimal::ZERO);
+
+        let result = db.close_year(MAIN, 2024, start, end, retained).await.unwrap();
+        assert_eq!(result.transaction.transaction.transaction_date, date(2024, 12, 31));
+        assert_eq!(balance_of(&db, "3200").await, Decimal::new(-70000, 2));
+        assert_eq!(balance_of(&db, "5210").await, Decimal::ZERO);
+        // 2025 activity stays on the revenue account
+        assert_eq!(balance_of(&db, "4100").await, Decimal::new(-5000, 2));
+
+        // Idempotent per year
+        assert!(db.close_year(MAIN, 2024, start, end, retained).await.is_err());
+        assert!(!db.preview_year_end_close(MAIN, 2024, start, end, retained).await.unwrap().has_activity());
+
+        let reopened = db.reopen_year(MAIN, 2024).await.unwrap().unwrap();
+        assert_eq!(reopened.closing.status, YearEndCloseStatus::Reopened);
+        assert_eq!(reopened.transaction.transaction.reversal_of_id, Some(result.transaction.transaction.id));
+        assert_eq!(balance_of(&db, "3200").await, Decimal::ZERO);
+        assert_eq!(balance_of(&db, "5210").await, Decimal::new(30000, 2));
+        assert!(db.get_year_end_close(MAIN, 2024).await.unwrap().is_none());
+
+        // A reopened year can be closed again
+        let result = db.close_year(MAIN, 2024, start, end, retained).await.unwrap();
+        assert_eq!(db.list_year_end_closes(MAIN).await.unwrap().len(), 2);
+
+        // Closing entries and their reversals stay out of reach of the generic void and reverse
+        let closing_id = result.transaction.transaction.id;
+        assert!(db.is_year_end_transaction(MAIN, closing_id).await.unwrap());
+        assert!(db.is_year_end_transaction(MAIN, reopened.transaction.transaction.id).await.unwrap());
+        let state = AppState::new(db);
+        let err = void_transaction(Extension(state.clone()), HeaderMap::new(), Path((MAIN, closing_id))).await.unwrap_err();
+        assert_eq!(err.status, StatusCode::CONFLICT);
+        let reverse = ReverseTransactionRequest { transaction_date: date(2025, 1, 1), description: None, reference: None };
+        let err = reverse_transaction(Extension(state.clone()), HeaderMap::new(), Path((MAIN, closing_id)), Json(reverse)).await.unwrap_err();
+        assert_eq!(err.status, StatusCode::CONFLICT);
+
+        // Fiscal years outside the supported dates are a bad request
+        let far_future = || Json(YearEndCloseRequest { fiscal_year: 999_999, equity_account_id: None });
+        let err = preview_year_end_close(Extension(state.clone()), Path(MAIN), far_future()).await.unwrap_err();
+        assert_eq!(err.status, StatusCode::BAD_REQUEST);
+        let err = close_year(Extension(state.clone()), Path(MAIN), HeaderMap::new(), far_future()).await.unwrap_err();
+        assert_eq!(err.status, StatusCode::BAD_REQUEST);
+
+        // A closing entry voided behind the close's back is not reversed again on reopening
+        state.database.void_transaction(MAIN, closing_id).await.unwrap();
+        assert!(state.database.reopen_year(MAIN, 2024).await.is_err());
+        assert_eq!(balance_of(&state.database, "5210").await, Decimal::new(30000, 2));
+    }
+
+    #[tokio::test]
+    async fn test_multi_currency_lines() {
+        use handlers::currency::resolve_exchange_rates;
+
+        let db = Database::new(":memory:").await.unwrap();
+        let eur_bank = db
+            .create_account(MAIN, CreateAccountRequest {
+                code: "1115".to_string(),
+                name: "EUR Bank".to_string(),
+                account_type: AccountType::Asset,
+                parent_id: None,
+                currency: Some("EUR".to_string()),
+                is_header: false,
+                cash_flow_category: Some(CashFlowCategory::Cash),
+                control_party_type: None,
+            })
+            .await
+            .unwrap();
+        let equity = account_id(&db, "3100").await;
+        db.upsert_exchange_rate(MAIN, CreateExchangeRateRequest {
+            currency: "EUR".to_string(),
+            rate_date: date(2024, 1, 1),
+            rate: Decimal::new(11, 1),
+        })
+        .await
+        .unwrap();
+        let state = AppState::new(db);
+
+        let mut request = simple_transaction(eur_bank.id, equity, Decimal::new(11000, 2), date(2024, 3, 1));
+        request.journal_entries[0].currency = Some("EUR".to_string());
+        request.journal_entries[0].currency_amount = Some(Decimal::new(10000, 2));
+        request.journal_entries[1].currency = Some("USD".to_string());
+
+        // The latest rate on or before the date is filled in; the functional currency is dropped
+        resolve_exchange_rates(&state, MAIN, request.transaction_date, &mut request.journal_entries).await.unwrap();
+        assert_eq!(request.journal_entries[0].exchange_rate, Some(Decimal::new(11, 1)));
+        assert!(request.journal_entries[1].currency.is_none());
+        assert!(request.validate().is_ok());
+
+        // The functional amount must match the converted currency amount
+        request.journal_entries[0].exchange_rate = Some(Decimal::new(12, 1));
+        assert!(request.validate().is_err());
+        request.journal_entries[0].exchange_rate = Some(Decimal::new(11, 1));
+
+        let created = state.database.create_transaction(MAIN, request).await.unwrap();
+        let line = &created.journal_entries[0];
+        assert_eq!(line.currency.as_deref(), Some("EUR"));
+        assert_eq!(line.currency_amount, Some(Decimal::new(10000, 2)));
+        assert_eq!(line.exchange_rate, Some(Decimal::new(11, 1)));
+        assert_eq!(balance_of(&state.database, "1115").await, Decimal::new(11000, 2));
+
+        // Lines all in one foreign currency must also balance in that currency
+        let mut eur_only = simple_transaction(eur_bank.id, eur_bank.id, Decimal::new(11000, 2), date(2024, 3, 1));
+        for (entry, amount) in eur_only.journal_entries.iter_mut().zip([10000, 10001]) {
+            entry.currency = Some("EUR".to_string());
+            entry.currency_amount = Some(Decimal::new(amount, 2));
+            entry.exchange_rate = Some(Decimal::new(11, 1));
+        }
+        assert!(eur_only.validate().is_err());
+
+        // A split whose lines round apart balances by taking a cent off one line
+        let eur_line = |debit: Option<i64>, credit: Option<i64>, currency_amount| CreateJournalEntryRequest {
+            account_id: eur_bank.id,
+            debit_amount: debit.map(|amount| Decimal::new(amount, 2)),
+            credit_amount: credit.map(|amount| Decimal::new(amount, 2)),
+            currency: Some("EUR".to_string()),
+            currency_amount: Some(Decimal::new(currency_amount, 2)),
+            exchange_rate: Some(Decimal::new(1105, 3)),
+            ..Default::default()
+        };
+        let mut split = simple_transaction(eur_bank.id, eur_bank.id, Decimal::ZERO, date(2024, 3, 2));
+        split.journal_entries = vec![
+            eur_line(Some(1105), None, 1000),
+            eur_line(None, Some(553), 500),
+            eur_line(None, Some(552), 500),
+        ];
+        assert!(split.validate().is_ok());
+        state.database.create_transaction(MAIN, split).await.unwrap();
+
+        // More than a cent away from the converted amount is still refused
+        let mut off = simple_transaction(eur_bank.id, eur_bank.id, Decimal::ZERO, date(2024, 3, 2));
+        off.journal_entries = vec![
+            eur_line(Some(1105), None, 1000),
+            eur_line(None, Some(554), 500),
+            eur_line(None, Some(551), 500),
+        ];
+        assert!(off.validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_fx_revaluation() {
+        let db = Database::new(":memory:").await.unwrap();
+        let eur_bank = db
+            .create_account(MAIN, CreateAccountRequest {
+                code: "1115".to_string(),
+                name: "EUR Bank".to_string(),
+                account_type: AccountType::Asset,
+                parent_id: None,
+                currency: Some("EUR".to_string()),
+                is_header: false,
+                cash_flow_category: Some(CashFlowCategory::Cash),
+                control_party_type: None,
+            })
+            .await
+            .unwrap();
+        let equity = account_id(&db, "3100").await;
+        let gain = account_id(&db, "4900").await;
+        let loss = account_id(&db, "5900").await;
+        for (rate_date, rate) in [(date(2024, 1, 1), 11), (date(2024, 1, 31), 12)] {
+            db.upsert_exchange_rate(MAIN, CreateExchangeRateRequest {
+                currency: "EUR".to_string(),
+                rate_date,
+                rate: Decimal::new(rate, 1),
+            })
+            .await
+            .unwrap();
+        }
+
+        let mut deposit = simple_transaction(eur_bank.id, equity, Decimal::new(11000, 2), date(2024, 1, 10));
+        deposit.journal_entries[0].currency = Some("EUR".to_string());
+        deposit.journal_entries[0].currency_amount = Some(Decimal::new(10000, 2));
+        deposit.journal_entries[0].exchange_rate = Some(Decimal::new(11, 1));
+        db.create_transaction(MAIN, deposit).await.unwrap();
+
+        let preview = db.preview_fx_revaluation(MAIN, date(2024, 1, 31), date(2024, 2, 1), gain, loss).await.unwrap();
+        assert_eq!(preview.lines.len(), 1);
+        assert_eq!(preview.lines[0].revalued_balance, Decimal::new(12000, 2));
+        assert_eq!(preview.total_gain, Decimal::new(1000, 2));
+        assert!(preview.missing_rates.is_empty());
+
+        let result = db.run_fx_revaluation(MAIN, date(2024, 1, 31), date(2024, 2, 1), gain, loss).await.unwrap();
+        assert_eq!(result.reversal.transaction.transaction_date, date(2024, 2, 1));
+        assert_eq!(result.reversal.transaction.reversal_of_id, Some(result.transaction.transaction.id));
+        // The adjustment and its reversal are both posted, so the ledger nets back to book value
+        assert_eq!(balance_of(&db, "1115").await, Decimal::new(11000, 2));
+        assert_eq!(balance_of(&db, "4900").await, Decimal::ZERO);
+
+        // At the revaluation date the account is already at the closing rate
+        let rerun = db.preview_fx_revaluation(MAIN, date(2024, 1, 31), date(2024, 2, 1), gain, loss).await.unwrap();
+        assert!(!rerun.has_adjustments());
+
+        let report = db.get_fx_revaluation(MAIN, result.revaluation.id).await.unwrap().unwrap();
+        assert_eq!(report.lines[0].adjustment, Decimal::new(1000, 2));
+        assert_eq!(report.lines[0].exchange_rate, Decimal::new(12, 1));
+    }
+
+    #[tokio::test]
+    async fn test_companies_keep_separate_books() {
+        let db = Database::new(":memory:").await.unwrap();
+        let other = db
+            .create_company(CreateCompanyRequest {
+                code: "SUB".to_string(),
+                name: "Subsidiary".to_string(),
+                functional_currency: None,
+            })
+            .await
+            .unwrap();
+
+        // The new company gets its own copy of the template chart
+        assert_eq!(db.list_accounts(other.id).await.unwrap().len(), db.list_accounts(MAIN).await.unwrap().len());
+        let other_cash = db.get_account_by_code(other.id, "1110").await.unwrap().unwrap();
+        let other_sales = db.get_account_by_code(other.id, "4100").await.unwrap().unwrap();
+        let main_cash = account_id(&db, "1110").await;
+        assert_ne!(other_cash.id, main_cash);
+
+        let sale = db
+            .create_transaction(other.id, simple_transaction(other_cash.id, other_sales.id, Decimal::new(20000, 2), date(2024, 2, 1)))
+            .await
+            .unwrap();
+        assert_eq!(sale.transaction.company_id, other.id);
+        assert_eq!(balance_of(&db, "1110").await, Decimal::ZERO);
+        assert!(db.get_transaction(MAIN, sale.transaction.id).await.unwrap().is_none());
+        assert!(db.get_account(MAIN, other_cash.id).await.unwrap().is_none());
+        assert_eq!(db.get_trial_balance(other.id, None).await.unwrap().total_debits, Decimal::new(20000, 2));
+        assert_eq!(db.get_trial_balance(MAIN, None).await.unwrap().total_debits, Decimal::ZERO);
+
+        // Journal lines cannot reach into another company's chart
+        let sales = account_id(&db, "4100").await;
+        assert!(db
+            .create_transaction(other.id, simple_transaction(other_cash.id, sales, Decimal::new(100, 2), date(2024, 2, 1)))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_companies_keep_separate_currencies() {
+        use axum::{extract::{Extension, Path}, http::StatusCode, Json};
+        use handlers::{companies::create_company, currency::{create_exchange_rate, resolve_exchange_rates}};
+
+        let state = AppState::new(Database::new(":memory:").await.unwrap()).with_functional_currency("GBP");
+        let company = |code: &str, functional_currency: Option<&str>| CreateCompanyRequest {
+            code: code.to_string(),
+            name: code.to_string(),
+            functional_currency: functional_currency.map(str::to_string),
+        };
+        let (_, Json(euro)) = create_company(Extension(state.clone()), Json(company("EURO", Some("EUR")))).await.unwrap();
+        let (_, Json(british)) = create_company(Extension(state.clone()), Json(company("UK", None))).await.unwrap();
+        assert_eq!(euro.functional_currency, "EUR");
+        assert_eq!(british.functional_currency, "GBP");
+        assert_eq!(state.database.get_company(MAIN).await.unwrap().unwrap().functional_currency, "USD");
+
+        // Each company reads its own rates into its own functional currency
+        let rate = |currency: &str, rate| CreateExchangeRateRequest {
+            currency: currency.to_string(),
+            rate_date: date(2024, 1, 1),
+            rate: Decimal::new(rate, 1),
+        };
+        let (_, Json(dollar)) = create_exchange_rate(Extension(state.clone()), Path(euro.id), Json(rate("USD", 9))).await.unwrap();
+        assert_eq!(dollar.company_id, euro.id);
+        state.database.upsert_exchange_rate(MAIN, rate("EUR", 11)).await.unwrap();
+        let err = create_exchange_rate(Extension(state.clone()), Path(euro.id), Json(rate("EUR", 10))).await.unwrap_err();
+        assert_eq!(err.status, StatusCode::BAD_REQUEST);
+        assert!(state.database.upsert_exchange_rate(euro.id, rate("EUR", 10)).await.is_err());
+        assert!(state.database.get_exchange_rate(euro.id, "EUR", date(2024, 2, 1)).await.unwrap().is_none());
+        assert!(state.database.get_exchange_rate(MAIN, "USD", date(2024, 2, 1)).await.unwrap().is_none());
+        assert_eq!(state.database.list_exchange_rates(MAIN, None).await.unwrap().len(), 1);
+
+        let euro_cash = state.database.get_account_by_code(euro.id, "1110").await.unwrap().unwrap();
+        let euro_sales = state.database.get_account_by_code(euro.id, "4100").await.unwrap().unwrap();
+        let mut request = simple_transaction(euro_cash.id, euro_sales.id, Decimal::new(900, 2), date(2024, 2, 1));
+        request.journal_entries[0].currency = Some("EUR".to_string());
+        request.journal_entries[1].currency = Some("USD".to_string());
+        request.journal_entries[1].currency_amount = Some(Decimal::new(1000, 2));
+        resolve_exchange_rates(&state, euro.id, request.transaction_date, &mut request.journal_entries).await.unwrap();
+        assert!(request.journal_entries[0].currency.is_none());
+        assert_eq!(request.journal_entries[1].exchange_rate, Some(Decimal::new(9, 1)));
+    }
+
+    #[tokio::test]
+    async fn test_account_tree_rollup() {
+        use handlers::reports::{BalanceSheet, ReportQuery};
+
+        let db = Database::new(":memory:").await.unwrap();
+        let cash = account_id(&db, "1110").await;
+        let equipment = account_id(&db, "1210").await;
+        let equity = account_id(&db, "3100").await;
+        db.create_transaction(MAIN, simple_transaction(cash, equity, Decimal::new(50000, 2), date(2024, 1, 2))).await.unwrap();
+        db.create_transaction(MAIN, simple_transaction(equipment, cash, Decimal::new(20000, 2), date(2024, 1, 3))).await.unwrap();
+
+        let forest = AccountTreeNode::build_forest(db.list_accounts(MAIN).await.unwrap());
+        let assets = forest.iter().find(|node| node.account.code == "1000").unwrap();
+        assert_eq!(assets.account.balance, Decimal::ZERO);
+        assert_eq!(assets.total_balance, Decimal::new(50000, 2));
+        let current = &assets.children[0];
+        assert_eq!((current.account.code.as_str(), current.depth), ("1100", 1));
+        assert_eq!(current.total_balance, Decimal::new(30000, 2));
+        assert_eq!(current.children[0].depth, 2);
+
+        // A depth limit keeps the rolled-up totals and hides the detail
+        let query = ReportQuery { rollup: true, depth: Some(1), ..Default::default() };
+        let mut summarized = BalanceSheet::new();
+        for node in query.forest(db.list_accounts(MAIN).await.unwrap()) {
+            summarized.add_tree(&node);
+        }
+        let codes: Vec<&str> = summarized.assets.accounts.iter().map(|line| line.code.as_str()).collect();
+        assert_eq!(codes, ["1000", "1100", "1200"]);
+        assert_eq!(summarized.assets.total, Decimal::new(50000, 2));
+        assert_eq!(summarized.equity.total, Decimal::new(50000, 2));
+        assert!(summarized.is_balanced);
+    }
+
+    #[tokio::test]
+    async fn test_account_reparenting() {
+        let db = Database::new(":memory:").await.unwrap();
+        let current_assets = db.get_account_by_code(MAIN, "1100").await.unwrap().unwrap();
+        let fixed_assets = account_id(&db, "1200").await;
+        let cash = account_id(&db, "1110").await;
+        let payables = db.get_account_by_code(MAIN, "2110").await.unwrap().unwrap();
+
+        // 1100 sits above 1110, so putting 1100 under 1110 would close a cycle
+        let chain = db.get_account_chain(MAIN, cash).await.unwrap();
+        assert_eq!(chain, vec![cash, current_assets.id, account_id(&db, "1000").await]);
+        assert!(validate_parent(Some(current_assets.id), &AccountType::Asset, &db.get_account(MAIN, cash).await.unwrap().unwrap(), &chain).is_err());
+        assert!(validate_parent(None, &AccountType::Asset, &payables, &[payables.id]).is_err());
+
+        let moved = db
+            .move_account(MAIN, current_assets.id, MoveAccountRequest { parent_id: Some(fixed_assets) })
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(moved.account.parent_id, Some(fixed_assets));
+        let codes: Vec<&str> = moved.moved_descendants.iter().map(|account| account.code.as_str()).collect();
+        assert_eq!(codes, ["1110", "1120", "1130"]);
+
+        // The database re-checks the hierarchy, leaving the tree as it was on failure
+        assert!(db.move_account(MAIN, fixed_assets, MoveAccountRequest { parent_id: Some(cash) }).await.is_err());
+        assert!(db.move_account(MAIN, current_assets.id, MoveAccountRequest { parent_id: Some(payables.id) }).await.is_err());
+        assert_eq!(db.get_account(MAIN, current_assets.id).await.unwrap().unwrap().parent_id, Some(fixed_assets));
+
+        let top = db.move_account(MAIN, current_assets.id, MoveAccountRequest { parent_id: None }).await.unwrap().unwrap();
+        assert_eq!(top.account.parent_id, None);
+        assert_eq!(top.previous_parent_id, Some(fixed_assets));
+    }
+
+    #[tokio::test]
+    async fn test_account_archiving() {
+        let db = Database::new(":memory:").await.unwrap();
+        let cash = account_id(&db, "1110").await;
+        let sales = account_id(&db, "4100").await;
+        let supplies = account_id(&db, "5230").await;
+        db.create_transaction(MAIN, simple_transaction(cash, sales, Decimal::new(7500, 2), date(2024, 3, 1))).await.unwrap();
+
+        let references = db.get_account_references(MAIN, sales).await.unwrap();
+        assert_eq!((references.journal_entries, references.child_accounts), (1, 0));
+        assert!(!db.get_account_references(MAIN, account_id(&db, "4000").await).await.unwrap().is_empty());
+        assert!(db.get_account_references(MAIN, supplies).await.unwrap().is_empty());
+
+        let archived = db.set_account_active(MAIN, sales, false).await.unwrap().unwrap();
+        assert!(!archived.is_active);
+        assert!(archived.archived_at.is_some());
+        // Archived accounts keep their history in reports
+        let trial_balance = db.get_trial_balance(MAIN, None).await.unwrap();
+        assert!(trial_balance.entries.iter().any(|entry| entry.account_id == sales && entry.credit_balance == Decimal::new(7500, 2)));
+
+        let restored = db.set_account_active(MAIN, sales, true).await.unwrap().unwrap();
+        assert!(restored.is_active);
+        assert!(restored.archived_at.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_header_accounts() {
+        let db = Database::new(":memory:").await.unwrap();
+        let assets = db.get_account_by_code(MAIN, "1000").await.unwrap().unwrap();
+        let cash = db.get_account_by_code(MAIN, "1110").await.unwrap().unwrap();
+        let sales = account_id(&db, "4100").await;
+        assert!(assets.is_header);
+        assert!(assets.posting_restriction().is_some());
+        assert!(!cash.is_header);
+        assert!(cash.posting_restriction().is_none());
+
+        // The database refuses lines on headers even when the handler checks are bypassed
+        assert!(db.create_transaction(MAIN, simple_transaction(assets.id, sales, Decimal::new(100, 2), date(2024, 1, 1))).await.is_err());
+
+        db.create_transaction(MAIN, simple_transaction(cash.id, sales, Decimal::new(100, 2), date(2024, 1, 1))).await.unwrap();
+        let to_header = UpdateAccountRequest { name: None, parent_id: None, is_header: Some(true), cash_flow_category: None, control_party_type: None };
+        assert!(db.update_account(MAIN, cash.id, to_header).await.is_err());
+
+        // New companies get the template headers
+        let other = db
+            .create_company(CreateCompanyRequest { code: "SUB".to_string(), name: "Subsidiary".to_string(), functional_currency: None })
+            .await
+            .unwrap();
+        assert!(db.get_account_by_code(other.id, "5200").await.unwrap().unwrap().is_header);
+    }
+
+    #[tokio::test]
+    async fn test_as_of_balances() {
+        let db = Database::new(":memory:").await.unwrap();
+        let cash = account_id(&db, "1110").await;
+        let equity = account_id(&db, "3100").await;
+        db.create_transaction(MAIN, simple_transaction(cash, equity, Decimal::new(10000, 2), date(2024, 3, 31))).await.unwrap();
+        db.create_transaction(MAIN, simple_transaction(cash, equity, Decimal::new(2500, 2), date(2024, 4, 1))).await.unwrap();
+        let mut draft = simple_transaction(cash, equity, Decimal::new(900, 2), date(2024, 3, 1));
+        draft.status = Some(TransactionStatus::Draft);
+        db.create_transaction(MAIN, draft).await.unwrap();
+
+        let quarter_end = db.get_trial_balance(MAIN, Some(date(2024, 3, 31))).await.unwrap();
+        assert_eq!(quarter_end.total_debits, Decimal::new(10000, 2));
+        assert!(quarter_end.is_balanced);
+        assert_eq!(db.get_trial_balance(MAIN, None).await.unwrap().total_debits, Decimal::new(12500, 2));
+
+        let accounts = db.list_accounts_as_of(MAIN, date(2024, 3, 31)).await.unwrap();
+        let cash_then = accounts.iter().find(|account| account.id == cash).unwrap();
+        assert_eq!(cash_then.balance, Decimal::new(10000, 2));
+        assert_eq!(balance_of(&db, "1110").await, Decimal::new(12500, 2));
+
+        let summary = db.get_account_summary(MAIN, Some(date(2024, 3, 30))).await.unwrap();
+        assert_eq!(summary.total_assets, Decimal::ZERO);
+        assert_eq!(db.get_account_summary(MAIN, Some(date(2024, 4, 1))).await.unwrap().total_equity, Decimal::new(12500, 2));
+    }
+
+    #[tokio::test]
+    async fn test_income_statement_range() {
+        use handlers::reports::ReportQuery;
+
+        let db = Database::new(":memory:").await.unwrap();
+        let cash = account_id(&db, "1110").await;
+        let sales = account_id(&db, "4100").await;
+        let retained = account_id(&db, "3200").await;
+        db.create_transaction(MAIN, simple_transaction(cash, sales, Decimal::new(30000, 2), date(2023, 11, 1))).await.unwrap();
+        db.create_transaction(MAIN, simple_transaction(cash, sales, Decimal::new(4000, 2), date(2024, 2, 10))).await.unwrap();
+        let (start, end) = FiscalCalendar::default().year_range(2023).unwrap();
+        db.close_year(MAIN, 2023, start, end, retained).await.unwrap();
+
+        // The closing entry does not wipe out the year it closes
+        let fy2023 = db.list_accounts_for_period(MAIN, Some(start), Some(end)).await.unwrap();
+        assert_eq!(fy2023.iter().find(|account| account.id == sales).unwrap().balance, Decimal::new(-30000, 2));
+        let february = db.list_accounts_for_period(MAIN, Some(date(2024, 2, 1)), Some(date(2024, 2, 29))).await.unwrap();
+        assert_eq!(february.iter().find(|account| account.id == sales).unwrap().balance, Decimal::new(-4000, 2));
+
+        let query = ReportQuery { period: Some(PeriodPreset::YearToDate), end_date: Some(date(2024, 3, 31)), ..Default::default() };
+        assert_eq!(query.date_range(&FiscalCalendar::default()).unwrap(), (Some(date(2024, 1, 1)), Some(date(2024, 3, 31))));
+        let backwards = ReportQuery { start_date: Some(date(2024, 3, 1)), end_date: Some(date(2024, 2, 1)), ..Default::default() };
+        assert!(backwards.date_range(&FiscalCalendar::default()).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_comparative_statements() {
+        use handlers::reports::{ComparativeBalanceSheet, ComparativeColumn, ComparativeIncomeStatement, ReportQuery};
+
+        assert_eq!(Comparison::PreviousMonth.shift_back(date(2024, 3, 31), 1), Some(date(2024, 2, 29)));
+        assert_eq!(Comparison::PreviousMonth.shift_back(date(2024, 3, 15), 2), Some(date(2024, 1, 15)));
+        assert_eq!(Comparison::PreviousYear.shift_back(date(2024, 2, 29), 1), Some(date(2023, 2, 28)));
+        assert_eq!(Comparison::PreviousYear.shift_back(NaiveDate::MIN, 1), None);
+
+        let db = Database::new(":memory:").await.unwrap();
+        let cash = account_id(&db, "1110").await;
+        let receivable = account_id(&db, "1120").await;
+        let sales = account_id(&db, "4100").await;
+        db.create_transaction(MAIN, simple_transaction(cash, sales, Decimal::new(4000, 2), date(2024, 2, 10))).await.unwrap();
+        db.create_transaction(MAIN, simple_transaction(cash, sales, Decimal::new(10000, 2), date(2024, 3, 5))).await.unwrap();
+        db.create_transaction(MAIN, simple_transaction(receivable, sales, Decimal::new(2500, 2), date(2024, 3, 6))).await.unwrap();
+
+        let query = ReportQuery::default();
+        let mut statements = Vec::new();
+        let mut columns = Vec::new();
+        for (start, end) in [(date(2024, 3, 1), date(2024, 3, 31)), (date(2024, 2, 1), date(2024, 2, 29))] {
+            let accounts = db.list_accounts_for_period(MAIN, Some(start), Some(end)).await.unwrap();
+            statements.push(query.income_statement(accounts, Some(start), Some(end)));
+            columns.push(ComparativeColumn { start_date: Some(start), end_date: end });
+        }
+        let comparative = ComparativeIncomeStatement::new(columns, &statements);
+        let row = comparative.revenue.accounts.iter().find(|row| row.id == sales).unwrap();
+        assert_eq!(row.amounts, vec![Decimal::new(12500, 2), Decimal::new(4000, 2)]);
+        assert_eq!(row.variances[0].amount, Decimal::new(8500, 2));
+        assert_eq!(row.variances[0].percent, Some(Decimal::new(21250, 2)));
+        assert_eq!(comparative.net_income, vec![Decimal::new(12500, 2), Decimal::new(4000, 2)]);
+
+        // An account missing from the earlier period still gets a row, with zero there
+        let current = db.list_accounts_as_of(MAIN, date(2024, 3, 31)).await.unwrap();
+        let mut previous = db.list_accounts_as_of(MAIN, date(2024, 2, 29)).await.unwrap();
+        previous.retain(|account| account.id != receivable);
+        let comparative = ComparativeBalanceSheet::new(
+            vec![
+                ComparativeColumn { start_date: None, end_date: date(2024, 3, 31) },
+                ComparativeColumn { start_date: None, end_date: date(2024, 2, 29) },
+            ],
+            &[query.balance_sheet(current), query.balance_sheet(previous)],
+        );
+        let row = comparative.assets.accounts.iter().find(|row| row.id == receivable).unwrap();
+        assert_eq!(row.amounts, vec![Decimal::new(2500, 2), Decimal::ZERO]);
+        assert_eq!(row.variances[0].percent, None);
+        assert_eq!(comparative.total_assets, vec![Decimal::new(16500, 2), Decimal::new(4000, 2)]);
+        assert_eq!(comparative.assets.total_variances[0].amount, Decimal::new(12500, 2));
+    }
+
+    #[tokio::test]
+    async fn test_cash_flow_statement() {
+        use handlers::reports::CashFlowStatement;
+
+        let db = Database::new(":memory:").await.unwrap();
+        let cash = account_id(&db, "1110").await;
+        let receivable = account_id(&db, "1120").await;
+        let equipment = account_id(&db, "1210").await;
+        let payable = account_id(&db, "2110").await;
+        let debt = account_id(&db, "2210").await;
+        let owner = account_id(&db, "3100").await;
+        let sales = account_id(&db, "4100").await;
+        let rent = account_id(&db, "5210").await;
+        let supplies = account_id(&db, "5230").await;
+
+        db.create_transaction(MAIN, simple_transaction(cash, owner, Decimal::new(100000, 2), date(2023, 12, 15))).await.unwrap();
+        for (debit, credit, amount) in [
+            (receivable, sales, 50000),
+            (cash, receivable, 30000),
+            (equipment, cash, 40000),
+            (cash, debt, 50000),
+            (rent, cash, 10000),
+            (supplies, payable, 5000),
+        ] {
+            db.create_transaction(MAIN, simple_transaction(debit, credit, Decimal::new(amount, 2), date(2024, 1, 20))).await.unwrap();
+        }
+
+        let (start, end) = (date(2024, 1, 1), date(2024, 1, 31));
+        let mut statement = CashFlowStatement::new(start, end);
+        for account in db.list_accounts_for_period(MAIN, Some(start), Some(end)).await.unwrap() {
+            statement.add_account(&account);
+        }
+        let opening = db.list_accounts_as_of(MAIN, date(2023, 12, 31)).await.unwrap();
+        let closing = db.list_accounts_as_of(MAIN, end).await.unwrap();
+        statement.set_cash_balances(&opening, &closing);
+
+        assert_eq!(statement.net_income, Decimal::new(35000, 2));
+        // Net income less the growth in receivables plus the growth in payables
+        assert_eq!(statement.operating.total, Decimal::new(20000, 2));
+        assert_eq!(statement.investing.total, Decimal::new(-40000, 2));
+        assert_eq!(statement.financing.total, Decimal::new(50000, 2));
+        assert_eq!(statement.net_change_in_cash, Decimal::new(30000, 2));
+        assert_eq!((statement.opening_cash, statement.closing_cash), (Decimal::new(100000, 2), Decimal::new(130000, 2)));
+        assert!(statement.is_reconciled);
+
+        assert!(validate_cash_flow_category(&AccountType::Revenue, CashFlowCategory::Operating).is_err());
+        assert!(validate_cash_flow_category(&AccountType::Liability, CashFlowCategory::Cash).is_err());
+        assert!(validate_cash_flow_category(&AccountType::Asset, CashFlowCategory::Investing).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_equity_movements() {
+        let db = Database::new(":memory:").await.unwrap();
+        let cash = account_id(&db, "1110").await;
+        let owner = account_id(&db, "3100").await;
+        let retained = account_id(&db, "3200").await;
+        let sales = account_id(&db, "4100").await;
+        db.create_transaction(MAIN, simple_transaction(cash, owner, Decimal::new(100000, 2), date(2023, 3, 1))).await.unwrap();
+        db.create_transaction(MAIN, simple_transaction(cash, sales, Decimal::new(30000, 2), date(2023, 6, 1))).await.unwrap();
+        let (start, end) = FiscalCalendar::default().year_range(2023).unwrap();
+        db.close_year(MAIN, 2023, start, end, retained).await.unwrap();
+        db.create_transaction(MAIN, simple_transaction(cash, owner, Decimal::new(20000, 2), date(2024, 2, 1))).await.unwrap();
+        db.create_transaction(MAIN, simple_transaction(owner, cash, Decimal::new(5000, 2), date(2024, 3, 1))).await.unwrap();
+
+        // The closing entry is the year's net income allocation, not a contribution
+        let fy2023 = db.get_equity_movements(MAIN, start, end).await.unwrap();
+        let retained_2023 = fy2023.iter().find(|movement| movement.account_id == retained).unwrap();
+        assert_eq!(retained_2023.contributions, Decimal::ZERO);
+        assert_eq!(retained_2023.net_income_allocation, Decimal::new(30000, 2));
+        assert_eq!(retained_2023.closing_balance, Decimal::new(30000, 2));
+
+        let fy2024 = db.get_equity_movements(MAIN, date(2024, 1, 1), date(2024, 12, 31)).await.unwrap();
+        let owner_2024 = fy2024.iter().find(|movement| movement.account_id == owner).unwrap();
+        assert_eq!(owner_2024.opening_balance, Decimal::new(100000, 2));
+        assert_eq!(owner_2024.contributions, Decimal::new(20000, 2));
+        assert_eq!(owner_2024.distributions, Decimal::new(5000, 2));
+        assert_eq!(owner_2024.closing_balance, Decimal::new(115000, 2));
+        let retained_2024 = fy2024.iter().find(|movement| movement.account_id == retained).unwrap();
+        assert_eq!((retained_2024.opening_balance, retained_2024.closing_balance), (Decimal::new(30000, 2), Decimal::new(30000, 2)));
+
+        // Closing balances tie to the balance sheet at the end of the range
+        let as_of = db.list_accounts_as_of(MAIN, date(2024, 12, 31)).await.unwrap();
+        for movement in &fy2024 {
+            let account = as_of.iter().find(|account| account.id == movement.account_id).unwrap();
+            assert_eq!(account.normal_balance(), movement.closing_balance);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_balance_sheet_current_year_earnings() {
+        use handlers::reports::{ReportQuery, CURRENT_YEAR_EARNINGS_ID};
+
+        let db = Database::new(":memory:").await.unwrap();
+        let cash = account_id(&db, "1110").await;
+        let owner = account_id(&db, "3100").await;
+        let retained = account_id(&db, "3200").await;
+        let sales = account_id(&db, "4100").await;
+        let rent = account_id(&db, "5210").await;
+        db.create_transaction(MAIN, simple_transaction(cash, owner, Decimal::new(100000, 2), date(2024, 1, 2))).await.unwrap();
+        db.create_transaction(MAIN, simple_transaction(cash, sales, Decimal::new(30000, 2), date(2024, 3, 1))).await.unwrap();
+        db.create_transaction(MAIN, simple_transaction(rent, cash, Decimal::new(12000, 2), date(2024, 4, 1))).await.unwrap();
+
+        // Mid-year the unclosed profit keeps the statement in balance, flat or rolled up
+        for query in [ReportQuery::default(), ReportQuery { rollup: true, ..Default::default() }] {
+            let balance_sheet = query.balance_sheet(db.list_accounts(MAIN).await.unwrap());
+            assert_eq!(balance_sheet.current_year_earnings, Decimal::new(18000, 2));
+            assert_eq!(balance_sheet.equity.total, Decimal::new(118000, 2));
+            assert_eq!(balance_sheet.equity.accounts.last().unwrap().id, CURRENT_YEAR_EARNINGS_ID);
+            assert!(balance_sheet.is_balanced);
+        }
+        assert!(db.get_account_summary(MAIN, None).await.unwrap().is_balanced());
+
+        // Once the year is closed the profit sits in retained earnings instead
+        let (start, end) = FiscalCalendar::default().year_range(2024).unwrap();
+        db.close_year(MAIN, 2024, start, end, retained).await.unwrap();
+        let balance_sheet = ReportQuery::default().balance_sheet(db.list_accounts(MAIN).await.unwrap());
+        assert_eq!(balance_sheet.current_year_earnings, Decimal::ZERO);
+        let retained_line = balance_sheet.equity.accounts.iter().find(|line| line.id == retained).unwrap();
+        assert_eq!(retained_line.balance, Decimal::new(18000, 2));
+        assert!(balance_sheet.is_balanced);
+
+        // A loss that overdraws cash and leaves a deficit is subtracted, not added
+        db.create_transaction(MAIN, simple_transaction(rent, cash, Decimal::new(200000, 2), date(2025, 2, 1))).await.unwrap();
+        let summary = db.get_account_summary(MAIN, None).await.unwrap();
+        let balance_sheet = ReportQuery::default().balance_sheet(db.list_accounts(MAIN).await.unwrap());
+        assert_eq!(summary.total_assets, Decimal::new(-82000, 2));
+        assert_eq!(summary.net_income, Decimal::new(-200000, 2));
+        assert_eq!(balance_sheet.current_year_earnings, Decimal::new(-200000, 2));
+        assert_eq!(summary.is_balanced(), balance_sheet.is_balanced);
+        assert!(summary.is_balanced());
+        let (start, end) = FiscalCalendar::default().year_range(2025).unwrap();
+        db.close_year(MAIN, 2025, start, end, retained).await.unwrap();
+        for query in [ReportQuery::default(), ReportQuery { rollup: true, ..Default::default() }] {
+            let balance_sheet = query.balance_sheet(db.list_accounts(MAIN).await.unwrap());
+            let retained_line = balance_sheet.equity.accounts.iter().find(|line| line.id == retained).unwrap();
+            assert_eq!(retained_line.balance, Decimal::new(-182000, 2));
+            assert_eq!(balance_sheet.total_assets, Decimal::new(-82000, 2));
+            assert_eq!(balance_sheet.equity.total, Decimal::new(-82000, 2));
+            assert!(balance_sheet.is_balanced);
+        }
+        let summary = db.get_account_summary(MAIN, None).await.unwrap();
+        assert_eq!(summary.total_equity, Decimal::new(-82000, 2));
+        assert!(summary.is_balanced());
+    }
+
+    #[tokio::test]
+    async fn test_account_ledger() {
+        let db = Database::new(":memory:").await.unwrap();
+        let cash = account_id(&db, "1110").await;
+        let owner = account_id(&db, "3100").await;
+        let rent = account_id(&db, "5210").await;
+        db.create_transaction(MAIN, simple_transaction(cash, owner, Decimal::new(10000, 2), date(2024, 1, 5))).await.unwrap();
+        db.create_transaction(MAIN, simple_transaction(owner, cash, Decimal::new(5000, 2), date(2024, 2, 10))).await.unwrap();
+        db.create_transaction(MAIN, simple_transaction(rent, cash, Decimal::new(3000, 2), date(2024, 2, 2))).await.unwrap();
+        db.create_transaction(MAIN, simple_transaction(rent, cash, Decimal::new(2000, 2), date(2024, 3, 1))).await.unwrap();
+        let mut draft = simple_transaction(rent, cash, Decimal::new(999, 2), date(2024, 2, 15));
+        draft.status = Some(TransactionStatus::Draft);
+        db.create_transaction(MAIN, draft).await.unwrap();
+
+        // Lines come in date order with the balance brought forward from January
+        let statement = db.get_account_statement(MAIN, cash, Some(date(2024, 2, 1)), Some(date(2024, 2, 29))).await.unwrap().unwrap();
+        assert_eq!(statement.opening_balance, Decimal::new(10000, 2));
+        let running: Vec<Decimal> = statement.entries.iter().map(|entry| entry.running_balance).collect();
+        assert_eq!(running, vec![Decimal::new(7000, 2), Decimal::new(2000, 2)]);
+        assert_eq!(statement.entries[0].transaction_date, date(2024, 2, 2));
+        assert_eq!(statement.closing_balance, Decimal::new(2000, 2));
+        assert_eq!((statement.total_debits, statement.total_credits), (Decimal::ZERO, Decimal::new(8000, 2)));
+
+        let ledger = db.get_general_ledger(MAIN, Some("1110"), Some("1130"), None, None).await.unwrap();
+        let codes: Vec<&str> = ledger.iter().map(|statement| statement.account_code.as_str()).collect();
+        assert_eq!(codes, vec!["1110", "1120", "1130"]);
+        assert_eq!(ledger[0].closing_balance, balance_of(&db, "1110").await);
+        assert!(ledger[1].is_empty());
+
+        assert!(db.get_account_statement(MAIN, 9999, None, None).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_party_subledger() {
+        use axum::{extract::{Extension, Path}, http::StatusCode, Json};
+        use handlers::accounts::update_account;
+
+        let db = Database::new(":memory:").await.unwrap();
+        let cash = account_id(&db, "1110").await;
+        let receivable = account_id(&db, "1120").await;
+        let sales = account_id(&db, "4100").await;
+        let party = |code: &str, party_type| CreatePartyRequest { code: code.to_string(), name: code.to_string(), party_type };
+        let acme = db.create_party(MAIN, party("ACME", PartyType::Customer)).await.unwrap();
+        let globex = db.create_party(MAIN, party("GLOBEX", PartyType::Customer)).await.unwrap();
+        let supplier = db.create_party(MAIN, party("SUPPLY", PartyType::Vendor)).await.unwrap();
+
+        // A line written before receivables needed a party
+        db.create_transaction(MAIN, simple_transaction(receivable, sales, Decimal::new(1000, 2), date(2024, 1, 2))).await.unwrap();
+        for (debit, credit, party_line, party_id, amount) in [
+            (receivable, sales, 0, acme.id, 10000),
+            (receivable, sales, 0, globex.id, 5000),
+            (cash, receivable, 1, acme.id, 4000),
+        ] {
+            let mut request = simple_transaction(debit, credit, Decimal::new(amount, 2), date(2024, 2, 1));
+            request.journal_entries[party_line].party_id = Some(party_id);
+            db.create_transaction(MAIN, request).await.unwrap();
+        }
+
+        let balances = db.get_control_account_balances(MAIN, None).await.unwrap();
+        let control = balances.iter().find(|control| control.account_id == receivable).unwrap();
+        assert_eq!(control.balance, Decimal::new(12000, 2));
+        let by_party: Vec<(&str, Decimal)> = control.parties.iter().map(|party| (party.party_code.as_str(), party.balance)).collect();
+        assert_eq!(by_party, vec![("ACME", Decimal::new(6000, 2)), ("GLOBEX", Decimal::new(5000, 2))]);
+        assert_eq!(control.unassigned, Decimal::new(1000, 2));
+        assert!(control.is_reconciled);
+
+        let statement = db.get_party_statement(MAIN, acme.id, None, None).await.unwrap().unwrap();
+        assert_eq!(statement.closing_balance, Decimal::new(6000, 2));
+        let running: Vec<Decimal> = statement.accounts[0].entries.iter().map(|entry| entry.running_balance).collect();
+        assert_eq!(running, vec![Decimal::new(10000, 2), Decimal::new(6000, 2)]);
+
+        // Vendors stay off receivables and parties off ordinary accounts
+        let mut wrong_type = simple_transaction(receivable, sales, Decimal::new(100, 2), date(2024, 2, 2));
+        wrong_type.journal_entries[0].party_id = Some(supplier.id);
+        assert!(db.create_transaction(MAIN, wrong_type).await.is_err());
+        let mut not_control = simple_transaction(cash, sales, Decimal::new(100, 2), date(2024, 2, 2));
+        not_control.journal_entries[0].party_id = Some(acme.id);
+        assert!(db.create_transaction(MAIN, not_control).await.is_err());
+
+        // Editing a draft moves its receivable line to the new customer and due date
+        let mut draft = simple_transaction(receivable, sales, Decimal::new(700, 2), date(2024, 2, 3));
+        draft.status = Some(TransactionStatus::Draft);
+        draft.journal_entries[0].party_id = Some(acme.id);
+        draft.journal_entries[0].due_date = Some(date(2024, 3, 1));
+        let draft = db.create_transaction(MAIN, draft).await.unwrap();
+        let mut lines: Vec<CreateJournalEntryRequest> = draft.journal_entries.iter().map(|entry| entry.to_request()).collect();
+        lines[0].party_id = Some(globex.id);
+        lines[0].due_date = Some(date(2024, 4, 1));
+        let edit = UpdateTransactionRequest { description: None, reference: None, transaction_date: None, journal_entries: Some(lines) };
+        let edited = db.update_transaction(MAIN, draft.transaction.id, edit).await.unwrap().unwrap();
+        assert_eq!(edited.journal_entries[0].party_id, Some(globex.id));
+        assert_eq!(edited.journal_entries[0].due_date, Some(date(2024, 4, 1)));
+        db.post_transaction(MAIN, draft.transaction.id).await.unwrap();
+        let balances = db.get_control_account_balances(MAIN, None).await.unwrap();
+        let control = balances.iter().find(|control| control.account_id == receivable).unwrap();
+        let globex_balance = control.parties.iter().find(|party| party.party_id == globex.id).unwrap().balance;
+        assert_eq!(globex_balance, Decimal::new(5700, 2));
+        assert!(control.is_reconciled);
+        let lines = db.get_control_lines(MAIN, PartyType::Customer, date(2024, 2, 28)).await.unwrap();
+        let line = lines.iter().find(|line| line.transaction_id == draft.transaction.id).unwrap();
+        assert_eq!(line.due_date, Some(date(2024, 4, 1)));
+
+        let receivable = db.get_account(MAIN, receivable).await.unwrap().unwrap();
+        assert!(receivable.party_restriction(None).is_some());
+        assert!(receivable.party_restriction(Some(&supplier)).is_some());
+        assert!(receivable.party_restriction(Some(&acme)).is_none());
+
+        // Only accounts without lines can change their control party type; null clears it
+        let clear: UpdateAccountRequest = serde_json::from_str(r#"{"name": null, "parent_id": null, "control_party_type": null}"#).unwrap();
+        assert_eq!(clear.control_party_type, Some(None));
+        let rename: UpdateAccountRequest = serde_json::from_str(r#"{"name": "Debtors", "parent_id": null}"#).unwrap();
+        assert_eq!(rename.control_party_type, None);
+        let state = AppState::new(db);
+        let err = update_account(Extension(state.clone()), Path((MAIN, receivable.id)), Json(clear)).await.unwrap_err();
+        assert_eq!(err.status, StatusCode::CONFLICT);
+        let to_vendor = || UpdateAccountRequest {
+            name: None, parent_id: None, is_header: None, cash_flow_category: None,
+            control_party_type: Some(Some(PartyType::Vendor)),
+        };
+        assert!(state.database.update_account(MAIN, receivable.id, to_vendor()).await.is_err());
+        let Json(renamed) = update_account(Extension(state.clone()), Path((MAIN, receivable.id)), Json(rename)).await.unwrap();
+        assert_eq!(renamed.control_party_type, Some(PartyType::Customer));
+
+        let deposits = state.database.create_account(MAIN, CreateAccountRequest {
+            code: "2150".to_string(),
+            name: "Customer Deposits".to_string(),
+            account_type: AccountType::Liability,
+            parent_id: None,
+            currency: None,
+            is_header: false,
+            cash_flow_category: None,
+            control_party_type: None,
+        }).await.unwrap();
+        let Json(deposits) = update_account(Extension(state.clone()), Path((MAIN, deposits.id)), Json(to_vendor())).await.unwrap();
+        assert_eq!(deposits.control_party_type, Some(PartyType::Vendor));
+        let clear: UpdateAccountRequest = serde_json::from_str(r#"{"name": null, "parent_id": null, "control_party_type": null}"#).unwrap();
+        let Json(deposits) = update_account(Extension(state.clone()), Path((MAIN, deposits.id)), Json(clear)).await.unwrap();
+        assert_eq!(deposits.control_party_type, None);
+    }
+
+    #[tokio::test]
+    async fn test_aging_report() {
+        let db = Database::new(":memory:").await.unwrap();
+        let cash = account_id(&db, "1110").await;
+        let receivable = account_id(&db, "1120").await;
+        let sales = account_id(&db, "4100").await;
+        let party = |code: &str| CreatePartyRequest { code: code.to_string(), name: code.to_string(), party_type: PartyType::Customer };
+        let acme = db.create_party(MAIN, party("ACME")).await.unwrap();
+        let globex = db.create_party(MAIN, party("GLOBEX")).await.unwrap();
+
+        db.create_transaction(MAIN, simple_transaction(receivable, sales, Decimal::new(1000, 2), date(2024, 1, 2))).await.unwrap();
+        for (debit, credit, party_line, party_id, amount, transaction_date, due_date) in [
+            (receivable, sales, 0, acme.id, 10000, date(2024, 1, 10), Some(date(2024, 1, 31))),
+            (receivable, sales, 0, acme.id, 5000, date(2024, 3, 1), Some(date(2024, 3, 31))),
+            (cash, receivable, 1, acme.id, 12000, date(2024, 3, 5), None),
+            // A payment ahead of its invoice stays open as a credit until the invoice arrives
+            (cash, receivable, 1, globex.id, 2000, date(2024, 2, 1), None),
+            (receivable, sales, 0, globex.id, 3000, date(2024, 4, 1), Some(date(2024, 5, 1))),
+        ] {
+            let mut request = simple_transaction(debit, credit, Decimal::new(amount, 2), transaction_date);
+            request.journal_entries[party_line].party_id = Some(party_id);
+            request.journal_entries[party_line].due_date = due_date;
+            db.create_transaction(MAIN, request).await.unwrap();
+        }
+
+        let as_of = date(2024, 4, 15);
+        let lines = db.get_control_lines(MAIN, PartyType::Customer, as_of).await.unwrap();
+        let mut report = AgingReport::build(as_of, PartyType::Customer, AgingBasis::DueDate, lines.clone());
+        report.set_control_balance(balance_of(&db, "1120").await);
+        assert!(report.is_reconciled);
+        assert_eq!(report.totals.total, Decimal::new(5000, 2));
+        assert_eq!(report.totals.over_90, Decimal::new(1000, 2));
+        assert_eq!(report.totals.days_1_30, Decimal::new(3000, 2));
+        assert_eq!(report.totals.current, Decimal::new(1000, 2));
+
+        let parties: Vec<Option<&str>> = report.parties.iter().map(|party| party.party_code.as_deref()).collect();
+        assert_eq!(parties, vec![None, Some("ACME"), Some("GLOBEX")]);
+        let acme_items = &report.parties[1].items;
+        assert_eq!(acme_items.len(), 1);
+        assert_eq!(acme_items[0].amount, Decimal::new(3000, 2));
+        assert_eq!(acme_items[0].days_overdue, 15);
+
+        let report = AgingReport::build(as_of, PartyType::Customer, AgingBasis::DocumentDate, lines);
+        assert_eq!(report.parties[1].buckets.days_31_60, Decimal::new(3000, 2));
+        assert_eq!(report.totals.total, Decimal::new(5000, 2));
+
+        // Nothing posted after the report date counts
+        let lines = db.get_control_lines(MAIN, PartyType::Customer, date(2024, 2, 15)).await.unwrap();
+        let report = AgingReport::build(date(2024, 2, 15), PartyType::Customer, AgingBasis::DueDate, lines);
+        assert_eq!(report.totals.total, Decimal::new(9000, 2));
+    }
+
+    #[tokio::test]
+    async fn test_invoice_lifecycle() {
+        let db = Database::new(":memory:").await.unwrap();
+        let cash = account_id(&db, "1110").await;
+        let receivable = account_id(&db, "1120").await;
+        let sales = account_id(&db, "4100").await;
+        let services = account_id(&db, "4200").await;
+        let tax = account_id(&db, "2130").await;
+        let customer = db.create_party(MAIN, CreatePartyRequest {
+            code: "ACME".to_string(),
+            name: "Acme".to_string(),
+            party_type: PartyType::Customer,
+        }).await.unwrap();
+
+        let invoice_request = |invoice_number: Option<&str>| CreateInvoiceRequest {
+            invoice_number: invoice_number.map(str::to_string),
+            party_id: customer.id,
+            invoice_date: date(2024, 3, 1),
+            due_date: Some(date(2024, 3, 31)),
+            description: None,
+            receivable_account_id: Some(receivable),
+            tax_account_id: Some(tax),
+            lines: vec![
+                CreateInvoiceLineRequest {
+                    description: "Widgets".to_string(),
+                    quantity: Decimal::new(3, 0),
+                    unit_price: Decimal::new(3333, 2),
+                    account_id: Some(sales),
+                    tax_rate: Some(Decimal::new(8, 2)),
+                },
+                CreateInvoiceLineRequest {
+                    description: "Installation".to_string(),
+                    quantity: Decimal::new(15, 1),
+                    unit_price: Decimal::new(4000, 2),
+                    account_id: Some(services),
+                    tax_rate: None,
+                },
+            ],
+        };
+
+        let invoice = db.create_invoice(MAIN, invoice_request(None)).await.unwrap();
+        assert_eq!(invoice.invoice.invoice_number, "INV-00001");
+        assert_eq!(invoice.invoice.subtotal, Decimal::new(15999, 2));
+        assert_eq!(invoice.invoice.tax_amount, Decimal::new(800, 2));
+        assert_eq!(invoice.invoice.total, Decimal::new(16799, 2));
+        assert_eq!(invoice.status, InvoiceStatus::Open);
+        assert_eq!(invoice.lines[1].quantity, Decimal::new(15, 1));
+        assert_eq!(balance_of(&db, "1120").await, Decimal::new(16799, 2));
+        assert_eq!(balance_of(&db, "4100").await, Decimal::new(-9999, 2));
+        assert_eq!(balance_of(&db, "2130").await, Decimal::new(-800, 2));
+
+        let posting = db.get_transaction(MAIN, invoice.invoice.transaction_id).await.unwrap().unwrap();
+        assert_eq!(posting.transaction.reference.as_deref(), Some("INV-00001"));
+        assert_eq!(posting.journal_entries[0].party_id, Some(customer.id));
+        assert_eq!(posting.journal_entries[0].due_date, Some(date(2024, 3, 31)));
+
+        // The sequence steps over numbers already given by hand
+        db.create_invoice(MAIN, invoice_request(Some("INV-00002"))).await.unwrap();
+        let third = db.create_invoice(MAIN, invoice_request(None)).await.unwrap();
+        assert_eq!(third.invoice.invoice_number, "INV-00003");
+        assert!(db.create_invoice(MAIN, invoice_request(Some("INV-00003"))).await.is_err());
+
+        // Amounts too large to compute are rejected rather than overflowing
+        let mut huge = invoice_request(None);
+        huge.lines[0].quantity = Decimal::MAX;
+        assert!(huge.validate().is_err());
+        huge.lines[0].unit_price = Decimal::ONE;
+        huge.lines[0].tax_rate = None;
+        assert!(huge.validate().is_err());
+        let mut huge = invoice_request(None);
+        huge.lines[0].unit_price = Decimal::new(100_000_000_000_000_000, 0);
+        assert!(db.create_invoice(MAIN, huge).await.is_err());
+
+        let payment = |amount| InvoicePaymentRequest {
+            payment_date: date(2024, 3, 15),
+            amount,
+            account_id: Some(cash),
+            reference: None,
+        };
+        let id = invoice.invoice.id;
+        let invoice = db.record_invoice_payment(MAIN, id, payment(Decimal::new(10000, 2))).await.unwrap().unwrap();
+        assert_eq!(invoice.status, InvoiceStatus::PartiallyPaid);
+        assert_eq!(invoice.balance_due, Decimal::new(6799, 2));
+        assert!(db.record_invoice_payment(MAIN, id, payment(Decimal::new(6800, 2))).await.is_err());
+
+        let invoice = db.record_invoice_payment(MAIN, id, payment(Decimal::new(6799, 2))).await.unwrap().unwrap();
+        assert_eq!(invoice.status, InvoiceStatus::Paid);
+        assert_eq!(invoice.payments.len(), 2);
+
+        // A voided payment no longer counts
+        db.void_transaction(MAIN, invoice.payments[1].transaction_id).await.unwrap();
+        let invoice = db.get_invoice(MAIN, id).await.unwrap().unwrap();
+        assert_eq!(invoice.status, InvoiceStatus::PartiallyPaid);
+        assert_eq!(invoice.amount_paid, Decimal::new(10000, 2));
+
+        let open = db.list_invoices(MAIN, Some(customer.id), Some(InvoiceStatus::Open)).await.unwrap();
+        let numbers: Vec<&str> = open.iter().map(|invoice| invoice.invoice.invoice_number.as_str()).collect();
+        assert_eq!(numbers, vec!["INV-00002", "INV-00003"]);
+    }
+
+    #[tokio::test]
+    async fn test_bills_and_vendor_payments() {
+        use axum::{extract::{Extension, Path}, http::{HeaderMap, StatusCode}, Json};
+        use handlers::bills::create_bill;
+
+        let db = Database::new(":memory:").await.unwrap();
+        let cash = account_id(&db, "1110").await;
+        let inventory = account_id(&db, "1130").await;
+        let payable = account_id(&db, "2110").await;
+        let rent = account_id(&db, "5210").await;
+        let party = |code: &str| CreatePartyRequest { code: code.to_string(), name: code.to_string(), party_type: PartyType::Vendor };
+        let supplier = db.create_party(MAIN, party("SUPPLY")).await.unwrap();
+        let landlord = db.create_party(MAIN, party("LANDLORD")).await.unwrap();
+
+        let bill_request = |party_id, account_id, unit_price| CreateBillRequest {
+            bill_number: None,
+            vendor_reference: Some("V-1".to_string()),
+            party_id,
+            bill_date: date(2024, 3, 1),
+            due_date: Some(date(2024, 3, 31)),
+            description: None,
+            payable_account_id: Some(payable),
+            lines: vec![CreateBillLineRequest {
+                description: "Goods".to_string(),
+                quantity: Decimal::new(2, 0),
+                unit_price: Decimal::new(unit_price, 2),
+                account_id,
+            }],
+        };
+        let first = db.create_bill(MAIN, bill_request(supplier.id, rent, 5000)).await.unwrap();
+        let second = db.create_bill(MAIN, bill_request(supplier.id, inventory, 2500)).await.unwrap();
+        let other = db.create_bill(MAIN, bill_request(landlord.id, rent, 1000)).await.unwrap();
+        assert_eq!(first.bill.bill_number, "BILL-00001");
+        assert_eq!(first.bill.total, Decimal::new(10000, 2));
+        assert_eq!(balance_of(&db, "1130").await, Decimal::new(5000, 2));
+        assert_eq!(balance_of(&db, "2110").await, Decimal::new(-17000, 2));
+
+        let allocation = |bill_id, amount| BillAllocationRequest { bill_id, amount: Decimal::new(amount, 2) };
+        let payment_request = |amount, allocations| VendorPaymentRequest {
+            party_id: supplier.id,
+            payment_date: date(2024, 3, 10),
+            amount: Decimal::new(amount, 2),
+            account_id: Some(cash),
+            payable_account_id: Some(payable),
+            reference: None,
+            allocations,
+        };
+
+        // Overpaying leaves credit with the vendor
+        let payment = db.create_vendor_payment(MAIN, payment_request(18000, vec![
+            allocation(first.bill.id, 10000),
+            allocation(second.bill.id, 2000),
+        ])).await.unwrap();
+        assert_eq!(payment.allocated, Decimal::new(12000, 2));
+        assert_eq!(payment.unapplied, Decimal::new(6000, 2));
+
+        let first = db.get_bill(MAIN, first.bill.id).await.unwrap().unwrap();
+        assert_eq!(first.status, InvoiceStatus::Paid);
+        let second = db.get_bill(MAIN, second.bill.id).await.unwrap().unwrap();
+        assert_eq!(second.status, InvoiceStatus::PartiallyPaid);
+        assert_eq!(second.balance_due, Decimal::new(3000, 2));
+        assert_eq!(second.allocations[0].payment_id, payment.payment.id);
+
+        // Allocations are checked against the bill, the vendor and the credit left
+        let id = payment.payment.id;
+        assert!(db.allocate_vendor_payment(MAIN, id, &[allocation(second.bill.id, 3001)]).await.is_err());
+        assert!(db.allocate_vendor_payment(MAIN, id, &[allocation(other.bill.id, 1000)]).await.is_err());
+        assert!(db.create_vendor_payment(MAIN, payment_request(1000, vec![allocation(first.bill.id, 1000)])).await.is_err());
+
+        let payment = db.allocate_vendor_payment(MAIN, id, &[allocation(second.bill.id, 3000)]).await.unwrap().unwrap();
+        assert_eq!(payment.unapplied, Decimal::new(3000, 2));
+        let second = db.get_bill(MAIN, second.bill.id).await.unwrap().unwrap();
+        assert_eq!(second.status, InvoiceStatus::Paid);
+        assert_eq!(second.allocations.len(), 2);
+
+        // Payable: the landlord's open bill less the supplier's remaining credit
+        assert_eq!(balance_of(&db, "2110").await, Decimal::new(1000, 2));
+
+        // Voiding a bill hands what was allocated to it back to the payment as credit
+        db.void_transaction(MAIN, second.bill.transaction_id).await.unwrap();
+        let payment = db.get_vendor_payment(MAIN, id).await.unwrap().unwrap();
+        assert_eq!(payment.allocated, Decimal::new(10000, 2));
+        assert_eq!(payment.unapplied, Decimal::new(8000, 2));
+        let third = db.create_bill(MAIN, bill_request(supplier.id, rent, 4000)).await.unwrap();
+        let payment = db.allocate_vendor_payment(MAIN, id, &[allocation(third.bill.id, 8000)]).await.unwrap().unwrap();
+        assert_eq!(payment.unapplied, Decimal::ZERO);
+
+        // Voiding the payment reopens its bills and drops the credit
+        db.void_transaction(MAIN, payment.payment.transaction_id).await.unwrap();
+        let payment = db.get_vendor_payment(MAIN, id).await.unwrap().unwrap();
+        assert!(payment.is_voided);
+        assert_eq!(payment.unapplied, Decimal::ZERO);
+        let open = db.list_bills(MAIN, Some(supplier.id), Some(InvoiceStatus::Open)).await.unwrap();
+        assert_eq!(open.len(), 2);
+
+        // Bills cannot debit cash against the payable
+        let state = AppState::new(db);
+        let err = create_bill(Extension(state.clone()), Path(MAIN), HeaderMap::new(), Json(bill_request(supplier.id, cash, 1000))).await.unwrap_err();
+        assert_eq!(err.status, StatusCode::BAD_REQUEST);
+
+        // Amounts too large to compute are rejected rather than overflowing
+        let mut huge = bill_request(supplier.id, rent, 1000);
+        huge.lines[0].quantity = Decimal::MAX;
+        let err = create_bill(Extension(state.clone()), Path(MAIN), HeaderMap::new(), Json(huge)).await.unwrap_err();
+        assert_eq!(err.status, StatusCode::BAD_REQUEST);
+        let mut huge = bill_request(supplier.id, rent, 100);
+        huge.lines[0].quantity = Decimal::MAX;
+        huge.lines.push(bill_request(supplier.id, rent, 100).lines.remove(0));
+        let err = create_bill(Extension(state), Path(MAIN), HeaderMap::new(), Json(huge)).await.unwrap_err();
+        assert_eq!(err.status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_clearing_groups() {
+        let db = Database::new(":memory:").await.unwrap();
+        let cash = account_id(&db, "1110").await;
+        let receivable = account_id(&db, "1120").await;
+        let sales = account_id(&db, "4100").await;
+        let party = |code: &str| CreatePartyRequest { code: code.to_string(), name: code.to_string(), party_type: PartyType::Customer };
+        let acme = db.create_party(MAIN, party("ACME")).await.unwrap();
+        let globex = db.create_party(MAIN, party("GLOBEX")).await.unwrap();
+
+        // Receivable line id of each posting
+        let mut lines = Vec::new();
+        for (debit, credit, party_line, party_id, amount, reference) in [
+            (receivable, sales, 0, acme.id, 10000, "INV-1"),
+            (receivable, sales, 0, acme.id, 8000, "INV-2"),
+            (cash, receivable, 1, acme.id, 10000, "INV-1"),
+            (cash, receivable, 1, acme.id, 5000, "R-9"),
+            (cash, receivable, 1, acme.id, 3000, "INV-2"),
+            (receivable, sales, 0, acme.id, 4000, "X"),
+            (cash, receivable, 1, acme.id, 2500, "Y"),
+            (cash, receivable, 1, acme.id, 2500, "Z"),
+            (cash, receivable, 1, globex.id, 1000, "INV-1"),
+        ] {
+            let mut request = simple_transaction(debit, credit, Decimal::new(amount, 2), date(2024, 3, 1));
+            request.reference = Some(reference.to_string());
+            request.journal_entries[party_line].party_id = Some(party_id);
+            let transaction = db.create_transaction(MAIN, request).await.unwrap();
+            lines.push((transaction.transaction.id, transaction.journal_entries[party_line].id));
+        }
+        let line = |index: usize, amount: Option<i64>| ClearingLineRequest {
+            journal_entry_id: lines[index].1,
+            amount: amount.map(|amount| Decimal::new(amount, 2)),
+        };
+        let group = |lines| CreateClearingGroupRequest { account_id: receivable, lines };
+
+        // Part of INV-2 against an unrelated receipt
+        let partial = db.create_clearing_group(MAIN, &group(vec![line(1, Some(5000)), line(3, None)])).await.unwrap();
+        assert!(partial.is_active);
+        assert_eq!(partial.lines[0].amount, Decimal::new(5000, 2));
+        assert_eq!(partial.lines[1].amount, Decimal::new(-5000, 2));
+
+        // One debit against two credits, the second only in part
+        db.create_clearing_group(MAIN, &group(vec![line(5, None), line(6, None), line(7, Some(1500))])).await.unwrap();
+
+        assert!(db.create_clearing_group(MAIN, &group(vec![line(0, None), line(7, None)])).await.is_err());
+        assert!(db.create_clearing_group(MAIN, &group(vec![line(0, Some(1000)), line(7, Some(1001))])).await.is_err());
+        assert!(db.create_clearing_group(MAIN, &group(vec![line(0, Some(1000)), line(8, None)])).await.is_err());
+
+        // INV-1 matches its receipt in full and INV-2 its remaining 30.00; GLOBEX has no pair
+        let matched = db.auto_match(MAIN, &AutoMatchRequest { account_id: receivable, party_id: None }).await.unwrap();
+        assert_eq!(matched.len(), 2);
+        assert!(matched.iter().all(|group| group.group.is_automatic && group.group.party_id == Some(acme.id)));
+
+        let open = db.get_uncleared_lines(MAIN, receivable, None).await.unwrap();
+        let residuals: Vec<(i64, Decimal)> = open.iter().map(|line| (line.journal_entry_id, line.residual)).collect();
+        assert_eq!(residuals, vec![(lines[7].1, Decimal::new(-1000, 2)), (lines[8].1, Decimal::new(-1000, 2))]);
+
+        // Voiding a matched receipt reopens both sides of its group
+        db.void_transaction(MAIN, lines[2].0).await.unwrap();
+        let open = db.get_uncleared_lines(MAIN, receivable, Some(acme.id)).await.unwrap();
+        assert_eq!(open[0].journal_entry_id, lines[0].1);
+        assert_eq!(open[0].residual, Decimal::new(10000, 2));
+        let groups = db.list_clearing_groups(MAIN, Some(receivable)).await.unwrap();
+        assert_eq!(groups.iter().filter(|group| !group.is_active).count(), 1);
+    }
+
+    #[test]
+    fn test_fiscal_calendar() {
+        let calendar = FiscalCalendar::parse("07-01").unwrap();
+        assert_eq!(calendar.year_range(2025), Some((date(2024, 7, 1), date(2025, 6, 30))));
+        assert_eq!(calendar.fiscal_year_of(date(2024, 7, 1)), 2025);
+        assert_eq!(calendar.fiscal_year_of(date(2024, 6, 30)), 2024);
+
+        let calendar = FiscalCalendar::default();
+        assert_eq!(calendar.year_range(2024), Some((date(2024, 1, 1), date(2024, 12, 31))));
+        // Years beyond the supported dates are refused rather than panicking
+        assert_eq!(calendar.year_range(999_999), None);
+        assert_eq!(calendar.year_range(i32::MAX), None);
+        assert_eq!(calendar.fiscal_year_of(NaiveDate::MAX), chrono::Datelike::year(&NaiveDate::MAX));
+        assert_eq!(calendar.fiscal_year_of(date(2024, 12, 31)), 2024);
+
+        // July fiscal years: Q2 of FY2025 starts in October
+        let calendar = FiscalCalendar::parse("07-01").unwrap();
+        assert_eq!(PeriodPreset::QuarterToDate.resolve(&calendar, date(2024, 11, 20)), Some((date(2024, 10, 1), date(2024, 11, 20))));
+        assert_eq!(PeriodPreset::YearToDate.resolve(&calendar, date(2024, 11, 20)).unwrap().0, date(2024, 7, 1));
+        assert_eq!(PeriodPreset::MonthToDate.resolve(&calendar, date(2024, 11, 20)).unwrap().0, date(2024, 11, 1));
+        let calendar = FiscalCalendar::parse("04-15").unwrap();
+        assert_eq!(PeriodPreset::MonthToDate.resolve(&calendar, date(2024, 4, 10)).unwrap().0, date(2024, 3, 15));
+
+        assert!(FiscalCalendar::parse("02-30").is_err());
+        assert!(FiscalCalendar::parse("July").is_err());
+    }
+
     #[tokio::test]
     async fn test_account_normal_balance() {
         let asset_account = Account {
             id: 1,
+            company_id: MAIN,
             code: "1110".to_string(),
             name: "Cash".to_string(),
             account_type: AccountType::Asset,
             parent_id: None,
+            currency: None,
             balance: Decimal::new(10000, 2), // $100.00 debit balance
+            is_header: false,
+            cash_flow_category: None,
+            control_party_type: None,
+            is_active: true,
+            archived_at: None,
             created_at: chrono::Utc::now(),
             updated_at: chrono::Utc::now(),
         };
@@ -104,11 +1586,18 @@ mod tests {
         
         let liability_account = Account {
             id: 2,
+            company_id: MAIN,
             code: "2110".to_string(),
             name: "Accounts Payable".to_string(),
             account_type: AccountType::Liability,
             parent_id: None,
+            currency: None,
             balance: Decimal::new(-10000, 2), // $100.00 credit balance
+            is_header: false,
+            cash_flow_category: None,
+            control_party_type: None,
+            is_active: true,
+            archived_at: None,
             created_at: chrono::Utc::now(),
             updated_at: chrono::Utc::now(),
         };
@@ -123,22 +1612,36 @@ mod tests {
         
         let cash_account = Account {
             id: 1,
+            company_id: MAIN,
             code: "1110".to_string(),
             name: "Cash".to_string(),
             account_type: AccountType::Asset,
             parent_id: None,
+            currency: None,
             balance: Decimal::new(50000, 2), // $500.00
+            is_header: false,
+            cash_flow_category: None,
+            control_party_type: None,
+            is_active: true,
+            archived_at: None,
             created_at: chrono::Utc::now(),
             updated_at: chrono::Utc::now(),
         };
         
         let revenue_account = Account {
             id: 2,
+            company_id: MAIN,
             code: "4100".to_string(),
             name: "Sales Revenue".to_string(),
             account_type: AccountType::Revenue,
             parent_id: None,
+            currency: None,
             balance: Decimal::new(-50000, 2), // $500.00 credit
+            is_header: false,
+            cash_flow_category: None,
+            control_party_type: None,
+            is_active: true,
+            archived_at: None,
             created_at: chrono::Utc::now(),
             updated_at: chrono::Utc::now(),
         };
@@ -162,6 +1665,7 @@ mod tests {
             account_type: AccountType::Asset,
             debit_balance: Decimal::new(10000, 2),
             credit_balance: Decimal::ZERO,
+            depth: None,
         };
         
         trial_balance.add_entry(entry);
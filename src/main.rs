@@ -1,26 +1,16 @@
-mod models;
-mod database;
-mod handlers;
-
 use axum::{
     routing::{get, post, put, delete},
     Router,
     extract::Extension,
+    middleware,
 };
 use tower::ServiceBuilder;
 use tower_http::{
     services::ServeDir,
     cors::CorsLayer,
 };
-use std::sync::Arc;
-use tracing_subscriber;
-
-use database::Database;
 
-#[derive(Clone)]
-pub struct AppState {
-    pub database: Arc<Database>,
-}
+use financial_accounting::{handlers, models, AppState, Database, FiscalCalendar};
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
@@ -35,9 +25,110 @@ async fn main() -> anyhow::Result<()> {
         .unwrap_or_else(|_| "sqlite:accounting.db".to_string());
     
     let db = Database::new(&database_url).await?;
-    let app_state = AppState {
-        database: Arc::new(db),
+    let fiscal_calendar = match std::env::var("FISCAL_YEAR_START") {
+        Ok(value) => FiscalCalendar::parse(&value)?,
+        Err(_) => FiscalCalendar::default(),
     };
+    let functional_currency = std::env::var("FUNCTIONAL_CURRENCY")
+        .unwrap_or_else(|_| models::DEFAULT_FUNCTIONAL_CURRENCY.to_string());
+    models::validate_currency_code(&functional_currency)?;
+    let app_state = AppState::new(db)
+        .with_admin_token(std::env::var("ADMIN_TOKEN").ok())
+        .with_fiscal_calendar(fiscal_calendar)
+        .with_functional_currency(&functional_currency);
+
+    // API routes for one company's books, all under /api/companies/:cid
+    let company_routes = Router::new()
+        // Accounts
+        .route("/accounts", get(handlers::accounts::list_accounts))
+        .route("/accounts", post(handlers::accounts::create_account))
+        .route("/accounts/tree", get(handlers::accounts::account_tree))
+        .route("/accounts/:id", get(handlers::accounts::get_account))
+        .route("/accounts/:id", put(handlers::accounts::update_account))
+        .route("/accounts/:id", delete(handlers::accounts::delete_account))
+        .route("/accounts/:id/ledger", get(handlers::accounts::account_ledger))
+        .route("/accounts/:id/open-items", get(handlers::clearing::open_items))
+        .route("/accounts/:id/move", post(handlers::accounts::move_account))
+        .route("/accounts/:id/archive", post(handlers::accounts::archive_account))
+        .route("/accounts/:id/restore", post(handlers::accounts::restore_account))
+        
+        // Customers and vendors
+        .route("/parties", get(handlers::parties::list_parties))
+        .route("/parties", post(handlers::parties::create_party))
+        .route("/parties/balances", get(handlers::parties::party_balances))
+        .route("/parties/:id", get(handlers::parties::get_party))
+        .route("/parties/:id", put(handlers::parties::update_party))
+        .route("/parties/:id/statement", get(handlers::parties::party_statement))
+        
+        // Sales invoices
+        .route("/invoices", get(handlers::invoices::list_invoices))
+        .route("/invoices", post(handlers::invoices::create_invoice))
+        .route("/invoices/:id", get(handlers::invoices::get_invoice))
+        .route("/invoices/:id/payments", post(handlers::invoices::record_invoice_payment))
+        
+        // Vendor bills and payments
+        .route("/bills", get(handlers::bills::list_bills))
+        .route("/bills", post(handlers::bills::create_bill))
+        .route("/bills/:id", get(handlers::bills::get_bill))
+        .route("/vendor-payments", get(handlers::bills::list_vendor_payments))
+        .route("/vendor-payments", post(handlers::bills::create_vendor_payment))
+        .route("/vendor-payments/:id", get(handlers::bills::get_vendor_payment))
+        .route("/vendor-payments/:id/allocations", post(handlers::bills::allocate_vendor_payment))
+        
+        // Open-item matching
+        .route("/clearing-groups", get(handlers::clearing::list_clearing_groups))
+        .route("/clearing-groups", post(handlers::clearing::create_clearing_group))
+        .route("/clearing-groups/auto-match", post(handlers::clearing::auto_match))
+        .route("/clearing-groups/:id", get(handlers::clearing::get_clearing_group))
+        
+        // Transactions
+        .route("/transactions", get(handlers::transactions::list_transactions))
+        .route("/transactions", post(handlers::transactions::create_transaction))
+        .route("/transactions/:id", get(handlers::transactions::get_transaction))
+        .route("/transactions/:id", put(handlers::transactions::update_transaction))
+        .route("/transactions/:id/post", post(handlers::transactions::post_transaction))
+        .route("/transactions/:id/void", post(handlers::transactions::void_transaction))
+        .route("/transactions/:id/reverse", post(handlers::transactions::reverse_transaction))
+        
+        // Accounting periods
+        .route("/periods", get(handlers::periods::list_periods))
+        .route("/periods", post(handlers::periods::create_period))
+        .route("/periods/:id", get(handlers::periods::get_period))
+        .route("/periods/:id/status", put(handlers::periods::update_period_status))
+        
+        // Year-end closing
+        .route("/year-end-closes", get(handlers::closing::list_year_end_closes))
+        .route("/year-end-closes", post(handlers::closing::close_year))
+        .route("/year-end-closes/preview", post(handlers::closing::preview_year_end_close))
+        .route("/year-end-closes/:fiscal_year/reopen", post(handlers::closing::reopen_year))
+        
+        // Exchange rates
+        .route("/exchange-rates", get(handlers::currency::list_exchange_rates))
+        .route("/exchange-rates", post(handlers::currency::create_exchange_rate))
+        .route("/exchange-rates/:currency", get(handlers::currency::get_exchange_rate))
+        
+        // FX revaluation
+        .route("/fx-revaluations", get(handlers::revaluation::list_fx_revaluations))
+        .route("/fx-revaluations", post(handlers::revaluation::run_fx_revaluation))
+        .route("/fx-revaluations/preview", post(handlers::revaluation::preview_fx_revaluation))
+        .route("/fx-revaluations/:id", get(handlers::revaluation::get_fx_revaluation))
+        
+        // Reports
+        .route("/reports/summary", get(handlers::reports::account_summary))
+        .route("/reports/trial-balance", get(handlers::reports::trial_balance))
+        .route("/reports/general-ledger", get(handlers::reports::general_ledger))
+        .route("/reports/aged-receivables", get(handlers::reports::aged_receivables))
+        .route("/reports/aged-payables", get(handlers::reports::aged_payables))
+        .route("/reports/balance-sheet", get(handlers::reports::balance_sheet))
+        .route("/reports/changes-in-equity", get(handlers::reports::changes_in_equity))
+        .route("/reports/income-statement", get(handlers::reports::income_statement))
+        .route("/reports/cash-flow", get(handlers::reports::cash_flow_statement))
+        .route("/reports/balance-sheet/comparative", get(handlers::reports::comparative_balance_sheet))
+        .route("/reports/income-statement/comparative", get(handlers::reports::comparative_income_statement))
+        
+        // Transaction validation
+        .route("/transactions/validate", post(handlers::transactions::validate_transaction))
+        .route_layer(middleware::from_fn(handlers::companies::require_company));
 
     // Build our application with routes
     let app = Router::new()
@@ -48,26 +139,11 @@ async fn main() -> anyhow::Result<()> {
         .route("/reports", get(handlers::web::reports_page))
         .route("/reports/trial-balance", get(handlers::web::trial_balance_page))
         
-        // API routes for accounts
-        .route("/api/accounts", get(handlers::accounts::list_accounts))
-        .route("/api/accounts", post(handlers::accounts::create_account))
-        .route("/api/accounts/:id", get(handlers::accounts::get_account))
-        .route("/api/accounts/:id", put(handlers::accounts::update_account))
-        .route("/api/accounts/:id", delete(handlers::accounts::delete_account))
-        
-        // API routes for transactions
-        .route("/api/transactions", get(handlers::transactions::list_transactions))
-        .route("/api/transactions", post(handlers::transactions::create_transaction))
-        .route("/api/transactions/:id", get(handlers::transactions::get_transaction))
-        
-        // API routes for reports
-        .route("/api/reports/summary", get(handlers::reports::account_summary))
-        .route("/api/reports/trial-balance", get(handlers::reports::trial_balance))
-        .route("/api/reports/balance-sheet", get(handlers::reports::balance_sheet))
-        .route("/api/reports/income-statement", get(handlers::reports::income_statement))
-        
-        // API route for transaction validation
-        .route("/api/transactions/validate", post(handlers::transactions::validate_transaction))
+        // API routes for companies
+        .route("/api/companies", get(handlers::companies::list_companies))
+        .route("/api/companies", post(handlers::companies::create_company))
+        .route("/api/companies/:cid", get(handlers::companies::get_company))
+        .nest("/api/companies/:cid", company_routes)
         
         // Serve static files
         .nest_service("/static", ServeDir::new("static"))
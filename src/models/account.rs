@@ -1,16 +1,33 @@
+use std::collections::{HashMap, HashSet};
+
 use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 
+use super::{Party, PartyType};
+
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Account {
     pub id: i64,
+    pub company_id: i64,
     pub code: String,
     pub name: String,
     pub account_type: AccountType,
     pub parent_id: Option<i64>,
+    /// Currency the account is kept in; `None` means the functional currency
+    pub currency: Option<String>,
     pub balance: Decimal,
+    /// Header accounts only group other accounts in the tree and take no journal lines
+    pub is_header: bool,
+    /// Section of the cash flow statement the account's changes go to; `None` falls back
+    /// to the account type's default
+    pub cash_flow_category: Option<CashFlowCategory>,
+    /// Control accounts such as receivables and payables take lines for parties of this type only
+    pub control_party_type: Option<PartyType>,
+    /// Archived accounts take no new postings but still appear in reports
+    pub is_active: bool,
+    pub archived_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -38,21 +55,144 @@ impl AccountType {
     }
 }
 
+/// How an account's balance changes appear on the statement of cash flows
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, sqlx::Type)]
+#[sqlx(type_name = "TEXT")]
+#[sqlx(rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum CashFlowCategory {
+    /// Cash and cash equivalents, whose change the statement explains
+    Cash,
+    Operating,
+    Investing,
+    Financing,
+}
+
+impl CashFlowCategory {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CashFlowCategory::Cash => "cash",
+            CashFlowCategory::Operating => "operating",
+            CashFlowCategory::Investing => "investing",
+            CashFlowCategory::Financing => "financing",
+        }
+    }
+}
+
+/// Check that an account of `account_type` may carry a cash flow category.
+/// Revenue and expenses reach the statement through net income, and only assets hold cash.
+pub fn validate_cash_flow_category(account_type: &AccountType, category: CashFlowCategory) -> anyhow::Result<()> {
+    match account_type {
+        AccountType::Revenue | AccountType::Expense => Err(anyhow::anyhow!(
+            "Revenue and expense accounts are part of net income and take no cash flow category"
+        )),
+        AccountType::Liability | AccountType::Equity if category == CashFlowCategory::Cash => {
+            Err(anyhow::anyhow!("Only asset accounts can be cash accounts"))
+        }
+        _ => Ok(()),
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CreateAccountRequest {
     pub code: String,
     pub name: String,
     pub account_type: AccountType,
     pub parent_id: Option<i64>,
+    #[serde(default)]
+    pub currency: Option<String>,
+    #[serde(default)]
+    pub is_header: bool,
+    #[serde(default)]
+    pub cash_flow_category: Option<CashFlowCategory>,
+    #[serde(default)]
+    pub control_party_type: Option<PartyType>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct UpdateAccountRequest {
     pub name: Option<String>,
     pub parent_id: Option<i64>,
+    /// Accounts that already have journal lines cannot become headers
+    #[serde(default)]
+    pub is_header: Option<bool>,
+    #[serde(default)]
+    pub cash_flow_category: Option<CashFlowCategory>,
+    /// `null` makes the account an ordinary one again; only accounts without journal lines
+    /// can change their control party type
+    #[serde(default, deserialize_with = "present_or_null")]
+    pub control_party_type: Option<Option<PartyType>>,
+}
+
+// Tell a field set to null (`Some(None)`) apart from a missing one (`None`)
+fn present_or_null<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+/// What still points at an account, deciding whether it can be hard-deleted
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct AccountReferences {
+    pub journal_entries: i64,
+    pub child_accounts: i64,
+}
+
+impl AccountReferences {
+    pub fn is_empty(&self) -> bool {
+        self.journal_entries == 0 && self.child_accounts == 0
+    }
 }
 
+/// Move an account, together with everything below it, under a new parent.
+/// A `null` parent makes it a top-level account.
 #[derive(Debug, Serialize, Deserialize)]
+pub struct MoveAccountRequest {
+    pub parent_id: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AccountMoveResult {
+    pub account: Account,
+    pub previous_parent_id: Option<i64>,
+    /// Every account below the moved one, which moved along with it
+    pub moved_descendants: Vec<Account>,
+}
+
+/// Check that an account of `account_type` may sit under `parent`.
+/// `parent_chain` holds the ids from `parent` up to the top of its tree, and `account_id` is
+/// `None` for an account that does not exist yet.
+pub fn validate_parent(
+    account_id: Option<i64>,
+    account_type: &AccountType,
+    parent: &Account,
+    parent_chain: &[i64],
+) -> anyhow::Result<()> {
+    if let Some(account_id) = account_id {
+        if parent.id == account_id {
+            return Err(anyhow::anyhow!("Account cannot be its own parent"));
+        }
+        if parent_chain.contains(&account_id) {
+            return Err(anyhow::anyhow!(
+                "Account {} is below this account; moving it there would create a cycle",
+                parent.code
+            ));
+        }
+    }
+
+    if parent.account_type != *account_type {
+        return Err(anyhow::anyhow!(
+            "Parent account {} is of a different account type",
+            parent.code
+        ));
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AccountBalance {
     pub account_id: i64,
     pub account_code: String,
@@ -63,6 +203,22 @@ pub struct AccountBalance {
     pub credit_total: Decimal,
 }
 
+/// How an equity account moved over a period, in its normal (credit) direction
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EquityMovement {
+    pub account_id: i64,
+    pub account_code: String,
+    pub account_name: String,
+    pub opening_balance: Decimal,
+    /// Credits other than year-end closing entries, such as owner contributions
+    pub contributions: Decimal,
+    /// Debits other than year-end closing entries, such as dividends and owner draws
+    pub distributions: Decimal,
+    /// Net income closed into the account, less any closing entries reversed in the period
+    pub net_income_allocation: Decimal,
+    pub closing_balance: Decimal,
+}
+
 impl Account {
     /// Calculate the normal balance for this account type
     pub fn normal_balance(&self) -> Decimal {
@@ -73,6 +229,52 @@ impl Account {
         }
     }
 
+    /// Why the account cannot take new journal lines, if it cannot
+    pub fn posting_restriction(&self) -> Option<String> {
+        if self.is_header {
+            return Some(format!("Account {} is a header account and cannot be posted to", self.code));
+        }
+        if !self.is_active {
+            return Some(format!("Account {} is archived and takes no new postings", self.code));
+        }
+        None
+    }
+
+    /// Why a journal line with the given party cannot go to this account, if it cannot
+    pub fn party_restriction(&self, party: Option<&Party>) -> Option<String> {
+        match (self.control_party_type, party) {
+            (Some(party_type), None) => Some(format!(
+                "Account {} is a control account; its lines need a {}",
+                self.code,
+                party_type.as_str()
+            )),
+            (Some(party_type), Some(party)) if party.party_type != party_type => Some(format!(
+                "Party {} is a {}, but account {} takes {} lines",
+                party.code,
+                party.party_type.as_str(),
+                self.code,
+                party_type.as_str()
+            )),
+            (None, Some(party)) => Some(format!(
+                "Account {} is not a control account and takes no party, but the line names {}",
+                self.code, party.code
+            )),
+            _ => None,
+        }
+    }
+
+    /// The cash flow section the account's changes go to: its own category, or operating for
+    /// assets and liabilities and financing for equity. Revenue and expenses have none.
+    pub fn effective_cash_flow_category(&self) -> Option<CashFlowCategory> {
+        match self.account_type {
+            AccountType::Revenue | AccountType::Expense => None,
+            AccountType::Asset | AccountType::Liability => {
+                Some(self.cash_flow_category.unwrap_or(CashFlowCategory::Operating))
+            }
+            AccountType::Equity => Some(self.cash_flow_category.unwrap_or(CashFlowCategory::Financing)),
+        }
+    }
+
     /// Check if the account has a normal balance (positive for debit accounts, negative for credit accounts)
     pub fn has_normal_balance(&self) -> bool {
         if self.account_type.is_debit_normal() {
@@ -83,6 +285,96 @@ impl Account {
     }
 }
 
+/// An account with its descendants, carrying the balance rolled up from the whole subtree
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountTreeNode {
+    pub account: Account,
+    /// Levels below the top of the tree; top-level accounts are at depth 0
+    pub depth: usize,
+    /// The account's own balance plus the balances of all of its descendants
+    pub total_balance: Decimal,
+    pub children: Vec<AccountTreeNode>,
+}
+
+impl AccountTreeNode {
+    /// Build the account forest from a flat list, ordering siblings by code.
+    /// Accounts whose parent is not in the list become top-level accounts.
+    pub fn build_forest(accounts: Vec<Account>) -> Vec<AccountTreeNode> {
+        let ids: HashSet<i64> = accounts.iter().map(|account| account.id).collect();
+        let mut children: HashMap<Option<i64>, Vec<Account>> = HashMap::new();
+        for account in accounts {
+            let parent_id = account.parent_id.filter(|parent_id| ids.contains(parent_id));
+            children.entry(parent_id).or_default().push(account);
+        }
+        for siblings in children.values_mut() {
+            siblings.sort_by(|a, b| a.code.cmp(&b.code));
+        }
+
+        let roots = children.remove(&None).unwrap_or_default();
+        let mut forest: Vec<AccountTreeNode> = roots
+            .into_iter()
+            .map(|account| Self::build(account, 0, &mut children))
+            .collect();
+
+        // Whatever is left hangs off a parent cycle; show it at the top so no balance is lost
+        while let Some(&parent_id) = children.keys().next() {
+            for account in children.remove(&parent_id).unwrap_or_default() {
+                forest.push(Self::build(account, 0, &mut children));
+            }
+        }
+
+        forest
+    }
+
+    fn build(account: Account, depth: usize, children: &mut HashMap<Option<i64>, Vec<Account>>) -> Self {
+        // Taking the children out of the map visits each account at most once
+        let child_nodes: Vec<AccountTreeNode> = children
+            .remove(&Some(account.id))
+            .unwrap_or_default()
+            .into_iter()
+            .map(|child| Self::build(child, depth + 1, children))
+            .collect();
+
+        let total_balance = account.balance + child_nodes.iter().map(|child| child.total_balance).sum::<Decimal>();
+
+        Self {
+            account,
+            depth,
+            total_balance,
+            children: child_nodes,
+        }
+    }
+
+    /// The rolled-up balance expressed in the account type's normal direction
+    pub fn normal_total_balance(&self) -> Decimal {
+        if self.account.account_type.is_debit_normal() {
+            self.total_balance
+        } else {
+            -self.total_balance
+        }
+    }
+
+    /// Drop every level below `max_depth`; their balances stay in the ancestors' totals
+    pub fn prune(&mut self, max_depth: usize) {
+        if self.depth >= max_depth {
+            self.children.clear();
+        } else {
+            for child in &mut self.children {
+                child.prune(max_depth);
+            }
+        }
+    }
+
+    /// This node and its descendants in display order, parents before their children
+    pub fn flatten(&self) -> Vec<&AccountTreeNode> {
+        let mut nodes = vec![self];
+        for child in &self.children {
+            nodes.extend(child.flatten());
+        }
+        nodes
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct AccountSummary {
     pub total_assets: Decimal,
@@ -93,6 +385,12 @@ pub struct AccountSummary {
     pub net_income: Decimal,
 }
 
+impl Default for AccountSummary {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl AccountSummary {
     pub fn new() -> Self {
         Self {
@@ -105,8 +403,9 @@ impl AccountSummary {
         }
     }
 
+    /// Add the account's balance on its normal side; contra and overdrawn balances subtract
     pub fn add_account(&mut self, account: &Account) {
-        let balance = account.normal_balance().abs();
+        let balance = account.normal_balance();
         
         match account.account_type {
             AccountType::Asset => self.total_assets += balance,
@@ -0,0 +1,186 @@
+use std::collections::VecDeque;
+
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use super::PartyType;
+
+/// Which date an open item ages from
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum AgingBasis {
+    /// The line's due date, or its transaction date when it has none
+    #[default]
+    DueDate,
+    DocumentDate,
+}
+
+/// A posted line on a control account, as debits less credits
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ControlLine {
+    pub journal_entry_id: i64,
+    pub transaction_id: i64,
+    pub account_id: i64,
+    pub party_id: Option<i64>,
+    pub party_code: Option<String>,
+    pub party_name: Option<String>,
+    pub transaction_date: NaiveDate,
+    pub due_date: Option<NaiveDate>,
+    pub reference: Option<String>,
+    pub description: String,
+    pub amount: Decimal,
+}
+
+/// What is left of a line after later lines on the other side have been applied to it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenItem {
+    pub journal_entry_id: i64,
+    pub transaction_id: i64,
+    pub document_date: NaiveDate,
+    pub due_date: Option<NaiveDate>,
+    pub reference: Option<String>,
+    pub description: String,
+    /// Open amount in the control account's normal direction; negative for unapplied
+    /// payments and credits
+    pub amount: Decimal,
+    /// Days past the aging date at the report date; zero or less is current
+    pub days_overdue: i64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct AgingBuckets {
+    pub current: Decimal,
+    pub days_1_30: Decimal,
+    pub days_31_60: Decimal,
+    pub days_61_90: Decimal,
+    pub over_90: Decimal,
+    pub total: Decimal,
+}
+
+impl AgingBuckets {
+    pub fn add(&mut self, days_overdue: i64, amount: Decimal) {
+        let bucket = match days_overdue {
+            i64::MIN..=0 => &mut self.current,
+            1..=30 => &mut self.days_1_30,
+            31..=60 => &mut self.days_31_60,
+            61..=90 => &mut self.days_61_90,
+            _ => &mut self.over_90,
+        };
+        *bucket += amount;
+        self.total += amount;
+    }
+}
+
+/// Open items of one party; lines without a party are grouped under `None`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PartyAging {
+    pub party_id: Option<i64>,
+    pub party_code: Option<String>,
+    pub party_name: Option<String>,
+    pub buckets: AgingBuckets,
+    pub items: Vec<OpenItem>,
+}
+
+/// Aged receivables or payables at a date
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AgingReport {
+    pub as_of: NaiveDate,
+    pub party_type: PartyType,
+    pub basis: AgingBasis,
+    pub parties: Vec<PartyAging>,
+    pub totals: AgingBuckets,
+    /// Balance of the control accounts in the trial balance, in their normal direction
+    pub control_balance: Decimal,
+    /// Whether the aged total equals the control balance
+    pub is_reconciled: bool,
+}
+
+impl AgingReport {
+    /// Age the control lines of one party type. Each party's payments and credits settle its
+    /// oldest open items first; whatever they do not cover stays open as a negative item.
+    /// `lines` must be ordered by party, then date.
+    pub fn build(as_of: NaiveDate, party_type: PartyType, basis: AgingBasis, lines: Vec<ControlLine>) -> Self {
+        let mut report = Self {
+            as_of,
+            party_type,
+            basis,
+            parties: Vec::new(),
+            totals: AgingBuckets::default(),
+            control_balance: Decimal::ZERO,
+            is_reconciled: true,
+        };
+
+        // Receivables are debit balances, payables credit balances
+        let direction = match party_type {
+            PartyType::Customer => Decimal::ONE,
+            PartyType::Vendor => Decimal::NEGATIVE_ONE,
+        };
+
+        let mut lines = lines.into_iter().peekable();
+        while let Some(first) = lines.next() {
+            let mut party = PartyAging {
+                party_id: first.party_id,
+                party_code: first.party_code.clone(),
+                party_name: first.party_name.clone(),
+                buckets: AgingBuckets::default(),
+                items: Vec::new(),
+            };
+
+            let mut open: VecDeque<OpenItem> = VecDeque::new();
+            let mut next = Some(first);
+            while let Some(line) = next {
+                let mut remaining = line.amount * direction;
+
+                // Settle open items on the other side, oldest first
+                while !remaining.is_zero() {
+                    let Some(item) = open.front_mut() else { break };
+                    if item.amount.is_sign_positive() == remaining.is_sign_positive() {
+                        break;
+                    }
+                    let applied = if item.amount.abs() <= remaining.abs() { item.amount } else { -remaining };
+                    item.amount -= applied;
+                    remaining += applied;
+                    if item.amount.is_zero() {
+                        open.pop_front();
+                    }
+                }
+
+                if !remaining.is_zero() {
+                    let aging_date = match basis {
+                        AgingBasis::DueDate => line.due_date.unwrap_or(line.transaction_date),
+                        AgingBasis::DocumentDate => line.transaction_date,
+                    };
+                    open.push_back(OpenItem {
+                        journal_entry_id: line.journal_entry_id,
+                        transaction_id: line.transaction_id,
+                        document_date: line.transaction_date,
+                        due_date: line.due_date,
+                        reference: line.reference,
+                        description: line.description,
+                        amount: remaining,
+                        days_overdue: (as_of - aging_date).num_days(),
+                    });
+                }
+
+                next = lines.next_if(|line| line.party_id == party.party_id);
+            }
+
+            for item in open {
+                party.buckets.add(item.days_overdue, item.amount);
+                report.totals.add(item.days_overdue, item.amount);
+                party.items.push(item);
+            }
+            if !party.items.is_empty() {
+                report.parties.push(party);
+            }
+        }
+
+        report
+    }
+
+    pub fn set_control_balance(&mut self, control_balance: Decimal) {
+        self.control_balance = control_balance;
+        self.is_reconciled = self.totals.total == control_balance;
+    }
+}
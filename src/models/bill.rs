@@ -0,0 +1,372 @@
+use std::collections::HashSet;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::{Decimal, RoundingStrategy};
+use serde::{Deserialize, Serialize};
+
+use super::{CreateJournalEntryRequest, CreateTransactionRequest, InvoiceStatus, TransactionStatus, AMOUNT_SCALE};
+
+/// A vendor bill, posted to the ledger when it is entered
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Bill {
+    pub id: i64,
+    pub company_id: i64,
+    pub bill_number: String,
+    /// The vendor's own invoice number
+    pub vendor_reference: Option<String>,
+    pub party_id: i64,
+    pub bill_date: NaiveDate,
+    pub due_date: NaiveDate,
+    pub description: Option<String>,
+    pub payable_account_id: i64,
+    pub total: Decimal,
+    pub transaction_id: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BillLine {
+    pub id: i64,
+    pub bill_id: i64,
+    pub line_number: i64,
+    pub description: String,
+    pub quantity: Decimal,
+    pub unit_price: Decimal,
+    /// Expense or inventory account debited with the line amount
+    pub account_id: i64,
+    pub amount: Decimal,
+}
+
+/// The part of a vendor payment applied to a bill
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BillAllocation {
+    pub id: i64,
+    pub payment_id: i64,
+    pub bill_id: i64,
+    pub bill_number: String,
+    pub payment_date: NaiveDate,
+    /// The payment's transaction; the allocation only counts while it is posted
+    pub transaction_id: i64,
+    pub amount: Decimal,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A bill with its lines and every allocation made to it. Allocations from payments that are
+/// no longer posted are listed but do not count towards `amount_paid`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BillWithDetails {
+    pub bill: Bill,
+    pub lines: Vec<BillLine>,
+    pub allocations: Vec<BillAllocation>,
+    pub amount_paid: Decimal,
+    pub balance_due: Decimal,
+    pub status: InvoiceStatus,
+}
+
+impl BillWithDetails {
+    pub fn new(
+        bill: Bill,
+        bill_status: TransactionStatus,
+        lines: Vec<BillLine>,
+        allocations: Vec<(BillAllocation, TransactionStatus)>,
+    ) -> Self {
+        let amount_paid = allocations
+            .iter()
+            .filter(|(_, status)| status.affects_balances())
+            .map(|(allocation, _)| allocation.amount)
+            .sum();
+        let status = if bill_status == TransactionStatus::Voided {
+            InvoiceStatus::Voided
+        } else {
+            InvoiceStatus::from_amounts(bill.total, amount_paid)
+        };
+
+        Self {
+            balance_due: bill.total - amount_paid,
+            bill,
+            lines,
+            allocations: allocations.into_iter().map(|(allocation, _)| allocation).collect(),
+            amount_paid,
+            status,
+        }
+    }
+
+    /// Why part of a payment from `party_id` through `payable_account_id` cannot be applied
+    /// to this bill, if it cannot
+    pub fn allocation_rejection(&self, party_id: i64, payable_account_id: i64, amount: Decimal) -> Option<String> {
+        let number = &self.bill.bill_number;
+        if self.bill.party_id != party_id {
+            return Some(format!("Bill {} belongs to another vendor", number));
+        }
+        if self.bill.payable_account_id != payable_account_id {
+            return Some(format!("Bill {} is payable through another account", number));
+        }
+        if self.status == InvoiceStatus::Voided {
+            return Some(format!("Bill {} has been voided", number));
+        }
+        if amount > self.balance_due {
+            return Some(format!("Allocation of {} is more than the {} due on bill {}", amount, self.balance_due, number));
+        }
+        None
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateBillRequest {
+    /// Taken from the company's bill sequence when omitted
+    pub bill_number: Option<String>,
+    pub vendor_reference: Option<String>,
+    pub party_id: i64,
+    pub bill_date: NaiveDate,
+    /// Defaults to the bill date
+    pub due_date: Option<NaiveDate>,
+    pub description: Option<String>,
+    /// Defaults to 2110 Accounts Payable
+    pub payable_account_id: Option<i64>,
+    pub lines: Vec<CreateBillLineRequest>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateBillLineRequest {
+    pub description: String,
+    pub quantity: Decimal,
+    pub unit_price: Decimal,
+    /// Expense or inventory account to debit
+    pub account_id: i64,
+}
+
+impl CreateBillLineRequest {
+    /// Quantity times unit price, rounded half away from zero to cents. `validate` checks the
+    /// product does not overflow.
+    pub fn amount(&self) -> Decimal {
+        (self.quantity * self.unit_price).round_dp_with_strategy(AMOUNT_SCALE, RoundingStrategy::MidpointAwayFromZero)
+    }
+
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.description.trim().is_empty() {
+            return Err(anyhow::anyhow!("Line description cannot be empty"));
+        }
+        if self.quantity <= Decimal::ZERO {
+            return Err(anyhow::anyhow!("Quantity must be positive"));
+        }
+        if self.unit_price < Decimal::ZERO {
+            return Err(anyhow::anyhow!("Unit price cannot be negative"));
+        }
+        if self.quantity.checked_mul(self.unit_price).is_none() {
+            return Err(anyhow::anyhow!("Line amount is too large"));
+        }
+        Ok(())
+    }
+}
+
+impl CreateBillRequest {
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if let Some(ref number) = self.bill_number {
+            if number.trim().is_empty() {
+                return Err(anyhow::anyhow!("Bill number cannot be empty"));
+            }
+        }
+        if self.lines.is_empty() {
+            return Err(anyhow::anyhow!("Bill must have at least one line"));
+        }
+        for (index, line) in self.lines.iter().enumerate() {
+            line.validate().map_err(|err| anyhow::anyhow!("Line {}: {}", index + 1, err))?;
+        }
+        if self.due_date() < self.bill_date {
+            return Err(anyhow::anyhow!("Due date cannot be before the bill date"));
+        }
+        let total = self.lines
+            .iter()
+            .try_fold(Decimal::ZERO, |total, line| total.checked_add(line.amount()))
+            .ok_or_else(|| anyhow::anyhow!("Bill total is too large"))?;
+        super::to_minor_units(total)?;
+        if total <= Decimal::ZERO {
+            return Err(anyhow::anyhow!("Bill total must be positive"));
+        }
+        Ok(())
+    }
+
+    pub fn due_date(&self) -> NaiveDate {
+        self.due_date.unwrap_or(self.bill_date)
+    }
+
+    pub fn total(&self) -> Decimal {
+        self.lines.iter().map(|line| line.amount()).sum()
+    }
+
+    /// The posting lines: each line to its account and the total to the vendor's payable.
+    /// The payable account must have been resolved.
+    pub fn journal_entries(&self) -> anyhow::Result<Vec<CreateJournalEntryRequest>> {
+        let mut journal_entries: Vec<CreateJournalEntryRequest> = self.lines
+            .iter()
+            .filter(|line| !line.amount().is_zero())
+            .map(|line| CreateJournalEntryRequest {
+                account_id: line.account_id,
+                debit_amount: Some(line.amount()),
+                description: Some(line.description.clone()),
+                ..Default::default()
+            })
+            .collect();
+
+        journal_entries.push(CreateJournalEntryRequest {
+            account_id: self.payable_account_id.ok_or_else(|| anyhow::anyhow!("Bill has no payable account"))?,
+            credit_amount: Some(self.total()),
+            description: self.description.clone(),
+            party_id: Some(self.party_id),
+            due_date: Some(self.due_date()),
+            ..Default::default()
+        });
+
+        Ok(journal_entries)
+    }
+
+    pub fn transaction_request(&self, bill_number: &str) -> anyhow::Result<CreateTransactionRequest> {
+        let description = match self.vendor_reference {
+            Some(ref reference) => format!("Bill {} ({})", bill_number, reference),
+            None => format!("Bill {}", bill_number),
+        };
+
+        Ok(CreateTransactionRequest {
+            description,
+            reference: Some(bill_number.to_string()),
+            transaction_date: self.bill_date,
+            status: Some(TransactionStatus::Posted),
+            journal_entries: self.journal_entries()?,
+        })
+    }
+}
+
+/// Money paid to a vendor
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VendorPayment {
+    pub id: i64,
+    pub company_id: i64,
+    pub party_id: i64,
+    pub payment_date: NaiveDate,
+    pub amount: Decimal,
+    /// Cash or bank account the money is paid from
+    pub account_id: i64,
+    pub payable_account_id: i64,
+    pub reference: Option<String>,
+    pub transaction_id: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A payment with its allocations; what is not allocated is credit held with the vendor.
+/// Allocations to bills that are no longer posted are listed but do not count towards
+/// `allocated`, so their amount is credit again.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VendorPaymentWithAllocations {
+    pub payment: VendorPayment,
+    pub allocations: Vec<BillAllocation>,
+    pub allocated: Decimal,
+    /// Vendor credit left to apply; zero once the payment is voided
+    pub unapplied: Decimal,
+    pub is_voided: bool,
+}
+
+impl VendorPaymentWithAllocations {
+    /// Work out the allocated amount from the allocations whose bill is posted
+    pub fn new(
+        payment: VendorPayment,
+        status: TransactionStatus,
+        allocations: Vec<(BillAllocation, TransactionStatus)>,
+    ) -> Self {
+        let allocated: Decimal = allocations
+            .iter()
+            .filter(|(_, bill_status)| bill_status.affects_balances())
+            .map(|(allocation, _)| allocation.amount)
+            .sum();
+        let is_voided = !status.affects_balances();
+
+        Self {
+            unapplied: if is_voided { Decimal::ZERO } else { payment.amount - allocated },
+            payment,
+            allocations: allocations.into_iter().map(|(allocation, _)| allocation).collect(),
+            allocated,
+            is_voided,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BillAllocationRequest {
+    pub bill_id: i64,
+    pub amount: Decimal,
+}
+
+/// Check a set of allocations against the amount available to them
+pub fn validate_allocations(allocations: &[BillAllocationRequest], available: Decimal) -> anyhow::Result<()> {
+    let mut bills = HashSet::new();
+    for allocation in allocations {
+        if allocation.amount <= Decimal::ZERO {
+            return Err(anyhow::anyhow!("Allocation amounts must be positive"));
+        }
+        if !bills.insert(allocation.bill_id) {
+            return Err(anyhow::anyhow!("Bill {} is allocated more than once", allocation.bill_id));
+        }
+    }
+
+    let total: Decimal = allocations.iter().map(|allocation| allocation.amount).sum();
+    if total > available {
+        return Err(anyhow::anyhow!("Allocations of {} are more than the {} available", total, available));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VendorPaymentRequest {
+    pub party_id: i64,
+    pub payment_date: NaiveDate,
+    pub amount: Decimal,
+    /// Account the money is paid from; defaults to 1110 Cash
+    pub account_id: Option<i64>,
+    /// Defaults to 2110 Accounts Payable
+    pub payable_account_id: Option<i64>,
+    pub reference: Option<String>,
+    /// Bills to apply the payment to; any remainder is held as vendor credit
+    #[serde(default)]
+    pub allocations: Vec<BillAllocationRequest>,
+}
+
+impl VendorPaymentRequest {
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.amount <= Decimal::ZERO {
+            return Err(anyhow::anyhow!("Payment amount must be positive"));
+        }
+        validate_allocations(&self.allocations, self.amount)
+    }
+
+    /// Clear the vendor's payable against the paying account. Both accounts must have been
+    /// resolved.
+    pub fn transaction_request(&self) -> anyhow::Result<CreateTransactionRequest> {
+        let account_id = self.account_id.ok_or_else(|| anyhow::anyhow!("Payment has no paying account"))?;
+        let payable_account_id = self.payable_account_id.ok_or_else(|| anyhow::anyhow!("Payment has no payable account"))?;
+
+        Ok(CreateTransactionRequest {
+            description: "Vendor payment".to_string(),
+            reference: self.reference.clone(),
+            transaction_date: self.payment_date,
+            status: Some(TransactionStatus::Posted),
+            journal_entries: vec![
+                CreateJournalEntryRequest {
+                    account_id: payable_account_id,
+                    debit_amount: Some(self.amount),
+                    party_id: Some(self.party_id),
+                    ..Default::default()
+                },
+                CreateJournalEntryRequest {
+                    account_id,
+                    credit_amount: Some(self.amount),
+                    ..Default::default()
+                },
+            ],
+        })
+    }
+}
+
+/// Apply held vendor credit from an earlier payment to bills
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AllocateVendorPaymentRequest {
+    pub allocations: Vec<BillAllocationRequest>,
+}
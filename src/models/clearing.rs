@@ -0,0 +1,170 @@
+use std::collections::HashSet;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+/// Debit and credit lines of one account matched against each other
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClearingGroup {
+    pub id: i64,
+    pub company_id: i64,
+    pub account_id: i64,
+    pub party_id: Option<i64>,
+    pub is_automatic: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+/// The part of a journal line cleared by a group, as debits less credits
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClearingLine {
+    pub id: i64,
+    pub group_id: i64,
+    pub journal_entry_id: i64,
+    pub transaction_id: i64,
+    pub transaction_date: NaiveDate,
+    pub reference: Option<String>,
+    pub amount: Decimal,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ClearingGroupWithLines {
+    pub group: ClearingGroup,
+    pub lines: Vec<ClearingLine>,
+    /// False once one of the lines' transactions is no longer posted; the lines are open again
+    pub is_active: bool,
+}
+
+/// A posted line with what is left of it after its active clearing groups
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnclearedLine {
+    pub journal_entry_id: i64,
+    pub transaction_id: i64,
+    pub account_id: i64,
+    pub party_id: Option<i64>,
+    pub party_code: Option<String>,
+    pub transaction_date: NaiveDate,
+    pub due_date: Option<NaiveDate>,
+    pub reference: Option<String>,
+    pub description: String,
+    /// The whole line, as debits less credits
+    pub amount: Decimal,
+    pub cleared: Decimal,
+    pub residual: Decimal,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateClearingGroupRequest {
+    pub account_id: i64,
+    pub lines: Vec<ClearingLineRequest>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ClearingLineRequest {
+    pub journal_entry_id: i64,
+    /// How much of the line to clear, as a positive amount; defaults to all that is left
+    pub amount: Option<Decimal>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AutoMatchRequest {
+    pub account_id: i64,
+    /// Only match this party's lines
+    pub party_id: Option<i64>,
+}
+
+/// Work out the signed amount each requested line clears. `open` must hold the uncleared
+/// lines of the account; every requested line has to be among them, belong to the same
+/// party, and the debits and credits cleared must be equal.
+pub fn clearing_amounts(open: &[UnclearedLine], requested: &[ClearingLineRequest]) -> anyhow::Result<Vec<(i64, Decimal)>> {
+    if requested.len() < 2 {
+        return Err(anyhow::anyhow!("A clearing group needs at least two lines"));
+    }
+
+    let mut seen = HashSet::new();
+    let mut party_id = None;
+    let mut amounts = Vec::with_capacity(requested.len());
+    for (index, request) in requested.iter().enumerate() {
+        if !seen.insert(request.journal_entry_id) {
+            return Err(anyhow::anyhow!("Journal entry {} is listed more than once", request.journal_entry_id));
+        }
+        let line = open
+            .iter()
+            .find(|line| line.journal_entry_id == request.journal_entry_id)
+            .ok_or_else(|| anyhow::anyhow!(
+                "Journal entry {} is not an open posted line of the account",
+                request.journal_entry_id
+            ))?;
+
+        if index == 0 {
+            party_id = line.party_id;
+        } else if line.party_id != party_id {
+            return Err(anyhow::anyhow!("Lines of different parties cannot be cleared together"));
+        }
+
+        let amount = request.amount.unwrap_or(line.residual.abs());
+        if amount <= Decimal::ZERO {
+            return Err(anyhow::anyhow!("Journal entry {}: amount to clear must be positive", line.journal_entry_id));
+        }
+        if amount > line.residual.abs() {
+            return Err(anyhow::anyhow!(
+                "Journal entry {}: only {} is left to clear",
+                line.journal_entry_id, line.residual.abs()
+            ));
+        }
+        let signed = if line.residual > Decimal::ZERO { amount } else { -amount };
+        amounts.push((line.journal_entry_id, signed));
+    }
+
+    let net: Decimal = amounts.iter().map(|(_, amount)| *amount).sum();
+    if !net.is_zero() {
+        return Err(anyhow::anyhow!("Debits and credits cleared differ by {}", net));
+    }
+
+    Ok(amounts)
+}
+
+/// Propose clearing groups among open lines that share a party and a reference. When all of
+/// them net to zero they clear together; otherwise a debit and a credit of the same size
+/// clear each other, oldest first.
+pub fn propose_matches(open: &[UnclearedLine]) -> Vec<Vec<(i64, Decimal)>> {
+    let mut buckets: Vec<(Option<i64>, &str, Vec<&UnclearedLine>)> = Vec::new();
+    for line in open {
+        let Some(reference) = line.reference.as_deref().filter(|reference| !reference.trim().is_empty()) else {
+            continue;
+        };
+        match buckets.iter_mut().find(|(party_id, key, _)| *party_id == line.party_id && *key == reference) {
+            Some((_, _, lines)) => lines.push(line),
+            None => buckets.push((line.party_id, reference, vec![line])),
+        }
+    }
+
+    let mut groups = Vec::new();
+    for (_, _, lines) in buckets {
+        if lines.len() < 2 {
+            continue;
+        }
+
+        let net: Decimal = lines.iter().map(|line| line.residual).sum();
+        if net.is_zero() {
+            groups.push(lines.iter().map(|line| (line.journal_entry_id, line.residual)).collect());
+            continue;
+        }
+
+        let mut used = HashSet::new();
+        for debit in lines.iter().filter(|line| line.residual > Decimal::ZERO) {
+            let credit = lines.iter().find(|line| {
+                line.residual == -debit.residual && !used.contains(&line.journal_entry_id)
+            });
+            if let Some(credit) = credit {
+                used.insert(credit.journal_entry_id);
+                groups.push(vec![
+                    (debit.journal_entry_id, debit.residual),
+                    (credit.journal_entry_id, credit.residual),
+                ]);
+            }
+        }
+    }
+
+    groups
+}
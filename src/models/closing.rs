@@ -0,0 +1,138 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::models::{AccountType, CreateJournalEntryRequest, CreateTransactionRequest, TransactionStatus, TransactionWithEntries};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct YearEndClose {
+    pub id: i64,
+    pub company_id: i64,
+    pub fiscal_year: i32,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub equity_account_id: i64,
+    pub transaction_id: i64,
+    pub reversal_transaction_id: Option<i64>,
+    pub status: YearEndCloseStatus,
+    pub created_at: DateTime<Utc>,
+    pub reopened_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "TEXT")]
+#[sqlx(rename_all = "lowercase")]
+pub enum YearEndCloseStatus {
+    Closed,
+    Reopened,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct YearEndCloseRequest {
+    pub fiscal_year: i32,
+    /// Equity account that receives the year's result; defaults to 3200 Retained Earnings
+    pub equity_account_id: Option<i64>,
+}
+
+/// One revenue or expense account as it stands at year end, before closing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClosingLine {
+    pub account_id: i64,
+    pub account_code: String,
+    pub account_name: String,
+    pub account_type: AccountType,
+    /// Net activity for the year as debits minus credits
+    pub balance: Decimal,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct YearEndClosePreview {
+    pub fiscal_year: i32,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub equity_account_id: i64,
+    pub lines: Vec<ClosingLine>,
+    pub total_revenue: Decimal,
+    pub total_expenses: Decimal,
+    pub net_income: Decimal,
+    pub transaction: CreateTransactionRequest,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct YearEndCloseResult {
+    pub closing: YearEndClose,
+    pub transaction: TransactionWithEntries,
+}
+
+impl YearEndClosePreview {
+    /// Build the closing entry that zeroes every line into the equity account
+    pub fn new(
+        fiscal_year: i32,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+        equity_account_id: i64,
+        lines: Vec<ClosingLine>,
+    ) -> Self {
+        let lines: Vec<ClosingLine> = lines.into_iter().filter(|line| !line.balance.is_zero()).collect();
+
+        let mut total_revenue = Decimal::ZERO;
+        let mut total_expenses = Decimal::ZERO;
+        let mut journal_entries = Vec::new();
+        let mut net_debit = Decimal::ZERO;
+
+        for line in &lines {
+            match line.account_type {
+                AccountType::Revenue => total_revenue -= line.balance,
+                AccountType::Expense => total_expenses += line.balance,
+                _ => {}
+            }
+            net_debit += line.balance;
+
+            // Post the opposite side of the year's net activity
+            journal_entries.push(CreateJournalEntryRequest {
+                account_id: line.account_id,
+                debit_amount: (line.balance < Decimal::ZERO).then_some(-line.balance),
+                credit_amount: (line.balance > Decimal::ZERO).then_some(line.balance),
+                description: Some(format!("Close {} {}", line.account_code, line.account_name)),
+                ..Default::default()
+            });
+        }
+
+        // A net debit across revenue and expenses is a loss and reduces equity
+        if !net_debit.is_zero() {
+            journal_entries.push(CreateJournalEntryRequest {
+                account_id: equity_account_id,
+                debit_amount: (net_debit > Decimal::ZERO).then_some(net_debit),
+                credit_amount: (net_debit < Decimal::ZERO).then_some(-net_debit),
+                description: Some(format!("Result for fiscal year {}", fiscal_year)),
+                ..Default::default()
+            });
+        }
+
+        let transaction = CreateTransactionRequest {
+            description: format!("Year-end close for fiscal year {}", fiscal_year),
+            reference: Some(format!("YEC-{}", fiscal_year)),
+            transaction_date: end_date,
+            status: Some(TransactionStatus::Posted),
+            journal_entries,
+        };
+
+        Self {
+            fiscal_year,
+            start_date,
+            end_date,
+            equity_account_id,
+            lines,
+            total_revenue,
+            total_expenses,
+            net_income: total_revenue - total_expenses,
+            transaction,
+        }
+    }
+
+    /// Check if there is any revenue or expense activity left to close
+    pub fn has_activity(&self) -> bool {
+        !self.lines.is_empty()
+    }
+}
@@ -0,0 +1,37 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// A legal entity with its own chart of accounts and books
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Company {
+    pub id: i64,
+    pub code: String,
+    pub name: String,
+    /// Currency that `debit_amount`, `credit_amount` and account balances are kept in
+    pub functional_currency: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateCompanyRequest {
+    pub code: String,
+    pub name: String,
+    /// Defaults to the configured functional currency
+    pub functional_currency: Option<String>,
+}
+
+impl CreateCompanyRequest {
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.code.trim().is_empty() {
+            return Err(anyhow::anyhow!("Company code cannot be empty"));
+        }
+        if self.name.trim().is_empty() {
+            return Err(anyhow::anyhow!("Company name cannot be empty"));
+        }
+        if let Some(ref currency) = self.functional_currency {
+            super::validate_currency_code(currency)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,58 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::{Decimal, RoundingStrategy};
+use serde::{Deserialize, Serialize};
+
+use crate::models::AMOUNT_SCALE;
+
+/// Functional currency used when none is configured
+pub const DEFAULT_FUNCTIONAL_CURRENCY: &str = "USD";
+
+/// A daily rate into a company's functional currency
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExchangeRate {
+    pub id: i64,
+    pub company_id: i64,
+    pub currency: String,
+    pub rate_date: NaiveDate,
+    /// Functional currency units per one unit of `currency`
+    pub rate: Decimal,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateExchangeRateRequest {
+    pub currency: String,
+    pub rate_date: NaiveDate,
+    pub rate: Decimal,
+}
+
+impl CreateExchangeRateRequest {
+    pub fn validate(&self) -> anyhow::Result<()> {
+        validate_currency_code(&self.currency)?;
+        if self.rate <= Decimal::ZERO {
+            return Err(anyhow::anyhow!("Exchange rate must be positive"));
+        }
+        Ok(())
+    }
+}
+
+/// Check that a currency code is three upper-case ASCII letters, as in ISO 4217
+pub fn validate_currency_code(code: &str) -> anyhow::Result<()> {
+    if code.len() != 3 || !code.bytes().all(|b| b.is_ascii_uppercase()) {
+        return Err(anyhow::anyhow!(
+            "Currency code {:?} must be three upper-case letters, e.g. EUR",
+            code
+        ));
+    }
+    Ok(())
+}
+
+/// How far a line's functional amount may be from its converted currency amount: one cent,
+/// so entries split over several lines can absorb the rounding and still balance
+pub const CONVERSION_TOLERANCE: Decimal = Decimal::from_parts(1, 0, 0, false, AMOUNT_SCALE);
+
+/// Convert a transaction-currency amount to the functional currency, rounded to whole cents
+pub fn convert_to_functional(currency_amount: Decimal, exchange_rate: Decimal) -> Decimal {
+    (currency_amount * exchange_rate).round_dp_with_strategy(AMOUNT_SCALE, RoundingStrategy::MidpointAwayFromZero)
+}
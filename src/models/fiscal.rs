@@ -0,0 +1,145 @@
+use chrono::{Datelike, Months, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+/// Reporting periods that run from the start of the current fiscal month, quarter or year
+/// up to a given date
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PeriodPreset {
+    MonthToDate,
+    QuarterToDate,
+    YearToDate,
+}
+
+impl PeriodPreset {
+    /// First and last day of the period ending on `date`, or `None` when the period would
+    /// start before the earliest supported date
+    pub fn resolve(&self, calendar: &FiscalCalendar, date: NaiveDate) -> Option<(NaiveDate, NaiveDate)> {
+        let start = match self {
+            PeriodPreset::MonthToDate => calendar.period_start(date, 1)?,
+            PeriodPreset::QuarterToDate => calendar.period_start(date, 3)?,
+            PeriodPreset::YearToDate => calendar.year_start(calendar.fiscal_year_of(date))?,
+        };
+
+        Some((start, date))
+    }
+}
+
+/// How far apart the columns of a comparative statement lie
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Comparison {
+    PreviousMonth,
+    PreviousQuarter,
+    PreviousYear,
+}
+
+impl Comparison {
+    pub fn months(&self) -> u32 {
+        match self {
+            Comparison::PreviousMonth => 1,
+            Comparison::PreviousQuarter => 3,
+            Comparison::PreviousYear => 12,
+        }
+    }
+
+    /// The date `steps` comparison intervals before `date`, or `None` when that falls before
+    /// the earliest supported date.
+    /// Month ends stay month ends, so March 31 compares with the last day of February.
+    pub fn shift_back(&self, date: NaiveDate, steps: u32) -> Option<NaiveDate> {
+        let shifted = date.checked_sub_months(Months::new(self.months().checked_mul(steps)?))?;
+
+        if date.succ_opt().is_none_or(|next| next.month() != date.month()) {
+            shifted
+                .with_day(1)?
+                .checked_add_months(Months::new(1))
+                .and_then(|next_month| next_month.pred_opt())
+        } else {
+            Some(shifted)
+        }
+    }
+}
+
+/// Where fiscal years start. Fiscal year N is the twelve months ending in calendar year N,
+/// so with a July 1 start FY2025 runs from 2024-07-01 to 2025-06-30.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct FiscalCalendar {
+    pub start_month: u32,
+    pub start_day: u32,
+}
+
+impl Default for FiscalCalendar {
+    fn default() -> Self {
+        Self {
+            start_month: 1,
+            start_day: 1,
+        }
+    }
+}
+
+impl FiscalCalendar {
+    /// Parse a fiscal year start written as `MM-DD`, e.g. `07-01`.
+    /// Days are limited to 1-28 so every year has the start date.
+    pub fn parse(value: &str) -> anyhow::Result<Self> {
+        let (month, day) = value
+            .trim()
+            .split_once('-')
+            .ok_or_else(|| anyhow::anyhow!("Fiscal year start must be written as MM-DD"))?;
+        let start_month: u32 = month.parse()?;
+        let start_day: u32 = day.parse()?;
+
+        if !(1..=12).contains(&start_month) || !(1..=28).contains(&start_day) {
+            return Err(anyhow::anyhow!(
+                "Fiscal year start {} is out of range; use a month of 1-12 and a day of 1-28",
+                value
+            ));
+        }
+
+        Ok(Self { start_month, start_day })
+    }
+
+    /// First day of the given fiscal year, or `None` when it lies outside the supported dates
+    pub fn year_start(&self, fiscal_year: i32) -> Option<NaiveDate> {
+        let calendar_year = if (self.start_month, self.start_day) == (1, 1) {
+            fiscal_year
+        } else {
+            fiscal_year.checked_sub(1)?
+        };
+
+        NaiveDate::from_ymd_opt(calendar_year, self.start_month, self.start_day)
+    }
+
+    /// Last day of the given fiscal year, or `None` when it lies outside the supported dates
+    pub fn year_end(&self, fiscal_year: i32) -> Option<NaiveDate> {
+        self.year_start(fiscal_year.checked_add(1)?)?.pred_opt()
+    }
+
+    /// First and last day of the given fiscal year, or `None` when it lies outside the
+    /// supported dates
+    pub fn year_range(&self, fiscal_year: i32) -> Option<(NaiveDate, NaiveDate)> {
+        Some((self.year_start(fiscal_year)?, self.year_end(fiscal_year)?))
+    }
+
+    /// The fiscal year a date falls into
+    pub fn fiscal_year_of(&self, date: NaiveDate) -> i32 {
+        match self.year_start(date.year() + 1) {
+            Some(next_start) if date >= next_start => date.year() + 1,
+            _ => date.year(),
+        }
+    }
+
+    /// First day of the fiscal period of `months` months that contains `date`.
+    /// Periods are counted from the start of the fiscal year, so fiscal months begin on the
+    /// fiscal year's start day. `None` when the fiscal year starts before the earliest
+    /// supported date.
+    pub fn period_start(&self, date: NaiveDate, months: u32) -> Option<NaiveDate> {
+        let mut start = self.year_start(self.fiscal_year_of(date))?;
+        while let Some(next) = start.checked_add_months(Months::new(months)) {
+            if next > date {
+                break;
+            }
+            start = next;
+        }
+        Some(start)
+    }
+}
@@ -0,0 +1,316 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::{Decimal, RoundingStrategy};
+use serde::{Deserialize, Serialize};
+
+use super::{CreateJournalEntryRequest, CreateTransactionRequest, TransactionStatus, AMOUNT_SCALE};
+
+/// A sales invoice, posted to the ledger when it is issued
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Invoice {
+    pub id: i64,
+    pub company_id: i64,
+    pub invoice_number: String,
+    pub party_id: i64,
+    pub invoice_date: NaiveDate,
+    pub due_date: NaiveDate,
+    pub description: Option<String>,
+    pub receivable_account_id: i64,
+    pub tax_account_id: Option<i64>,
+    pub subtotal: Decimal,
+    pub tax_amount: Decimal,
+    pub total: Decimal,
+    pub transaction_id: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InvoiceLine {
+    pub id: i64,
+    pub invoice_id: i64,
+    pub line_number: i64,
+    pub description: String,
+    pub quantity: Decimal,
+    pub unit_price: Decimal,
+    /// Revenue account credited with the line amount
+    pub account_id: i64,
+    pub tax_rate: Option<Decimal>,
+    pub amount: Decimal,
+    pub tax_amount: Decimal,
+}
+
+/// The part of a payment transaction applied to an invoice
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InvoicePayment {
+    pub id: i64,
+    pub invoice_id: i64,
+    pub transaction_id: i64,
+    pub payment_date: NaiveDate,
+    pub amount: Decimal,
+    pub created_at: DateTime<Utc>,
+}
+
+/// How far an invoice or a vendor bill has been paid
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum InvoiceStatus {
+    Open,
+    PartiallyPaid,
+    Paid,
+    /// The document's posting has been voided
+    Voided,
+}
+
+impl InvoiceStatus {
+    pub fn from_amounts(total: Decimal, amount_paid: Decimal) -> Self {
+        if amount_paid >= total {
+            InvoiceStatus::Paid
+        } else if amount_paid > Decimal::ZERO {
+            InvoiceStatus::PartiallyPaid
+        } else {
+            InvoiceStatus::Open
+        }
+    }
+}
+
+/// An invoice with its lines and the payments applied to it. Payments whose transaction is no
+/// longer posted are listed but do not count towards `amount_paid`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InvoiceWithDetails {
+    pub invoice: Invoice,
+    pub lines: Vec<InvoiceLine>,
+    pub payments: Vec<InvoicePayment>,
+    pub amount_paid: Decimal,
+    pub balance_due: Decimal,
+    pub status: InvoiceStatus,
+}
+
+impl InvoiceWithDetails {
+    /// Work out the paid amount and status from the payments whose transaction is posted
+    pub fn new(
+        invoice: Invoice,
+        invoice_status: TransactionStatus,
+        lines: Vec<InvoiceLine>,
+        payments: Vec<(InvoicePayment, TransactionStatus)>,
+    ) -> Self {
+        let amount_paid = payments
+            .iter()
+            .filter(|(_, status)| status.affects_balances())
+            .map(|(payment, _)| payment.amount)
+            .sum();
+        let status = if invoice_status == TransactionStatus::Voided {
+            InvoiceStatus::Voided
+        } else {
+            InvoiceStatus::from_amounts(invoice.total, amount_paid)
+        };
+
+        Self {
+            balance_due: invoice.total - amount_paid,
+            invoice,
+            lines,
+            payments: payments.into_iter().map(|(payment, _)| payment).collect(),
+            amount_paid,
+            status,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateInvoiceRequest {
+    /// Taken from the company's invoice sequence when omitted
+    pub invoice_number: Option<String>,
+    pub party_id: i64,
+    pub invoice_date: NaiveDate,
+    /// Defaults to the invoice date
+    pub due_date: Option<NaiveDate>,
+    pub description: Option<String>,
+    /// Defaults to 1120 Accounts Receivable
+    pub receivable_account_id: Option<i64>,
+    /// Defaults to 2130 Sales Tax Payable; only used when a line carries tax
+    pub tax_account_id: Option<i64>,
+    pub lines: Vec<CreateInvoiceLineRequest>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateInvoiceLineRequest {
+    pub description: String,
+    pub quantity: Decimal,
+    pub unit_price: Decimal,
+    /// Defaults to 4100 Sales Revenue
+    pub account_id: Option<i64>,
+    /// Tax as a fraction of the line amount, e.g. 0.08
+    pub tax_rate: Option<Decimal>,
+}
+
+impl CreateInvoiceLineRequest {
+    /// Quantity times unit price, rounded half away from zero to cents. `validate` checks the
+    /// product does not overflow.
+    pub fn amount(&self) -> Decimal {
+        (self.quantity * self.unit_price).round_dp_with_strategy(AMOUNT_SCALE, RoundingStrategy::MidpointAwayFromZero)
+    }
+
+    pub fn tax_amount(&self) -> Decimal {
+        self.tax_rate
+            .map(|rate| (self.amount() * rate).round_dp_with_strategy(AMOUNT_SCALE, RoundingStrategy::MidpointAwayFromZero))
+            .unwrap_or(Decimal::ZERO)
+    }
+
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.description.trim().is_empty() {
+            return Err(anyhow::anyhow!("Line description cannot be empty"));
+        }
+        if self.quantity <= Decimal::ZERO {
+            return Err(anyhow::anyhow!("Quantity must be positive"));
+        }
+        if self.unit_price < Decimal::ZERO {
+            return Err(anyhow::anyhow!("Unit price cannot be negative"));
+        }
+        if self.quantity.checked_mul(self.unit_price).is_none() {
+            return Err(anyhow::anyhow!("Line amount is too large"));
+        }
+        if let Some(rate) = self.tax_rate {
+            if rate < Decimal::ZERO || rate > Decimal::ONE {
+                return Err(anyhow::anyhow!("Tax rate must be between 0 and 1"));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl CreateInvoiceRequest {
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if let Some(ref number) = self.invoice_number {
+            if number.trim().is_empty() {
+                return Err(anyhow::anyhow!("Invoice number cannot be empty"));
+            }
+        }
+        if self.lines.is_empty() {
+            return Err(anyhow::anyhow!("Invoice must have at least one line"));
+        }
+        for (index, line) in self.lines.iter().enumerate() {
+            line.validate().map_err(|err| anyhow::anyhow!("Line {}: {}", index + 1, err))?;
+        }
+        if self.due_date() < self.invoice_date {
+            return Err(anyhow::anyhow!("Due date cannot be before the invoice date"));
+        }
+        let total = self.lines
+            .iter()
+            .try_fold(Decimal::ZERO, |total, line| total.checked_add(line.amount())?.checked_add(line.tax_amount()))
+            .ok_or_else(|| anyhow::anyhow!("Invoice total is too large"))?;
+        super::to_minor_units(total)?;
+        if total <= Decimal::ZERO {
+            return Err(anyhow::anyhow!("Invoice total must be positive"));
+        }
+        Ok(())
+    }
+
+    pub fn due_date(&self) -> NaiveDate {
+        self.due_date.unwrap_or(self.invoice_date)
+    }
+
+    pub fn subtotal(&self) -> Decimal {
+        self.lines.iter().map(|line| line.amount()).sum()
+    }
+
+    pub fn tax_amount(&self) -> Decimal {
+        self.lines.iter().map(|line| line.tax_amount()).sum()
+    }
+
+    pub fn total(&self) -> Decimal {
+        self.subtotal() + self.tax_amount()
+    }
+
+    /// The posting lines: the total to the customer's receivable, each line to its revenue
+    /// account and the tax to the tax account. Every account must have been resolved.
+    pub fn journal_entries(&self) -> anyhow::Result<Vec<CreateJournalEntryRequest>> {
+        let receivable_account_id = self.receivable_account_id
+            .ok_or_else(|| anyhow::anyhow!("Invoice has no receivable account"))?;
+
+        let mut journal_entries = vec![CreateJournalEntryRequest {
+            account_id: receivable_account_id,
+            debit_amount: Some(self.total()),
+            description: self.description.clone(),
+            party_id: Some(self.party_id),
+            due_date: Some(self.due_date()),
+            ..Default::default()
+        }];
+
+        for line in &self.lines {
+            let amount = line.amount();
+            if amount.is_zero() {
+                continue;
+            }
+            journal_entries.push(CreateJournalEntryRequest {
+                account_id: line.account_id.ok_or_else(|| anyhow::anyhow!("Invoice line has no revenue account"))?,
+                credit_amount: Some(amount),
+                description: Some(line.description.clone()),
+                ..Default::default()
+            });
+        }
+
+        let tax_amount = self.tax_amount();
+        if !tax_amount.is_zero() {
+            journal_entries.push(CreateJournalEntryRequest {
+                account_id: self.tax_account_id.ok_or_else(|| anyhow::anyhow!("Invoice has no tax account"))?,
+                credit_amount: Some(tax_amount),
+                description: Some("Sales tax".to_string()),
+                ..Default::default()
+            });
+        }
+
+        Ok(journal_entries)
+    }
+
+    pub fn transaction_request(&self, invoice_number: &str) -> anyhow::Result<CreateTransactionRequest> {
+        Ok(CreateTransactionRequest {
+            description: format!("Invoice {}", invoice_number),
+            reference: Some(invoice_number.to_string()),
+            transaction_date: self.invoice_date,
+            status: Some(TransactionStatus::Posted),
+            journal_entries: self.journal_entries()?,
+        })
+    }
+}
+
+/// Money received against one invoice
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InvoicePaymentRequest {
+    pub payment_date: NaiveDate,
+    pub amount: Decimal,
+    /// Account the money is received into; defaults to 1110 Cash
+    pub account_id: Option<i64>,
+    pub reference: Option<String>,
+}
+
+impl InvoicePaymentRequest {
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.amount <= Decimal::ZERO {
+            return Err(anyhow::anyhow!("Payment amount must be positive"));
+        }
+        Ok(())
+    }
+
+    /// Debit the receiving account and clear the customer's receivable
+    pub fn transaction_request(&self, invoice: &Invoice) -> anyhow::Result<CreateTransactionRequest> {
+        let account_id = self.account_id.ok_or_else(|| anyhow::anyhow!("Payment has no receiving account"))?;
+
+        Ok(CreateTransactionRequest {
+            description: format!("Payment for invoice {}", invoice.invoice_number),
+            reference: self.reference.clone().or_else(|| Some(invoice.invoice_number.clone())),
+            transaction_date: self.payment_date,
+            status: Some(TransactionStatus::Posted),
+            journal_entries: vec![
+                CreateJournalEntryRequest {
+                    account_id,
+                    debit_amount: Some(self.amount),
+                    ..Default::default()
+                },
+                CreateJournalEntryRequest {
+                    account_id: invoice.receivable_account_id,
+                    credit_amount: Some(self.amount),
+                    party_id: Some(invoice.party_id),
+                    ..Default::default()
+                },
+            ],
+        })
+    }
+}
@@ -36,6 +36,7 @@ pub struct JournalEntryWithDetails {
     pub id: i64,
     pub transaction_id: i64,
     pub transaction_description: String,
+    pub transaction_reference: Option<String>,
     pub transaction_date: chrono::NaiveDate,
     pub account_id: i64,
     pub account_code: String,
@@ -43,6 +44,10 @@ pub struct JournalEntryWithDetails {
     pub debit_amount: Decimal,
     pub credit_amount: Decimal,
     pub description: Option<String>,
+    pub party_id: Option<i64>,
+    /// Account balance (debits less credits) after this line, set by `AccountStatement`
+    #[sqlx(skip)]
+    pub running_balance: Decimal,
     pub created_at: DateTime<Utc>,
 }
 
@@ -126,6 +131,14 @@ pub struct TrialBalanceEntry {
     pub account_type: crate::models::AccountType,
     pub debit_balance: Decimal,
     pub credit_balance: Decimal,
+    /// Level in the account tree when balances are rolled up
+    pub depth: Option<usize>,
+}
+
+impl Default for TrialBalance {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl TrialBalance {
@@ -145,6 +158,34 @@ impl TrialBalance {
         self.is_balanced = self.total_debits == self.total_credits;
     }
 
+    /// Add a top-level account with rolled-up balances, followed by its listed descendants.
+    /// Only the top-level balance counts towards the totals.
+    pub fn add_tree(&mut self, node: &crate::models::AccountTreeNode) {
+        for line in node.flatten() {
+            let (debit_balance, credit_balance) = if line.total_balance >= Decimal::ZERO {
+                (line.total_balance, Decimal::ZERO)
+            } else {
+                (Decimal::ZERO, -line.total_balance)
+            };
+
+            let entry = TrialBalanceEntry {
+                account_id: line.account.id,
+                account_code: line.account.code.clone(),
+                account_name: line.account.name.clone(),
+                account_type: line.account.account_type.clone(),
+                debit_balance,
+                credit_balance,
+                depth: Some(line.depth),
+            };
+
+            if line.depth == node.depth {
+                self.add_entry(entry);
+            } else {
+                self.entries.push(entry);
+            }
+        }
+    }
+
     /// Sort entries by account code
     pub fn sort_by_code(&mut self) {
         self.entries.sort_by(|a, b| a.account_code.cmp(&b.account_code));
@@ -175,6 +216,9 @@ pub struct AccountStatement {
     pub account_code: String,
     pub account_name: String,
     pub account_type: crate::models::AccountType,
+    /// Range the statement covers; the opening balance sums every line before `start_date`
+    pub start_date: Option<chrono::NaiveDate>,
+    pub end_date: Option<chrono::NaiveDate>,
     pub opening_balance: Decimal,
     pub closing_balance: Decimal,
     pub entries: Vec<JournalEntryWithDetails>,
@@ -195,6 +239,8 @@ impl AccountStatement {
             account_code,
             account_name,
             account_type,
+            start_date: None,
+            end_date: None,
             opening_balance,
             closing_balance: opening_balance,
             entries: Vec::new(),
@@ -203,15 +249,27 @@ impl AccountStatement {
         }
     }
 
-    pub fn add_entry(&mut self, entry: JournalEntryWithDetails) {
+    pub fn add_entry(&mut self, mut entry: JournalEntryWithDetails) {
         self.total_debits += entry.debit_amount;
         self.total_credits += entry.credit_amount;
         self.closing_balance += entry.debit_amount - entry.credit_amount;
+        entry.running_balance = self.closing_balance;
         self.entries.push(entry);
     }
 
-    /// Sort entries by transaction date
+    /// Sort entries by transaction date and recompute their running balances
     pub fn sort_by_date(&mut self) {
-        self.entries.sort_by(|a, b| a.transaction_date.cmp(&b.transaction_date));
+        self.entries.sort_by_key(|entry| entry.transaction_date);
+
+        let mut balance = self.opening_balance;
+        for entry in &mut self.entries {
+            balance += entry.debit_amount - entry.credit_amount;
+            entry.running_balance = balance;
+        }
+    }
+
+    /// Whether the account had neither a balance nor any lines over the range
+    pub fn is_empty(&self) -> bool {
+        self.opening_balance.is_zero() && self.entries.is_empty()
     }
 }
\ No newline at end of file
@@ -1,7 +1,34 @@
+pub mod company;
 pub mod account;
 pub mod transaction;
 pub mod journal_entry;
+pub mod money;
+pub mod currency;
+pub mod revaluation;
+pub mod fiscal;
+pub mod closing;
+pub mod period;
+pub mod party;
+pub mod aging;
+pub mod invoice;
+pub mod bill;
+pub mod clearing;
 
+pub use company::*;
 pub use account::*;
 pub use transaction::*;
-pub use journal_entry::*;
\ No newline at end of file
+pub use journal_entry::*;
+pub use money::*;
+pub use currency::*;
+pub use revaluation::*;
+pub use fiscal::*;
+pub use closing::*;
+pub use period::*;
+pub use party::*;
+pub use aging::*;
+pub use invoice::*;
+pub use bill::*;
+pub use clearing::*;
+
+// Both modules define a CreateJournalEntryRequest; transactions use the one without a transaction_id
+pub use transaction::CreateJournalEntryRequest;
@@ -0,0 +1,30 @@
+use anyhow::anyhow;
+use rust_decimal::Decimal;
+
+/// Number of decimal places stored for every monetary amount
+pub const AMOUNT_SCALE: u32 = 2;
+
+/// Convert a decimal amount to the integer minor units (cents) stored in the database.
+///
+/// Fails instead of rounding when the amount has more decimal places than
+/// `AMOUNT_SCALE`, and when it does not fit in an `i64`.
+pub fn to_minor_units(amount: Decimal) -> anyhow::Result<i64> {
+    let normalized = amount.normalize();
+    if normalized.scale() > AMOUNT_SCALE {
+        return Err(anyhow!(
+            "Amount {} has more than {} decimal places",
+            amount, AMOUNT_SCALE
+        ));
+    }
+
+    let minor = normalized
+        .checked_mul(Decimal::from(10i64.pow(AMOUNT_SCALE)))
+        .ok_or_else(|| anyhow!("Amount {} is out of range", amount))?;
+
+    i64::try_from(minor).map_err(|_| anyhow!("Amount {} is out of range", amount))
+}
+
+/// Convert integer minor units (cents) read from the database back to a decimal amount
+pub fn from_minor_units(minor: i64) -> Decimal {
+    Decimal::new(minor, AMOUNT_SCALE)
+}
@@ -0,0 +1,100 @@
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use super::AccountStatement;
+
+/// A customer or vendor whose movements on a control account are tracked separately
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Party {
+    pub id: i64,
+    pub company_id: i64,
+    pub code: String,
+    pub name: String,
+    pub party_type: PartyType,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "TEXT")]
+#[sqlx(rename_all = "lowercase")]
+pub enum PartyType {
+    Customer,
+    Vendor,
+}
+
+impl PartyType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PartyType::Customer => "customer",
+            PartyType::Vendor => "vendor",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreatePartyRequest {
+    pub code: String,
+    pub name: String,
+    pub party_type: PartyType,
+}
+
+impl CreatePartyRequest {
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.code.trim().is_empty() {
+            return Err(anyhow::anyhow!("Party code cannot be empty"));
+        }
+        if self.name.trim().is_empty() {
+            return Err(anyhow::anyhow!("Party name cannot be empty"));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdatePartyRequest {
+    pub name: Option<String>,
+}
+
+/// What one party owes or is owed on a control account, as debits less credits
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PartyBalance {
+    pub party_id: i64,
+    pub party_code: String,
+    pub party_name: String,
+    pub balance: Decimal,
+}
+
+/// A control account's balance split by party
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ControlAccountBalance {
+    pub account_id: i64,
+    pub account_code: String,
+    pub account_name: String,
+    pub party_type: PartyType,
+    /// The control account's own balance, as debits less credits
+    pub balance: Decimal,
+    pub parties: Vec<PartyBalance>,
+    /// Lines without a party, written before the account became a control account
+    pub unassigned: Decimal,
+    /// Whether the party balances and the unassigned amount add up to `balance`
+    pub is_reconciled: bool,
+}
+
+impl ControlAccountBalance {
+    pub fn update_reconciliation(&mut self) {
+        let total: Decimal = self.parties.iter().map(|party| party.balance).sum();
+        self.is_reconciled = total + self.unassigned == self.balance;
+    }
+}
+
+/// A party's lines on every control account of its type
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PartyStatement {
+    pub party: Party,
+    pub accounts: Vec<AccountStatement>,
+    /// Sum of the closing balances, as debits less credits
+    pub closing_balance: Decimal,
+}
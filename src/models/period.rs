@@ -0,0 +1,67 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Period {
+    pub id: i64,
+    pub company_id: i64,
+    pub name: String,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub status: PeriodStatus,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "TEXT")]
+#[sqlx(rename_all = "snake_case")]
+pub enum PeriodStatus {
+    Open,
+    SoftClosed,
+    HardClosed,
+}
+
+impl PeriodStatus {
+    /// Returns true if postings dated in this period are accepted from the given kind of user
+    pub fn accepts_postings(&self, is_admin: bool) -> bool {
+        match self {
+            PeriodStatus::Open => true,
+            PeriodStatus::SoftClosed => is_admin,
+            PeriodStatus::HardClosed => false,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreatePeriodRequest {
+    pub name: String,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdatePeriodStatusRequest {
+    pub status: PeriodStatus,
+}
+
+impl Period {
+    /// Check if the given date falls inside this period
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        self.start_date <= date && date <= self.end_date
+    }
+}
+
+impl CreatePeriodRequest {
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.name.trim().is_empty() {
+            return Err(anyhow::anyhow!("Period name cannot be empty"));
+        }
+        if self.start_date > self.end_date {
+            return Err(anyhow::anyhow!("Period start date must not be after its end date"));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,149 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::models::{CreateJournalEntryRequest, CreateTransactionRequest, TransactionStatus, TransactionWithEntries};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FxRevaluation {
+    pub id: i64,
+    pub company_id: i64,
+    pub revaluation_date: NaiveDate,
+    /// First day of the next period, when the adjustment is reversed
+    pub reversal_date: NaiveDate,
+    pub gain_account_id: i64,
+    pub loss_account_id: i64,
+    pub transaction_id: i64,
+    pub reversal_transaction_id: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FxRevaluationRequest {
+    pub revaluation_date: NaiveDate,
+    /// Defaults to 4900 Unrealized FX Gains
+    pub gain_account_id: Option<i64>,
+    /// Defaults to 5900 Unrealized FX Losses
+    pub loss_account_id: Option<i64>,
+}
+
+/// One foreign-currency monetary account restated at the closing rate
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RevaluationLine {
+    pub account_id: i64,
+    pub account_code: String,
+    pub account_name: String,
+    pub currency: String,
+    /// Balance in the account currency as debits minus credits
+    pub currency_balance: Decimal,
+    pub exchange_rate: Decimal,
+    /// Functional balance on the books before revaluation
+    pub book_balance: Decimal,
+    pub revalued_balance: Decimal,
+    /// Positive for a gain on the account, negative for a loss
+    pub adjustment: Decimal,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FxRevaluationPreview {
+    pub revaluation_date: NaiveDate,
+    pub reversal_date: NaiveDate,
+    pub gain_account_id: i64,
+    pub loss_account_id: i64,
+    pub lines: Vec<RevaluationLine>,
+    /// Currencies with a balance to revalue but no rate on or before the revaluation date
+    pub missing_rates: Vec<String>,
+    pub total_gain: Decimal,
+    pub total_loss: Decimal,
+    pub net_adjustment: Decimal,
+    pub transaction: CreateTransactionRequest,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FxRevaluationResult {
+    pub revaluation: FxRevaluation,
+    pub lines: Vec<RevaluationLine>,
+    pub transaction: TransactionWithEntries,
+    pub reversal: TransactionWithEntries,
+}
+
+impl FxRevaluationPreview {
+    /// Build the adjustment that brings every line to its revalued balance
+    pub fn new(
+        revaluation_date: NaiveDate,
+        reversal_date: NaiveDate,
+        gain_account_id: i64,
+        loss_account_id: i64,
+        lines: Vec<RevaluationLine>,
+        missing_rates: Vec<String>,
+    ) -> Self {
+        let lines: Vec<RevaluationLine> = lines.into_iter().filter(|line| !line.adjustment.is_zero()).collect();
+
+        let mut total_gain = Decimal::ZERO;
+        let mut total_loss = Decimal::ZERO;
+        let mut journal_entries = Vec::new();
+
+        // Adjustments are functional-only lines, so the account-currency balance is unchanged
+        for line in &lines {
+            if line.adjustment > Decimal::ZERO {
+                total_gain += line.adjustment;
+            } else {
+                total_loss -= line.adjustment;
+            }
+
+            journal_entries.push(CreateJournalEntryRequest {
+                account_id: line.account_id,
+                debit_amount: (line.adjustment > Decimal::ZERO).then_some(line.adjustment),
+                credit_amount: (line.adjustment < Decimal::ZERO).then_some(-line.adjustment),
+                description: Some(format!(
+                    "Revalue {} {} {} at {}",
+                    line.account_code, line.currency_balance, line.currency, line.exchange_rate
+                )),
+                ..Default::default()
+            });
+        }
+
+        if !total_gain.is_zero() {
+            journal_entries.push(CreateJournalEntryRequest {
+                account_id: gain_account_id,
+                credit_amount: Some(total_gain),
+                description: Some("Unrealized FX gain".to_string()),
+                ..Default::default()
+            });
+        }
+        if !total_loss.is_zero() {
+            journal_entries.push(CreateJournalEntryRequest {
+                account_id: loss_account_id,
+                debit_amount: Some(total_loss),
+                description: Some("Unrealized FX loss".to_string()),
+                ..Default::default()
+            });
+        }
+
+        let transaction = CreateTransactionRequest {
+            description: format!("FX revaluation at {}", revaluation_date),
+            reference: Some(format!("FXR-{}", revaluation_date)),
+            transaction_date: revaluation_date,
+            status: Some(TransactionStatus::Posted),
+            journal_entries,
+        };
+
+        Self {
+            revaluation_date,
+            reversal_date,
+            gain_account_id,
+            loss_account_id,
+            lines,
+            missing_rates,
+            total_gain,
+            total_loss,
+            net_adjustment: total_gain - total_loss,
+            transaction,
+        }
+    }
+
+    /// Check if any account needs adjusting
+    pub fn has_adjustments(&self) -> bool {
+        !self.lines.is_empty()
+    }
+}
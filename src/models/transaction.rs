@@ -6,27 +6,79 @@ use sqlx::FromRow;
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Transaction {
     pub id: i64,
+    pub company_id: i64,
     pub description: String,
     pub reference: Option<String>,
     pub transaction_date: NaiveDate,
+    pub reversal_of_id: Option<i64>,
+    pub status: TransactionStatus,
+    pub posted_at: Option<DateTime<Utc>>,
+    pub voided_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "TEXT")]
+#[sqlx(rename_all = "lowercase")]
+pub enum TransactionStatus {
+    Draft,
+    Posted,
+    Voided,
+}
+
+impl TransactionStatus {
+    /// Returns true if transactions in this state count towards balances and reports
+    pub fn affects_balances(&self) -> bool {
+        matches!(self, TransactionStatus::Posted)
+    }
+
+    /// Returns true if the transaction header and lines may still be changed
+    pub fn is_editable(&self) -> bool {
+        matches!(self, TransactionStatus::Draft)
+    }
+
+    /// Returns true if the lifecycle allows moving from this state to `next`
+    pub fn can_transition_to(&self, next: TransactionStatus) -> bool {
+        matches!(
+            (self, next),
+            (TransactionStatus::Draft, TransactionStatus::Posted)
+                | (TransactionStatus::Draft, TransactionStatus::Voided)
+                | (TransactionStatus::Posted, TransactionStatus::Voided)
+        )
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CreateTransactionRequest {
     pub description: String,
     pub reference: Option<String>,
     pub transaction_date: NaiveDate,
+    /// Draft or posted; transactions are posted immediately when omitted
+    pub status: Option<TransactionStatus>,
     pub journal_entries: Vec<CreateJournalEntryRequest>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct CreateJournalEntryRequest {
     pub account_id: i64,
+    /// Functional-currency amounts; exactly one side must be set
     pub debit_amount: Option<Decimal>,
     pub credit_amount: Option<Decimal>,
     pub description: Option<String>,
+    /// Transaction currency of a foreign-currency line; `None` means the functional currency
+    pub currency: Option<String>,
+    /// Amount in the transaction currency, on the same side as the functional amount
+    pub currency_amount: Option<Decimal>,
+    /// Functional units per one unit of `currency`; looked up from the rate table when omitted
+    pub exchange_rate: Option<Decimal>,
+    /// Customer or vendor of a line on a control account
+    #[serde(default)]
+    pub party_id: Option<i64>,
+    /// When a receivable or payable falls due
+    #[serde(default)]
+    pub due_date: Option<NaiveDate>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -34,6 +86,15 @@ pub struct UpdateTransactionRequest {
     pub description: Option<String>,
     pub reference: Option<String>,
     pub transaction_date: Option<NaiveDate>,
+    /// Replaces every journal line of the transaction when present
+    pub journal_entries: Option<Vec<CreateJournalEntryRequest>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReverseTransactionRequest {
+    pub transaction_date: NaiveDate,
+    pub description: Option<String>,
+    pub reference: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -54,9 +115,31 @@ pub struct JournalEntryWithAccount {
     pub debit_amount: Decimal,
     pub credit_amount: Decimal,
     pub description: Option<String>,
+    pub currency: Option<String>,
+    pub currency_amount: Option<Decimal>,
+    pub exchange_rate: Option<Decimal>,
+    pub party_id: Option<i64>,
+    pub due_date: Option<NaiveDate>,
     pub created_at: DateTime<Utc>,
 }
 
+impl JournalEntryWithAccount {
+    /// The request that would recreate this line
+    pub fn to_request(&self) -> CreateJournalEntryRequest {
+        CreateJournalEntryRequest {
+            account_id: self.account_id,
+            debit_amount: (!self.debit_amount.is_zero()).then_some(self.debit_amount),
+            credit_amount: (!self.credit_amount.is_zero()).then_some(self.credit_amount),
+            description: self.description.clone(),
+            currency: self.currency.clone(),
+            currency_amount: self.currency_amount,
+            exchange_rate: self.exchange_rate,
+            party_id: self.party_id,
+            due_date: self.due_date,
+        }
+    }
+}
+
 impl CreateTransactionRequest {
     /// Validate that the transaction balances (total debits = total credits)
     pub fn validate(&self) -> anyhow::Result<()> {
@@ -64,6 +147,10 @@ impl CreateTransactionRequest {
             return Err(anyhow::anyhow!("Transaction must have at least one journal entry"));
         }
 
+        if self.status == Some(TransactionStatus::Voided) {
+            return Err(anyhow::anyhow!("Transactions can only be created as draft or posted"));
+        }
+
         let mut total_debits = Decimal::ZERO;
         let mut total_credits = Decimal::ZERO;
 
@@ -73,12 +160,14 @@ impl CreateTransactionRequest {
                     if debit <= Decimal::ZERO {
                         return Err(anyhow::anyhow!("Debit amounts must be positive"));
                     }
+                    crate::models::to_minor_units(debit)?;
                     total_debits += debit;
                 }
                 (None, Some(credit)) => {
                     if credit <= Decimal::ZERO {
                         return Err(anyhow::anyhow!("Credit amounts must be positive"));
                     }
+                    crate::models::to_minor_units(credit)?;
                     total_credits += credit;
                 }
                 (Some(_), Some(_)) => {
@@ -97,6 +186,72 @@ impl CreateTransactionRequest {
             ));
         }
 
+        self.validate_currencies()
+    }
+
+    /// Check foreign-currency lines against their rates, up to a cent of rounding, and require
+    /// the transaction-currency amounts to balance as well when every line is in the same
+    /// foreign currency
+    fn validate_currencies(&self) -> anyhow::Result<()> {
+        let mut currency_debits = Decimal::ZERO;
+        let mut currency_credits = Decimal::ZERO;
+
+        for (index, entry) in self.journal_entries.iter().enumerate() {
+            let currency = match &entry.currency {
+                Some(currency) => currency,
+                None => {
+                    if entry.currency_amount.is_some() || entry.exchange_rate.is_some() {
+                        return Err(anyhow::anyhow!(
+                            "Journal entry {}: currency amount and exchange rate require a currency",
+                            index + 1
+                        ));
+                    }
+                    continue;
+                }
+            };
+            crate::models::validate_currency_code(currency)?;
+
+            let currency_amount = entry.currency_amount.ok_or_else(|| {
+                anyhow::anyhow!("Journal entry {}: {} line needs a currency amount", index + 1, currency)
+            })?;
+            let exchange_rate = entry.exchange_rate.ok_or_else(|| {
+                anyhow::anyhow!("Journal entry {}: no exchange rate for {}", index + 1, currency)
+            })?;
+            if currency_amount <= Decimal::ZERO || exchange_rate <= Decimal::ZERO {
+                return Err(anyhow::anyhow!(
+                    "Journal entry {}: currency amount and exchange rate must be positive",
+                    index + 1
+                ));
+            }
+            crate::models::to_minor_units(currency_amount)?;
+
+            let functional_amount = entry.debit_amount.or(entry.credit_amount).unwrap_or(Decimal::ZERO);
+            let expected = crate::models::convert_to_functional(currency_amount, exchange_rate);
+            if (functional_amount - expected).abs() > crate::models::CONVERSION_TOLERANCE {
+                return Err(anyhow::anyhow!(
+                    "Journal entry {}: {} {} at rate {} is {} in the functional currency, not {}",
+                    index + 1, currency_amount, currency, exchange_rate, expected, functional_amount
+                ));
+            }
+
+            if entry.debit_amount.is_some() {
+                currency_debits += currency_amount;
+            } else {
+                currency_credits += currency_amount;
+            }
+        }
+
+        // Per-currency balance only applies when all lines share one foreign currency
+        let first_currency = self.journal_entries.first().and_then(|entry| entry.currency.as_ref());
+        let single_currency = first_currency.is_some()
+            && self.journal_entries.iter().all(|entry| entry.currency.as_ref() == first_currency);
+        if single_currency && currency_debits != currency_credits {
+            return Err(anyhow::anyhow!(
+                "Transaction does not balance in {}: debits ({}) != credits ({})",
+                first_currency.map(String::as_str).unwrap_or_default(), currency_debits, currency_credits
+            ));
+        }
+
         Ok(())
     }
 
@@ -111,12 +266,57 @@ impl CreateTransactionRequest {
     }
 }
 
+impl UpdateTransactionRequest {
+    /// Merge this update over an existing transaction, producing the full request to validate and store
+    pub fn apply_to(&self, existing: &TransactionWithEntries) -> CreateTransactionRequest {
+        let journal_entries = match &self.journal_entries {
+            Some(entries) => entries.clone(),
+            None => existing.journal_entries.iter().map(JournalEntryWithAccount::to_request).collect(),
+        };
+
+        CreateTransactionRequest {
+            description: self.description.clone().unwrap_or_else(|| existing.transaction.description.clone()),
+            reference: self.reference.clone().or_else(|| existing.transaction.reference.clone()),
+            transaction_date: self.transaction_date.unwrap_or(existing.transaction.transaction_date),
+            status: Some(existing.transaction.status),
+            journal_entries,
+        }
+    }
+}
+
 impl TransactionWithEntries {
     /// Check if the transaction is balanced
     pub fn is_balanced(&self) -> bool {
         self.total_debits == self.total_credits
     }
 
+    /// Build the mirror transaction that cancels this one, swapping debits and credits on every line
+    pub fn reversal_request(&self, request: &ReverseTransactionRequest) -> CreateTransactionRequest {
+        let journal_entries = self
+            .journal_entries
+            .iter()
+            .map(|entry| {
+                let line = entry.to_request();
+                CreateJournalEntryRequest {
+                    debit_amount: line.credit_amount,
+                    credit_amount: line.debit_amount,
+                    ..line
+                }
+            })
+            .collect();
+
+        CreateTransactionRequest {
+            description: request
+                .description
+                .clone()
+                .unwrap_or_else(|| format!("Reversal of: {}", self.transaction.description)),
+            reference: request.reference.clone().or_else(|| self.transaction.reference.clone()),
+            transaction_date: request.transaction_date,
+            status: Some(TransactionStatus::Posted),
+            journal_entries,
+        }
+    }
+
     /// Get the net amount of the transaction
     pub fn net_amount(&self) -> Decimal {
         self.total_debits.max(self.total_credits)
@@ -136,6 +336,7 @@ pub struct TransactionFilter {
     pub end_date: Option<NaiveDate>,
     pub account_id: Option<i64>,
     pub description_contains: Option<String>,
+    pub status: Option<TransactionStatus>,
     pub min_amount: Option<Decimal>,
     pub max_amount: Option<Decimal>,
     pub limit: Option<i64>,
@@ -149,6 +350,7 @@ impl Default for TransactionFilter {
             end_date: None,
             account_id: None,
             description_contains: None,
+            status: None,
             min_amount: None,
             max_amount: None,
             limit: Some(50),
@@ -158,8 +360,8 @@ impl Default for TransactionFilter {
 }
 impl CreateJournalEntryRequest {
     pub fn validate(&self) -> anyhow::Result<()> {
-        let debit_zero = self.debit_amount.map_or(true, |d| d.is_zero());
-        let credit_zero = self.credit_amount.map_or(true, |c| c.is_zero());
+        let debit_zero = self.debit_amount.is_none_or(|d| d.is_zero());
+        let credit_zero = self.credit_amount.is_none_or(|c| c.is_zero());
         
         if debit_zero && credit_zero {
             return Err(anyhow::anyhow!("Either debit or credit amount must be non-zero"));
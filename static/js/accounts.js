@@ -1,4 +1,9 @@
 // Accounts page functionality
+
+// Books shown on this page: the company in ?company=, or the default company
+const companyId = new URLSearchParams(window.location.search).get('company') || 1;
+const apiBase = `/api/companies/${companyId}`;
+
 let accounts = [];
 
 document.addEventListener('DOMContentLoaded', function() {
@@ -8,7 +13,7 @@ document.addEventListener('DOMContentLoaded', function() {
 
 async function loadAccounts() {
     try {
-        const response = await fetch('/api/accounts');
+        const response = await fetch(`${apiBase}/accounts`);
         accounts = await response.json();
         displayAccounts(accounts);
     } catch (error) {
@@ -111,7 +116,7 @@ function setupNewAccountForm() {
         };
 
         try {
-            const response = await fetch('/api/accounts', {
+            const response = await fetch(`${apiBase}/accounts`, {
                 method: 'POST',
                 headers: {
                     'Content-Type': 'application/json',
@@ -144,7 +149,7 @@ async function editAccount(accountId) {
         const newName = prompt('Enter new account name:', account.name);
         if (newName && newName !== account.name) {
             try {
-                const response = await fetch(`/api/accounts/${accountId}`, {
+                const response = await fetch(`${apiBase}/accounts/${accountId}`, {
                     method: 'PUT',
                     headers: {
                         'Content-Type': 'application/json',
@@ -174,7 +179,7 @@ async function deleteAccount(accountId) {
     const account = accounts.find(a => a.id === accountId);
     if (account && confirm(`Are you sure you want to delete account "${account.name}"?`)) {
         try {
-            const response = await fetch(`/api/accounts/${accountId}`, {
+            const response = await fetch(`${apiBase}/accounts/${accountId}`, {
                 method: 'DELETE'
             });
 
@@ -1,4 +1,9 @@
 // Dashboard functionality
+
+// Books shown on this page: the company in ?company=, or the default company
+const companyId = new URLSearchParams(window.location.search).get('company') || 1;
+const apiBase = `/api/companies/${companyId}`;
+
 document.addEventListener('DOMContentLoaded', function() {
     loadAccountSummary();
     loadRecentTransactions();
@@ -7,7 +12,7 @@ document.addEventListener('DOMContentLoaded', function() {
 
 async function loadAccountSummary() {
     try {
-        const response = await fetch('/api/reports/summary');
+        const response = await fetch(`${apiBase}/reports/summary`);
         const summary = await response.json();
         
         const summaryHtml = `
@@ -43,7 +48,7 @@ async function loadAccountSummary() {
 
 async function loadRecentTransactions() {
     try {
-        const response = await fetch('/api/transactions?limit=5');
+        const response = await fetch(`${apiBase}/transactions?limit=5`);
         const transactions = await response.json();
         
         if (transactions.length === 0) {
@@ -79,7 +84,7 @@ async function loadRecentTransactions() {
 
 async function checkBalanceStatus() {
     try {
-        const response = await fetch('/api/reports/trial-balance');
+        const response = await fetch(`${apiBase}/reports/trial-balance`);
         const trialBalance = await response.json();
         
         const statusElement = document.getElementById('balance-status');
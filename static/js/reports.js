@@ -1,4 +1,9 @@
 // Reports page functionality
+
+// Books shown on this page: the company in ?company=, or the default company
+const companyId = new URLSearchParams(window.location.search).get('company') || 1;
+const apiBase = `/api/companies/${companyId}`;
+
 document.addEventListener('DOMContentLoaded', function() {
     // Reports page is ready
 });
@@ -7,7 +12,7 @@ async function loadTrialBalance() {
     showReportContent('Trial Balance');
     
     try {
-        const response = await fetch('/api/reports/trial-balance');
+        const response = await fetch(`${apiBase}/reports/trial-balance`);
         const trialBalance = await response.json();
         
         const reportHtml = `
@@ -73,7 +78,7 @@ async function loadBalanceSheet() {
     showReportContent('Balance Sheet');
     
     try {
-        const response = await fetch('/api/reports/balance-sheet');
+        const response = await fetch(`${apiBase}/reports/balance-sheet`);
         const balanceSheet = await response.json();
         
         const reportHtml = `
@@ -169,7 +174,7 @@ async function loadIncomeStatement() {
     showReportContent('Income Statement');
     
     try {
-        const response = await fetch('/api/reports/income-statement');
+        const response = await fetch(`${apiBase}/reports/income-statement`);
         const incomeStatement = await response.json();
         
         const reportHtml = `
@@ -239,7 +244,7 @@ async function loadAccountSummary() {
     showReportContent('Account Summary');
     
     try {
-        const response = await fetch('/api/reports/summary');
+        const response = await fetch(`${apiBase}/reports/summary`);
         const summary = await response.json();
         
         const reportHtml = `
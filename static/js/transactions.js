@@ -1,4 +1,9 @@
 // Transactions page functionality
+
+// Books shown on this page: the company in ?company=, or the default company
+const companyId = new URLSearchParams(window.location.search).get('company') || 1;
+const apiBase = `/api/companies/${companyId}`;
+
 let transactions = [];
 let accounts = [];
 let journalEntryCount = 0;
@@ -14,7 +19,7 @@ document.addEventListener('DOMContentLoaded', function() {
 
 async function loadTransactions() {
     try {
-        const response = await fetch('/api/transactions');
+        const response = await fetch(`${apiBase}/transactions`);
         transactions = await response.json();
         displayTransactions(transactions);
     } catch (error) {
@@ -26,7 +31,7 @@ async function loadTransactions() {
 
 async function loadAccounts() {
     try {
-        const response = await fetch('/api/accounts');
+        const response = await fetch(`${apiBase}/accounts`);
         accounts = await response.json();
     } catch (error) {
         console.error('Error loading accounts:', error);
@@ -249,7 +254,7 @@ function setupNewTransactionForm() {
         };
 
         try {
-            const response = await fetch('/api/transactions', {
+            const response = await fetch(`${apiBase}/transactions`, {
                 method: 'POST',
                 headers: {
                     'Content-Type': 'application/json',
@@ -276,7 +281,7 @@ function setupNewTransactionForm() {
 
 async function viewTransaction(transactionId) {
     try {
-        const response = await fetch(`/api/transactions/${transactionId}`);
+        const response = await fetch(`${apiBase}/transactions/${transactionId}`);
         const transaction = await response.json();
         
         let entriesHtml = '';
@@ -1,11 +1,16 @@
 // Trial Balance page functionality
+
+// Books shown on this page: the company in ?company=, or the default company
+const companyId = new URLSearchParams(window.location.search).get('company') || 1;
+const apiBase = `/api/companies/${companyId}`;
+
 document.addEventListener('DOMContentLoaded', function() {
     loadTrialBalance();
 });
 
 async function loadTrialBalance() {
     try {
-        const response = await fetch('/api/reports/trial-balance');
+        const response = await fetch(`${apiBase}/reports/trial-balance`);
         const trialBalance = await response.json();
         displayTrialBalance(trialBalance);
     } catch (error) {